@@ -0,0 +1,543 @@
+//! Rewrites logic networks from files with the rules and extraction of *eggmock*.
+//!
+//! Run `eggmock --help` for the available options.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use eggmock::egg::{AstSize, CostFunction, EGraph, Id as EggId, Rewrite, Runner, StopReason};
+use eggmock::{
+    convert_network, extract_restricted, json_string, mixed_rules, read_aiger, read_blif,
+    read_rules, read_verilog, run_worker, simulate, write_aiger, write_binary, write_blif, Aig, Aoig,
+    DepthView, Mig, Mixed, MixedLanguage, MutableNetwork, Network, NetworkLanguage, Node, Receiver,
+    Rewriter, TopologicalView, Xag, Xmg,
+};
+
+const USAGE: &str = "\
+Usage: eggmock [OPTIONS] <INPUT> -o <OUTPUT>
+       eggmock worker [OPTIONS]
+
+Reads the logic network in INPUT (.aig, .aag, .blif or .v), converts it to the chosen network type,
+rewrites it with an e-graph and writes the result to OUTPUT (.aig, .blif or .egmk).
+
+As a worker, reads a network of the chosen type in the .egmk format from the standard input,
+rewrites it in the same way and writes the result to the standard output. This is the worker
+process of an IsolatedRewriter; -o, --verify, --random-verify, --no-verify and --stats are ignored.
+
+The result is checked for equivalence with the input by simulating all input assignments, which is
+supported for networks with at most 20 inputs and registers. Larger networks are rejected unless
+they may be checked with random patterns only (--random-verify) or not at all (--no-verify).
+
+Options:
+  -o, --output <FILE>      the file to write the rewritten network to
+  -n, --network <TYPE>     the network type of the result: aig, mig, xag, xmg or aoig [default: aig]
+  -r, --rules <SET>        the rules to rewrite with: mixed or none [default: mixed]
+      --rule-file <FILE>   additionally rewrite with the rules in FILE, with the gates and, or, xor
+                           and maj; may be given multiple times
+  -c, --cost <COST>        the cost to minimize: gates, size (gates and inverters) or depth
+                           [default: gates]
+      --iter-limit <N>     the maximum number of rewrite iterations [default: 30]
+      --node-limit <N>     the maximum number of e-nodes [default: 10000]
+      --time-limit <SECS>  the maximum time for rewriting in seconds [default: 5]
+      --verify             fail if the equivalence of the result cannot be proven [default]
+      --random-verify      check networks with more than 20 inputs and registers only with random
+                           patterns, which may miss differences
+      --no-verify          skip checking the result for equivalence with the input
+      --stats <FILE>       write statistics as JSON to FILE or to the standard output for `-`
+  -h, --help               print this help
+";
+
+struct Options {
+    worker: bool,
+    input: String,
+    output: String,
+    network: String,
+    rules: String,
+    rule_files: Vec<String>,
+    cost: String,
+    iter_limit: usize,
+    node_limit: usize,
+    time_limit: Duration,
+    verify: bool,
+    random_verify: bool,
+    stats: Option<String>,
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1).peekable();
+    let worker = args.next_if(|arg| arg == "worker").is_some();
+    let options = match parse_options(args, worker) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("eggmock: {error}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("eggmock: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_options(
+    mut args: impl Iterator<Item = String>,
+    worker: bool,
+) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        worker,
+        input: String::new(),
+        output: String::new(),
+        network: "aig".to_string(),
+        rules: "mixed".to_string(),
+        rule_files: Vec::new(),
+        cost: "gates".to_string(),
+        iter_limit: 30,
+        node_limit: 10_000,
+        time_limit: Duration::from_secs(5),
+        verify: true,
+        random_verify: false,
+        stats: None,
+    };
+    let mut input = None;
+    let mut output = None;
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name.to_string(), Some(value)),
+            _ => (arg.clone(), None),
+        };
+        let mut value = || match inline_value {
+            Some(value) => Ok(value.to_string()),
+            None => args
+                .next()
+                .ok_or_else(|| format!("missing value for `{name}`")),
+        };
+        let number = |value: String| {
+            value
+                .parse()
+                .map_err(|_| format!("invalid value `{value}` for `{name}`"))
+        };
+        match name.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-o" | "--output" => output = Some(value()?),
+            "-n" | "--network" => options.network = value()?,
+            "-r" | "--rules" => options.rules = value()?,
+            "--rule-file" => options.rule_files.push(value()?),
+            "-c" | "--cost" => options.cost = value()?,
+            "--iter-limit" => options.iter_limit = number(value()?)?,
+            "--node-limit" => options.node_limit = number(value()?)?,
+            "--time-limit" => {
+                let limit = value()?;
+                options.time_limit = limit
+                    .parse()
+                    .ok()
+                    .and_then(|limit| Duration::try_from_secs_f64(limit).ok())
+                    .ok_or_else(|| format!("invalid value `{limit}` for `{name}`"))?;
+            }
+            "--verify" => (options.verify, options.random_verify) = (true, false),
+            "--random-verify" => (options.verify, options.random_verify) = (true, true),
+            "--no-verify" => (options.verify, options.random_verify) = (false, false),
+            "--stats" => options.stats = Some(value()?),
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("unknown option `{arg}`"))
+            }
+            _ if input.is_none() => input = Some(arg),
+            _ => return Err(format!("unexpected argument `{arg}`")),
+        }
+    }
+    let choices = [
+        (
+            "network",
+            &options.network,
+            &["aig", "mig", "xag", "xmg", "aoig"][..],
+        ),
+        ("rules", &options.rules, &["mixed", "none"]),
+        ("cost", &options.cost, &["gates", "size", "depth"]),
+    ];
+    for (option, value, allowed) in choices {
+        if !allowed.contains(&value.as_str()) {
+            return Err(format!("invalid value `{value}` for `--{option}`"));
+        }
+    }
+    if worker {
+        return match input {
+            Some(input) => Err(format!("unexpected argument `{input}` for the worker")),
+            None => Ok(Some(options)),
+        };
+    }
+    options.input = input.ok_or("missing input file")?;
+    options.output = output.ok_or("missing output file")?;
+    if !["aig", "aag", "blif", "v"].contains(&extension(&options.input)) {
+        return Err(format!("unknown input format of {}", options.input));
+    }
+    if !["aig", "blif", "egmk"].contains(&extension(&options.output)) {
+        return Err(format!("unknown output format of {}", options.output));
+    }
+    Ok(Some(options))
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    match options.network.as_str() {
+        "aig" => run_with::<Aig>(options),
+        "mig" => run_with::<Mig>(options),
+        "xag" => run_with::<Xag>(options),
+        "xmg" => run_with::<Xmg>(options),
+        "aoig" => run_with::<Aoig>(options),
+        _ => unreachable!("network types are checked when parsing the options"),
+    }
+}
+
+fn run_with<N: Node>(options: &Options) -> Result<(), String> {
+    if !options.worker {
+        return rewrite::<N>(options);
+    }
+    let worker = Worker::<N> {
+        options,
+        rules: rules(options)?,
+        _node: PhantomData,
+    };
+    run_worker(worker, io::stdin().lock(), io::stdout().lock()).map_err(|error| error.to_string())
+}
+
+/// Statistics about a network.
+struct Sizes {
+    inputs: u64,
+    outputs: usize,
+    registers: u64,
+    gates: usize,
+    depth: u32,
+}
+
+impl Sizes {
+    fn of(network: &impl Network) -> Self {
+        let order = TopologicalView::new(network);
+        let gates = order
+            .order()
+            .iter()
+            .filter(|id| !network.node(**id).is_leaf());
+        Self {
+            inputs: network.num_inputs(),
+            outputs: network.outputs().count(),
+            registers: network.num_registers(),
+            gates: gates.count(),
+            depth: DepthView::new(network).depth(),
+        }
+    }
+
+    fn json(&self) -> String {
+        format!(
+            "{{ \"inputs\": {}, \"outputs\": {}, \"registers\": {}, \"gates\": {}, \"depth\": {} }}",
+            self.inputs, self.outputs, self.registers, self.gates, self.depth
+        )
+    }
+}
+
+fn rewrite<N: Node>(options: &Options) -> Result<(), String> {
+    let start = Instant::now();
+    let input = read(&options.input)?;
+    let original: MutableNetwork<N> = convert_network(&input);
+    let read_time = start.elapsed();
+
+    let Rewritten {
+        network: rewritten,
+        runner,
+        rewrite_time,
+        extract_time,
+    } = saturate(&original, &rules(options)?, options);
+
+    let verify_start = Instant::now();
+    let verification = match options.verify {
+        true => verify(&input, &rewritten, options.random_verify)?,
+        false => "skipped",
+    };
+    let verify_time = verify_start.elapsed();
+
+    write(&options.output, &rewritten)?;
+
+    let before = Sizes::of(&original);
+    let after = Sizes::of(&rewritten);
+    let stop_reason = match &runner.stop_reason {
+        Some(StopReason::Saturated) => "saturated".to_string(),
+        Some(StopReason::IterationLimit(_)) => "iteration_limit".to_string(),
+        Some(StopReason::NodeLimit(_)) => "node_limit".to_string(),
+        Some(StopReason::TimeLimit(_)) => "time_limit".to_string(),
+        Some(StopReason::Other(reason)) => reason.clone(),
+        None => "none".to_string(),
+    };
+    let Some(stats) = &options.stats else {
+        eprintln!(
+            "{}: {} gates, depth {} -> {}: {} gates, depth {} (stopped after \
+             {} iteration(s): {stop_reason}, verification: {verification})",
+            options.input,
+            before.gates,
+            before.depth,
+            options.output,
+            after.gates,
+            after.depth,
+            runner.iterations.len()
+        );
+        return Ok(());
+    };
+
+    let mut json = String::from("{\n");
+    let mut field = |name: &str, value: String| writeln!(json, "  \"{name}\": {value},").unwrap();
+    field("input", json_string(&options.input));
+    field("output", json_string(&options.output));
+    field("network", json_string(&options.network));
+    field("rules", json_string(&options.rules));
+    field("cost", json_string(&options.cost));
+    field("before", before.json());
+    field("after", after.json());
+    field(
+        "egraph",
+        format!(
+            "{{ \"nodes\": {}, \"classes\": {}, \"iterations\": {}, \"stop_reason\": {} }}",
+            runner.egraph.total_number_of_nodes(),
+            runner.egraph.number_of_classes(),
+            runner.iterations.len(),
+            json_string(&stop_reason)
+        ),
+    );
+    field("verification", json_string(verification));
+    json.push_str(&format!(
+        "  \"seconds\": {{ \"read\": {}, \"rewrite\": {}, \"extract\": {}, \"verify\": {}, \
+         \"total\": {} }}\n}}\n",
+        read_time.as_secs_f64(),
+        rewrite_time.as_secs_f64(),
+        extract_time.as_secs_f64(),
+        verify_time.as_secs_f64(),
+        start.elapsed().as_secs_f64()
+    ));
+    match stats.as_str() {
+        "-" => print!("{json}"),
+        path => fs::write(path, json).map_err(|error| format!("cannot write {path}: {error}"))?,
+    }
+    Ok(())
+}
+
+/// Loads the rule set and the rule files of the options.
+fn rules(options: &Options) -> Result<Vec<Rewrite<MixedLanguage, ()>>, String> {
+    let mut rules = match options.rules.as_str() {
+        "mixed" => mixed_rules(),
+        "none" => Vec::new(),
+        _ => unreachable!("rule sets are checked when parsing the options"),
+    };
+    for path in &options.rule_files {
+        let source =
+            fs::read_to_string(path).map_err(|error| format!("cannot read {path}: {error}"))?;
+        rules.extend(read_rules(&source).map_err(|error| format!("{path}: {error}"))?);
+    }
+    Ok(rules)
+}
+
+/// The result of [`saturate`].
+struct Rewritten<N: Node> {
+    network: MutableNetwork<N>,
+    runner: Runner<MixedLanguage, ()>,
+    rewrite_time: Duration,
+    extract_time: Duration,
+}
+
+/// Rewrites the network with the given rules within the limits of the options and extracts the
+/// result with the cost function of the options.
+fn saturate<N: Node>(
+    network: &MutableNetwork<N>,
+    rules: &[Rewrite<MixedLanguage, ()>],
+    options: &Options,
+) -> Rewritten<N> {
+    let rewrite_start = Instant::now();
+    let receiver = EGraph::<MixedLanguage, ()>::default()
+        .convert::<N>()
+        .collect_interface();
+    let ((egraph, roots), interface) = network.send(receiver);
+    let runner = Runner::default()
+        .with_iter_limit(options.iter_limit)
+        .with_node_limit(options.node_limit)
+        .with_time_limit(options.time_limit)
+        .with_egraph(egraph)
+        .run(rules);
+    let rewrite_time = rewrite_start.elapsed();
+
+    let extract_start = Instant::now();
+    let receiver = MutableNetwork::<N>::default().with_interface(interface);
+    let network = match options.cost.as_str() {
+        "gates" => extract_restricted(&runner.egraph, &roots, GateCount, receiver),
+        "size" => extract_restricted(&runner.egraph, &roots, AstSize, receiver),
+        "depth" => extract_restricted(&runner.egraph, &roots, GateDepth, receiver),
+        _ => unreachable!("cost functions are checked when parsing the options"),
+    };
+    Rewritten {
+        network,
+        runner,
+        rewrite_time,
+        extract_time: extract_start.elapsed(),
+    }
+}
+
+/// Rewrites the networks of an `IsolatedRewriter` like [`rewrite`], see `eggmock worker`.
+struct Worker<'a, N> {
+    options: &'a Options,
+    rules: Vec<Rewrite<MixedLanguage, ()>>,
+    _node: PhantomData<N>,
+}
+
+impl<N: Node> Rewriter for Worker<'_, N> {
+    type Node = N;
+    type Intermediate = MutableNetwork<N>;
+
+    fn create_receiver(
+        &mut self,
+    ) -> impl Receiver<Node = Self::Node, Result = Self::Intermediate> + 'static {
+        MutableNetwork::default()
+    }
+
+    fn rewrite(
+        self,
+        input: Self::Intermediate,
+        output: impl Receiver<Node = Self::Node, Result = ()>,
+    ) {
+        saturate(&input, &self.rules, self.options)
+            .network
+            .send(output)
+    }
+}
+
+fn extension(path: &str) -> &str {
+    Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+}
+
+/// Reads a network into a [`Mixed`] network, which keeps the AND, OR, XOR and majority gates of
+/// the file.
+fn read(path: &str) -> Result<MutableNetwork<Mixed>, String> {
+    let source = fs::read(path).map_err(|error| format!("cannot read {path}: {error}"))?;
+    let text = || String::from_utf8(source.clone()).map_err(|_| format!("{path} is not UTF-8"));
+    let receiver = MutableNetwork::default();
+    let result = match extension(path) {
+        "aig" | "aag" => read_aiger(receiver, &source).map_err(|error| error.to_string()),
+        "blif" => read_blif(receiver, &text()?).map_err(|error| error.to_string()),
+        "v" => read_verilog(receiver, &text()?).map_err(|error| error.to_string()),
+        _ => unreachable!("input formats are checked when parsing the options"),
+    };
+    result.map_err(|error| format!("{path}: {error}"))
+}
+
+fn write<N: Node>(path: &str, network: &MutableNetwork<N>) -> Result<(), String> {
+    let mut bytes = Vec::new();
+    let written = match extension(path) {
+        "aig" => write_aiger(&convert_network::<Aig, _>(network), &mut bytes),
+        "blif" => {
+            let model = Path::new(path).file_stem().unwrap_or_default();
+            write_blif(network, &model.to_string_lossy(), &mut bytes)
+        }
+        "egmk" => write_binary(network, &mut bytes),
+        _ => unreachable!("output formats are checked when parsing the options"),
+    };
+    written.map_err(|error| format!("cannot write {path}: {error}"))?;
+    fs::write(path, bytes).map_err(|error| format!("cannot write {path}: {error}"))
+}
+
+/// The largest number of inputs and registers for which [`verify`] simulates all assignments.
+const EXHAUSTIVE_INPUTS: usize = 20;
+
+/// Checks that both networks compute the same functions. Networks with up to
+/// [`EXHAUSTIVE_INPUTS`] inputs and registers are simulated with all assignments, which proves
+/// their equivalence. Larger ones are only simulated with random patterns if `random` is set and
+/// rejected otherwise. Returns the kind of check.
+fn verify(
+    expected: &impl Network,
+    actual: &impl Network,
+    random: bool,
+) -> Result<&'static str, String> {
+    let num_inputs = (expected.num_inputs() + expected.num_registers()) as usize;
+    let check = |inputs: &[u64]| match simulate(expected, 0, inputs) == simulate(actual, 0, inputs) {
+        true => Ok(()),
+        false => Err("the rewritten network is not equivalent to the input".to_string()),
+    };
+    if num_inputs <= EXHAUSTIVE_INPUTS {
+        // the assignments of each round, with the first six inputs varying within the words
+        const MASKS: [u64; 6] = [
+            0xaaaa_aaaa_aaaa_aaaa,
+            0xcccc_cccc_cccc_cccc,
+            0xf0f0_f0f0_f0f0_f0f0,
+            0xff00_ff00_ff00_ff00,
+            0xffff_0000_ffff_0000,
+            0xffff_ffff_0000_0000,
+        ];
+        for round in 0..(1usize << num_inputs).div_ceil(64) {
+            let inputs = Vec::from_iter((0..num_inputs).map(|input| match MASKS.get(input) {
+                Some(mask) => *mask,
+                None => 0u64.wrapping_sub((round >> (input - 6) & 1) as u64),
+            }));
+            check(&inputs)?;
+        }
+        return Ok("exhaustive");
+    }
+    if !random {
+        return Err(format!(
+            "cannot prove the equivalence of networks with {num_inputs} inputs and registers, \
+             at most {EXHAUSTIVE_INPUTS} are supported; use `--random-verify` to check the \
+             result with random patterns or `--no-verify` to skip the check"
+        ));
+    }
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut random = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    for _ in 0..64 {
+        check(&Vec::from_iter((0..num_inputs).map(|_| random())))?;
+    }
+    Ok("simulated_random")
+}
+
+/// Counts the gates of a network, i.e. all e-nodes but inverters and leaves.
+struct GateCount;
+
+impl<L: NetworkLanguage> CostFunction<L> for GateCount {
+    type Cost = usize;
+
+    fn cost<C>(&mut self, enode: &L, mut costs: C) -> usize
+    where
+        C: FnMut(EggId) -> usize,
+    {
+        let gate = !enode.is_not() && !enode.is_leaf();
+        enode
+            .children()
+            .iter()
+            .map(|child| costs(*child))
+            .sum::<usize>()
+            + gate as usize
+    }
+}
+
+/// The number of gates on the longest path of a network, ignoring inverters.
+struct GateDepth;
+
+impl<L: NetworkLanguage> CostFunction<L> for GateDepth {
+    type Cost = usize;
+
+    fn cost<C>(&mut self, enode: &L, mut costs: C) -> usize
+    where
+        C: FnMut(EggId) -> usize,
+    {
+        let depth = enode.children().iter().map(|child| costs(*child)).max();
+        let gate = !enode.is_not() && !enode.is_leaf();
+        depth.unwrap_or(0) + gate as usize
+    }
+}
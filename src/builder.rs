@@ -0,0 +1,224 @@
+use crate::{GateFunction, Node, Receiver, Signal};
+
+/// Builds a network gate by gate and passes it to a [`Receiver`].
+///
+/// Gates are created with their native gate type if the network has one (see
+/// [`Node::from_function`]) and are otherwise derived from the available gates using inversions,
+/// e.g. an OR is built as an inverted AND with inverted inputs in AIGs and an AND as a majority
+/// with a constant-false input in MIGs.
+///
+/// # Example
+/// ```
+/// use eggmock::{Aig, MutableNetwork, Network, NetworkBuilder};
+///
+/// let mut ntk = NetworkBuilder::new(MutableNetwork::<Aig>::default());
+/// let [a, b, c] = [ntk.pi(), ntk.pi(), ntk.pi()];
+/// let carry = ntk.maj(a, b, c);
+/// let sum = ntk.xor(a, b);
+/// let sum = ntk.xor(sum, c);
+/// ntk.po(sum);
+/// ntk.po(!carry);
+/// let ntk = ntk.done();
+/// assert_eq!(ntk.num_inputs(), 3);
+/// assert_eq!(ntk.outputs().count(), 2);
+/// ```
+pub struct NetworkBuilder<R: Receiver> {
+    receiver: R,
+    num_inputs: u64,
+    constant: Option<Signal>,
+    outputs: Vec<Signal>,
+}
+
+impl<R: Receiver> NetworkBuilder<R> {
+    pub fn new(receiver: R) -> Self {
+        Self {
+            receiver,
+            num_inputs: 0,
+            constant: None,
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Creates a new primary input. The number of inputs is declared to the receiver before the
+    /// input is created.
+    pub fn pi(&mut self) -> Signal {
+        let index = self.num_inputs;
+        self.num_inputs += 1;
+        self.receiver.set_num_inputs(self.num_inputs);
+        self.receiver.create_node(R::Node::input(index))
+    }
+
+    /// Creates a new primary input with the given name.
+    pub fn named_pi(&mut self, name: &str) -> Signal {
+        let input = self.pi();
+        self.receiver.set_input_name(self.num_inputs - 1, name);
+        input
+    }
+
+    /// Creates the given number of primary inputs.
+    pub fn pis(&mut self, count: usize) -> Vec<Signal> {
+        Vec::from_iter((0..count).map(|_| self.pi()))
+    }
+
+    /// Returns the number of primary inputs created so far.
+    pub fn num_inputs(&self) -> u64 {
+        self.num_inputs
+    }
+
+    /// Returns a signal with the given constant value. The constant node is only created once.
+    pub fn constant(&mut self, value: bool) -> Signal {
+        let constant = match self.constant {
+            Some(constant) => constant,
+            None => {
+                let constant = self.receiver.create_node(R::Node::constant_false());
+                *self.constant.insert(constant)
+            }
+        };
+        constant.maybe_invert(value)
+    }
+
+    /// Passes the given node to the receiver.
+    pub fn create(&mut self, node: R::Node) -> Signal {
+        self.receiver.create_node(node)
+    }
+
+    /// Creates a native gate of the network that computes the given function of the inputs or
+    /// returns [`None`] if the network has no such gate.
+    pub fn gate(&mut self, function: GateFunction, inputs: &[Signal]) -> Option<Signal> {
+        let node = R::Node::from_function(function, inputs)?;
+        Some(self.create(node))
+    }
+
+    /// # Panics
+    /// Panics if the network has neither AND, OR nor majority gates.
+    pub fn and(&mut self, a: Signal, b: Signal) -> Signal {
+        if let Some(and) = self.gate(GateFunction::And, &[a, b]) {
+            return and;
+        }
+        if let Some(or) = self.gate(GateFunction::Or, &[!a, !b]) {
+            return !or;
+        }
+        let zero = self.constant(false);
+        self.gate(GateFunction::Maj, &[a, b, zero])
+            .expect("network should have AND, OR or majority gates")
+    }
+
+    pub fn or(&mut self, a: Signal, b: Signal) -> Signal {
+        match self.gate(GateFunction::Or, &[a, b]) {
+            Some(or) => or,
+            None => !self.and(!a, !b),
+        }
+    }
+
+    pub fn nand(&mut self, a: Signal, b: Signal) -> Signal {
+        !self.and(a, b)
+    }
+
+    pub fn nor(&mut self, a: Signal, b: Signal) -> Signal {
+        !self.or(a, b)
+    }
+
+    pub fn xor(&mut self, a: Signal, b: Signal) -> Signal {
+        if let Some(xor) = self.gate(GateFunction::Xor, &[a, b]) {
+            return xor;
+        }
+        let a_and_not_b = self.and(a, !b);
+        let not_a_and_b = self.and(!a, b);
+        self.or(a_and_not_b, not_a_and_b)
+    }
+
+    pub fn xnor(&mut self, a: Signal, b: Signal) -> Signal {
+        !self.xor(a, b)
+    }
+
+    /// Creates the majority of three signals.
+    pub fn maj(&mut self, a: Signal, b: Signal, c: Signal) -> Signal {
+        if let Some(maj) = self.gate(GateFunction::Maj, &[a, b, c]) {
+            return maj;
+        }
+        let a_and_b = self.and(a, b);
+        let a_or_b = self.or(a, b);
+        let c_and_a_or_b = self.and(c, a_or_b);
+        self.or(a_and_b, c_and_a_or_b)
+    }
+
+    /// Creates a multiplexer that returns `then` if `condition` is true and `otherwise` else.
+    pub fn mux(&mut self, condition: Signal, then: Signal, otherwise: Signal) -> Signal {
+        if let Some(ite) = self.gate(GateFunction::Ite, &[condition, then, otherwise]) {
+            return ite;
+        }
+        let then = self.and(condition, then);
+        let otherwise = self.and(!condition, otherwise);
+        self.or(then, otherwise)
+    }
+
+    /// Creates the conjunction of all given signals as a balanced tree. Returns constant true if
+    /// there are no signals.
+    pub fn and_all(&mut self, signals: &[Signal]) -> Signal {
+        self.reduce(signals, true, Self::and)
+    }
+
+    /// Creates the disjunction of all given signals as a balanced tree. Returns constant false if
+    /// there are no signals.
+    pub fn or_all(&mut self, signals: &[Signal]) -> Signal {
+        self.reduce(signals, false, Self::or)
+    }
+
+    /// Creates the parity of all given signals as a balanced tree. Returns constant false if there
+    /// are no signals.
+    pub fn xor_all(&mut self, signals: &[Signal]) -> Signal {
+        self.reduce(signals, false, Self::xor)
+    }
+
+    /// Adds a primary output and returns its position.
+    pub fn po(&mut self, signal: Signal) -> usize {
+        self.outputs.push(signal);
+        self.outputs.len() - 1
+    }
+
+    /// Adds a primary output with the given name and returns its position.
+    pub fn named_po(&mut self, signal: Signal, name: &str) -> usize {
+        let output = self.po(signal);
+        self.receiver.set_output_name(output, name);
+        output
+    }
+
+    /// Passes the primary outputs to the receiver and returns its result.
+    pub fn done(self) -> R::Result {
+        self.receiver.done(&self.outputs)
+    }
+
+    pub fn receiver(&self) -> &R {
+        &self.receiver
+    }
+
+    /// Returns the receiver, e.g. to declare the interface of the network. Creating primary
+    /// inputs or constants through it is not tracked by the builder.
+    pub fn receiver_mut(&mut self) -> &mut R {
+        &mut self.receiver
+    }
+
+    /// Returns the receiver without passing the primary outputs to it.
+    pub fn into_receiver(self) -> R {
+        self.receiver
+    }
+
+    fn reduce(
+        &mut self,
+        signals: &[Signal],
+        empty: bool,
+        mut op: impl FnMut(&mut Self, Signal, Signal) -> Signal,
+    ) -> Signal {
+        if signals.is_empty() {
+            return self.constant(empty);
+        }
+        let mut signals = signals.to_vec();
+        while signals.len() > 1 {
+            signals = Vec::from_iter(signals.chunks(2).map(|pair| match pair {
+                [a, b] => op(self, *a, *b),
+                _ => pair[0],
+            }));
+        }
+        signals[0]
+    }
+}
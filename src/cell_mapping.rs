@@ -0,0 +1,646 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use egg::{Analysis, EGraph, Searcher, Var};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{Cell, GateLibrary, Id, InterfaceNames, Klut, Network, NetworkLanguage, Node, Signal};
+
+/// The quantity that is minimised by [`map_cells`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum CellMappingObjective {
+    /// Minimise the area, then the delay.
+    #[default]
+    Area,
+    /// Minimise the delay, then the area.
+    Delay,
+}
+
+/// Parameters for [`map_cells`].
+#[derive(Debug, Clone)]
+pub struct CellMappingParams {
+    pub objective: CellMappingObjective,
+    /// The number of area recovery rounds for [`CellMappingObjective::Area`], in which the
+    /// matches are selected by their exact area.
+    pub area_rounds: usize,
+}
+
+impl Default for CellMappingParams {
+    fn default() -> Self {
+        Self {
+            objective: CellMappingObjective::default(),
+            area_rounds: 2,
+        }
+    }
+}
+
+/// Returned by [`map_cells`] if an e-class that is required for the outputs cannot be implemented
+/// with the cells of the library.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct UnmappedClassError {
+    pub class: egg::Id,
+}
+
+impl Display for UnmappedClassError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "e-class {} cannot be implemented with the cells of the library",
+            self.class
+        )
+    }
+}
+
+impl Error for UnmappedClassError {}
+
+/// A network of library cells that is the result of [`map_cells`].
+///
+/// Every gate is a [`Klut`] look-up table computing the function of the cell it is bound to (see
+/// [`cell`](Self::cell)), so the mapped network can be simulated and transferred like any other
+/// k-LUT network. Constants are represented by (the complement of) [`Klut::False`], which is not
+/// bound to a cell and is written as a tie-off by [`write_verilog`](crate::write_verilog).
+#[derive(Debug, Clone)]
+pub struct CellMapping<'l> {
+    library: &'l GateLibrary,
+    nodes: Vec<Klut>,
+    cells: Vec<Option<usize>>,
+    outputs: Vec<Signal>,
+    num_inputs: u64,
+    names: InterfaceNames,
+}
+
+impl<'l> CellMapping<'l> {
+    pub fn library(&self) -> &'l GateLibrary {
+        self.library
+    }
+
+    /// Returns the cell that implements the given node or [`None`] if it is a primary input or a
+    /// constant.
+    pub fn cell(&self, id: Id) -> Option<&'l Cell> {
+        let library = self.library;
+        self.cells[u32::from(id) as usize].map(|cell| &library.cells()[cell])
+    }
+
+    /// Returns the ids of the nodes in topological order.
+    pub fn ids(&self) -> impl Iterator<Item = Id> {
+        (0..self.nodes.len() as u32).map(Id::from)
+    }
+
+    /// Returns the number of cell instances.
+    pub fn num_cells(&self) -> usize {
+        self.cells.iter().flatten().count()
+    }
+
+    /// Returns the total area of the cell instances.
+    pub fn area(&self) -> f64 {
+        self.ids()
+            .filter_map(|id| self.cell(id))
+            .map(Cell::area)
+            .sum()
+    }
+
+    /// Returns the delay of the longest path from a primary input to a primary output, based on
+    /// the block delays of the cell pins.
+    pub fn delay(&self) -> f64 {
+        let mut arrival = vec![0.0f64; self.nodes.len()];
+        for id in self.ids() {
+            let Some(cell) = self.cell(id) else {
+                continue;
+            };
+            arrival[u32::from(id) as usize] = self.nodes[u32::from(id) as usize]
+                .inputs()
+                .iter()
+                .zip(cell.pins())
+                .map(|(input, pin)| arrival[u32::from(input.node_id()) as usize] + pin.delay(0.0))
+                .fold(0.0, f64::max);
+        }
+        self.outputs
+            .iter()
+            .map(|output| arrival[u32::from(output.node_id()) as usize])
+            .fold(0.0, f64::max)
+    }
+
+    pub fn names(&self) -> &InterfaceNames {
+        &self.names
+    }
+
+    /// Returns the names of the primary inputs and outputs, e.g. to name the ports written by
+    /// [`write_verilog`](crate::write_verilog).
+    pub fn names_mut(&mut self) -> &mut InterfaceNames {
+        &mut self.names
+    }
+}
+
+impl Network for CellMapping<'_> {
+    type Node = Klut;
+
+    fn outputs(&self) -> impl Iterator<Item = Signal> {
+        self.outputs.iter().copied()
+    }
+    fn node(&self, id: Id) -> Klut {
+        self.nodes[u32::from(id) as usize].clone()
+    }
+    fn num_inputs(&self) -> u64 {
+        self.num_inputs
+    }
+    fn input_name(&self, input: u64) -> Option<&str> {
+        self.names.input(input)
+    }
+    fn output_name(&self, output: usize) -> Option<&str> {
+        self.names.output(output)
+    }
+}
+
+/// Maps the given roots of a (saturated) e-graph to the cells of the library.
+///
+/// The [`patterns`](Cell::patterns) of all cells are matched against the e-graph, such that a
+/// cell may implement any of the equivalent structures represented by the e-graph. The delay of a
+/// class is the largest arrival time at the output of its cell, computed from the block delays of
+/// the pins.
+///
+/// For [`CellMappingObjective::Delay`], each e-class is implemented by the match with the smallest
+/// delay and, secondarily, the smallest area of its cone. For [`CellMappingObjective::Area`],
+/// these matches are refined by selecting the matches by their area flow, i.e. the area of the
+/// cone where shared logic is divided among its uses, which is then improved in
+/// [`area_rounds`](CellMappingParams::area_rounds) rounds of exact area recovery. Ties are broken
+/// by the delay. To keep the mapping acyclic, the refinement only selects matches whose inputs
+/// precede the e-class in a topological order of the previously selected matches.
+///
+/// Primary inputs and constants do not require cells. Returns an error if a class that is required
+/// by the roots cannot be implemented by the library, e.g. because the library has no inverter.
+///
+/// # Example
+/// ```
+/// use eggmock::egg::{EGraph, Runner};
+/// use eggmock::{majority, map_cells, mixed_rules, read_genlib, simulate_truth_tables};
+/// use eggmock::{Aig, CellMappingObjective, CellMappingParams, MixedLanguage, MutableNetwork};
+/// use eggmock::{Network, Receiver};
+///
+/// let library = read_genlib(
+///     "GATE inv 1 Y=!A; PIN * INV 1 999 1 0 1 0
+///      GATE nand2 2 Y=!(A*B); PIN * INV 1 999 1 0 1 0
+///      GATE nor2 2 Y=!(A+B); PIN * INV 1 999 1.4 0 1.4 0
+///      GATE aoi21 3 Y=!(A*B+C); PIN * INV 1 999 1.6 0 1.6 0
+///      GATE xor2 5 Y=A^B; PIN * UNKNOWN 1 999 1.9 0 1.9 0",
+/// )
+/// .unwrap();
+/// let network = majority(MutableNetwork::<Aig>::default(), 5);
+/// let receiver = EGraph::<MixedLanguage, ()>::default().convert::<Aig>();
+/// let (egraph, roots) = network.send(receiver);
+/// let runner = Runner::default()
+///     .with_iter_limit(5)
+///     .with_egraph(egraph)
+///     .run(&mixed_rules());
+///
+/// let map = |objective| {
+///     let params = CellMappingParams { objective, ..CellMappingParams::default() };
+///     map_cells(&runner.egraph, &roots, &library, &params).unwrap()
+/// };
+/// let area = map(CellMappingObjective::Area);
+/// let delay = map(CellMappingObjective::Delay);
+/// assert_eq!(simulate_truth_tables(&area), simulate_truth_tables(&network));
+/// assert_eq!(simulate_truth_tables(&delay), simulate_truth_tables(&network));
+/// assert!(area.area() <= delay.area());
+/// assert!(delay.delay() <= area.delay());
+///
+/// // every gate of the mapping is bound to a cell of the library
+/// let cells = area.ids().filter_map(|id| area.cell(id));
+/// assert_eq!(cells.count(), area.num_cells());
+/// ```
+pub fn map_cells<'l, L, A>(
+    egraph: &EGraph<L, A>,
+    roots: &[egg::Id],
+    library: &'l GateLibrary,
+    params: &CellMappingParams,
+) -> Result<CellMapping<'l>, UnmappedClassError>
+where
+    L: NetworkLanguage + egg::FromOp,
+    A: Analysis<L>,
+{
+    let mut mapper = Mapper {
+        egraph,
+        library,
+        objective: params.objective,
+        leaves: FxHashMap::default(),
+        matches: Vec::new(),
+        class_matches: FxHashMap::default(),
+        best: FxHashMap::default(),
+        refs: FxHashMap::default(),
+    };
+    mapper.find_leaves();
+    mapper.find_matches();
+    mapper.select();
+    if params.objective == CellMappingObjective::Area {
+        mapper.select_area_flow(roots);
+        for _ in 0..params.area_rounds {
+            mapper.recover_area(roots);
+        }
+    }
+    mapper.extract(roots)
+}
+
+/// An e-class that is implemented without a cell.
+#[derive(Debug, Copy, Clone)]
+enum Leaf {
+    Input(u64),
+    Const(bool),
+}
+
+/// A cell that implements an e-class with the given e-classes at its pins.
+struct Match {
+    class: egg::Id,
+    cell: usize,
+    inputs: Vec<egg::Id>,
+}
+
+/// The cost of implementing an e-class. The costs are compared lexicographically, where the
+/// primary and secondary cost are the area and the delay depending on the objective. As the
+/// number of cells strictly increases towards the outputs, the selected matches cannot form a
+/// cycle.
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+struct Cost {
+    primary: f64,
+    secondary: f64,
+    cells: usize,
+}
+
+struct Mapper<'a, 'l, L: NetworkLanguage, A: Analysis<L>> {
+    egraph: &'a EGraph<L, A>,
+    library: &'l GateLibrary,
+    objective: CellMappingObjective,
+    leaves: FxHashMap<egg::Id, Leaf>,
+    matches: Vec<Match>,
+    /// The indices of the matches of each e-class.
+    class_matches: FxHashMap<egg::Id, Vec<usize>>,
+    /// The index of the selected match of each e-class that can be implemented.
+    best: FxHashMap<egg::Id, usize>,
+    /// The number of references to each e-class in the current mapping.
+    refs: FxHashMap<egg::Id, u32>,
+}
+
+impl<'l, L, A> Mapper<'_, 'l, L, A>
+where
+    L: NetworkLanguage + egg::FromOp,
+    A: Analysis<L>,
+{
+    fn find_leaves(&mut self) {
+        let egraph = self.egraph;
+        for class in egraph.classes() {
+            for enode in &class.nodes {
+                let Some(node) = enode.to_node(|_| Signal::new(Id::from(0), false)) else {
+                    continue;
+                };
+                if let Some(input) = node.input_index() {
+                    self.leaves.insert(class.id, Leaf::Input(input));
+                } else if node.is_leaf() {
+                    self.leaves.entry(class.id).or_insert(Leaf::Const(false));
+                }
+            }
+        }
+        let falses = Vec::from_iter(
+            self.leaves
+                .iter()
+                .filter(|(_, leaf)| matches!(leaf, Leaf::Const(false)))
+                .map(|(class, _)| *class),
+        );
+        for class in egraph.classes() {
+            let inverts_false = class
+                .nodes
+                .iter()
+                .any(|enode| enode.is_not() && falses.contains(&egraph.find(enode.children()[0])));
+            if inverts_false {
+                self.leaves.entry(class.id).or_insert(Leaf::Const(true));
+            }
+        }
+    }
+
+    fn find_matches(&mut self) {
+        let egraph = self.egraph;
+        for (index, cell) in self.library.cells().iter().enumerate() {
+            // constants are tied off instead of being implemented by cells
+            if cell.num_inputs() == 0 {
+                continue;
+            }
+            let vars = Vec::from_iter(
+                (0..cell.num_inputs()).map(|pin| format!("?x{pin}").parse::<Var>().unwrap()),
+            );
+            for pattern in cell.patterns::<L>() {
+                for matches in pattern.search(egraph) {
+                    let class = egraph.find(matches.eclass);
+                    if self.leaves.contains_key(&class) {
+                        continue;
+                    }
+                    for subst in matches.substs {
+                        // pins that the pattern does not constrain cannot be connected
+                        let Some(inputs) = vars
+                            .iter()
+                            .map(|var| subst.get(*var).map(|id| egraph.find(*id)))
+                            .collect::<Option<Vec<_>>>()
+                        else {
+                            continue;
+                        };
+                        self.class_matches
+                            .entry(class)
+                            .or_default()
+                            .push(self.matches.len());
+                        self.matches.push(Match {
+                            class,
+                            cell: index,
+                            inputs,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    fn cost(&self, class: egg::Id, costs: &FxHashMap<egg::Id, Cost>) -> Option<Cost> {
+        if self.leaves.contains_key(&class) {
+            return Some(Cost {
+                primary: 0.0,
+                secondary: 0.0,
+                cells: 0,
+            });
+        }
+        costs.get(&class).copied()
+    }
+
+    fn match_cost(&self, m: &Match, costs: &FxHashMap<egg::Id, Cost>) -> Option<Cost> {
+        let cell = &self.library.cells()[m.cell];
+        let mut area = cell.area();
+        let mut delay = 0.0f64;
+        let mut cells = 1;
+        for (input, pin) in m.inputs.iter().zip(cell.pins()) {
+            let cost = self.cost(*input, costs)?;
+            let (input_area, input_delay) = match self.objective {
+                CellMappingObjective::Area => (cost.primary, cost.secondary),
+                CellMappingObjective::Delay => (cost.secondary, cost.primary),
+            };
+            area += input_area;
+            delay = delay.max(input_delay + pin.delay(0.0));
+            cells += cost.cells;
+        }
+        let (primary, secondary) = match self.objective {
+            CellMappingObjective::Area => (area, delay),
+            CellMappingObjective::Delay => (delay, area),
+        };
+        Some(Cost {
+            primary,
+            secondary,
+            cells,
+        })
+    }
+
+    /// Returns the arrival time at the output of the given match.
+    fn match_delay(&self, m: &Match, arrival: &FxHashMap<egg::Id, f64>) -> f64 {
+        let cell = &self.library.cells()[m.cell];
+        m.inputs
+            .iter()
+            .zip(cell.pins())
+            .map(|(input, pin)| arrival.get(input).copied().unwrap_or(0.0) + pin.delay(0.0))
+            .fold(0.0, f64::max)
+    }
+
+    /// Selects the cheapest match for each e-class until the costs do not improve anymore, where
+    /// the area of an e-class is the sum of the areas of the cells in its cone.
+    fn select(&mut self) {
+        let mut costs: FxHashMap<egg::Id, Cost> = FxHashMap::default();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for index in 0..self.matches.len() {
+                let m = &self.matches[index];
+                let Some(cost) = self.match_cost(m, &costs) else {
+                    continue;
+                };
+                let improves = match costs.get(&m.class) {
+                    None => true,
+                    Some(best) => cost < *best,
+                };
+                if improves {
+                    costs.insert(m.class, cost);
+                    self.best.insert(m.class, index);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    /// Returns the e-classes with a selected match in a topological order of the selected
+    /// matches, i.e. each e-class succeeds the inputs of its match.
+    fn order(&self) -> Vec<egg::Id> {
+        let mut classes = Vec::from_iter(self.best.keys().copied());
+        classes.sort_unstable();
+        let mut order = Vec::with_capacity(classes.len());
+        let mut visited = FxHashSet::default();
+        for class in classes {
+            let mut stack = vec![(class, false)];
+            while let Some((class, expanded)) = stack.pop() {
+                if expanded {
+                    order.push(class);
+                    continue;
+                }
+                let Some(index) = self.best.get(&class) else {
+                    continue;
+                };
+                if !visited.insert(class) {
+                    continue;
+                }
+                stack.push((class, true));
+                stack.extend(self.matches[*index].inputs.iter().map(|input| (*input, false)));
+            }
+        }
+        order
+    }
+
+    /// Returns the matches of the given e-class whose inputs precede it in the given order.
+    fn ordered_matches<'m>(
+        &'m self,
+        class: egg::Id,
+        position: &'m FxHashMap<egg::Id, usize>,
+    ) -> impl Iterator<Item = usize> + 'm {
+        let limit = position[&class];
+        self.class_matches[&class].iter().copied().filter(move |index| {
+            self.matches[*index].inputs.iter().all(|input| {
+                self.leaves.contains_key(input)
+                    || position.get(input).is_some_and(|position| *position < limit)
+            })
+        })
+    }
+
+    /// Counts the references of the e-classes in the current mapping.
+    fn compute_refs(&mut self, roots: &[egg::Id], order: &[egg::Id]) {
+        self.refs.clear();
+        for root in roots {
+            *self.refs.entry(self.egraph.find(*root)).or_default() += 1;
+        }
+        for class in order.iter().rev() {
+            if self.refs.get(class).copied().unwrap_or(0) == 0 {
+                continue;
+            }
+            for input in &self.matches[self.best[class]].inputs {
+                *self.refs.entry(*input).or_default() += 1;
+            }
+        }
+    }
+
+    /// Selects the match of each e-class with the smallest area flow, where the area of the cone
+    /// is divided by the number of references in the current mapping.
+    fn select_area_flow(&mut self, roots: &[egg::Id]) {
+        let order = self.order();
+        self.compute_refs(roots, &order);
+        let position = FxHashMap::from_iter(order.iter().enumerate().map(|(i, class)| (*class, i)));
+        let mut flow: FxHashMap<egg::Id, f64> = FxHashMap::default();
+        let mut arrival: FxHashMap<egg::Id, f64> = FxHashMap::default();
+        for class in order {
+            let fanouts = self.refs.get(&class).copied().unwrap_or(0).max(1) as f64;
+            let mut best = (f64::INFINITY, f64::INFINITY, self.best[&class]);
+            for index in self.ordered_matches(class, &position) {
+                let m = &self.matches[index];
+                let inputs: f64 = m
+                    .inputs
+                    .iter()
+                    .map(|input| flow.get(input).copied().unwrap_or(0.0))
+                    .sum();
+                let area = (self.library.cells()[m.cell].area() + inputs) / fanouts;
+                let delay = self.match_delay(m, &arrival);
+                if (area, delay) < (best.0, best.1) {
+                    best = (area, delay, index);
+                }
+            }
+            flow.insert(class, best.0);
+            arrival.insert(class, best.1);
+            self.best.insert(class, best.2);
+        }
+    }
+
+    /// Selects the match of each e-class that requires the least additional area given the
+    /// current mapping of the other e-classes.
+    fn recover_area(&mut self, roots: &[egg::Id]) {
+        let order = self.order();
+        self.compute_refs(roots, &order);
+        let position = FxHashMap::from_iter(order.iter().enumerate().map(|(i, class)| (*class, i)));
+        let mut arrival: FxHashMap<egg::Id, f64> = FxHashMap::default();
+        for class in order {
+            let mapped = self.refs.get(&class).copied().unwrap_or(0) > 0;
+            if mapped {
+                self.deref_match(self.best[&class]);
+            }
+            let mut best = (f64::INFINITY, f64::INFINITY, self.best[&class]);
+            for index in Vec::from_iter(self.ordered_matches(class, &position)) {
+                let area = self.ref_match(index);
+                self.deref_match(index);
+                let delay = self.match_delay(&self.matches[index], &arrival);
+                if (area, delay) < (best.0, best.1) {
+                    best = (area, delay, index);
+                }
+            }
+            arrival.insert(class, best.1);
+            self.best.insert(class, best.2);
+            if mapped {
+                self.ref_match(best.2);
+            }
+        }
+    }
+
+    /// References the inputs of the given match and returns the area of the cells that are added
+    /// to the mapping.
+    fn ref_match(&mut self, index: usize) -> f64 {
+        // an explicit stack, as the mapped cones may be arbitrarily deep
+        let mut stack = vec![index];
+        let mut area = 0.0;
+        while let Some(index) = stack.pop() {
+            let m = &self.matches[index];
+            area += self.library.cells()[m.cell].area();
+            for input in &m.inputs {
+                let refs = self.refs.entry(*input).or_default();
+                if *refs == 0 {
+                    stack.extend(self.best.get(input).copied());
+                }
+                *refs += 1;
+            }
+        }
+        area
+    }
+
+    /// Dereferences the inputs of the given match and returns the area of the cells that are
+    /// removed from the mapping.
+    fn deref_match(&mut self, index: usize) -> f64 {
+        let mut stack = vec![index];
+        let mut area = 0.0;
+        while let Some(index) = stack.pop() {
+            let m = &self.matches[index];
+            area += self.library.cells()[m.cell].area();
+            for input in &m.inputs {
+                let refs = self.refs.get_mut(input).unwrap();
+                *refs -= 1;
+                if *refs == 0 {
+                    stack.extend(self.best.get(input).copied());
+                }
+            }
+        }
+        area
+    }
+
+    fn extract(self, roots: &[egg::Id]) -> Result<CellMapping<'l>, UnmappedClassError> {
+        let mut mapping = CellMapping {
+            library: self.library,
+            nodes: Vec::new(),
+            cells: Vec::new(),
+            outputs: Vec::new(),
+            num_inputs: 0,
+            names: InterfaceNames::default(),
+        };
+        let mut signals: FxHashMap<egg::Id, Signal> = FxHashMap::default();
+        let mut constant = None;
+        for root in roots {
+            // post-order traversal of the selected matches
+            let mut stack = vec![(self.egraph.find(*root), false)];
+            while let Some((class, expanded)) = stack.pop() {
+                if signals.contains_key(&class) {
+                    continue;
+                }
+                if let Some(leaf) = self.leaves.get(&class) {
+                    let signal = match leaf {
+                        Leaf::Input(input) => {
+                            mapping.num_inputs = mapping.num_inputs.max(input + 1);
+                            mapping.push(Klut::Input(*input), None)
+                        }
+                        Leaf::Const(value) => {
+                            let id = *constant
+                                .get_or_insert_with(|| mapping.push(Klut::False, None).node_id());
+                            Signal::new(id, *value)
+                        }
+                    };
+                    signals.insert(class, signal);
+                    continue;
+                }
+                let Some(index) = self.best.get(&class) else {
+                    return Err(UnmappedClassError { class });
+                };
+                let m = &self.matches[*index];
+                if !expanded {
+                    stack.push((class, true));
+                    stack.extend(m.inputs.iter().map(|input| (*input, false)));
+                    continue;
+                }
+                let function = self.library.cells()[m.cell].function().clone();
+                let inputs = Vec::from_iter(m.inputs.iter().map(|input| signals[input]));
+                signals.insert(
+                    class,
+                    mapping.push(Klut::lut(function, inputs), Some(m.cell)),
+                );
+            }
+            mapping.outputs.push(signals[&self.egraph.find(*root)]);
+        }
+        Ok(mapping)
+    }
+}
+
+impl CellMapping<'_> {
+    fn push(&mut self, node: Klut, cell: Option<usize>) -> Signal {
+        self.nodes.push(node);
+        self.cells.push(cell);
+        Signal::new(Id::from(self.nodes.len() as u32 - 1), false)
+    }
+}
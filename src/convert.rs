@@ -0,0 +1,184 @@
+use std::marker::PhantomData;
+
+use crate::{
+    GateFunction, GateType, MutableNetwork, Network, NetworkBuilder, Node, Receiver, Register,
+    Signal, TruthTable,
+};
+
+/// A receiver that converts each received node into a small sub-network of another network type.
+/// Created by [`Receiver::decompose`] and [`Receiver::convert`].
+pub struct DecomposedReceiver<From, To: Receiver, F> {
+    _from: PhantomData<fn(From) -> ()>,
+    builder: NetworkBuilder<To>,
+    decomposer: F,
+}
+
+impl<From, To: Receiver, F> DecomposedReceiver<From, To, F> {
+    pub(crate) fn new(to: To, decomposer: F) -> Self {
+        Self {
+            _from: PhantomData,
+            builder: NetworkBuilder::new(to),
+            decomposer,
+        }
+    }
+}
+
+impl<From, To, F> Receiver for DecomposedReceiver<From, To, F>
+where
+    From: Node,
+    To: Receiver,
+    F: FnMut(&From, &mut NetworkBuilder<To>) -> Signal,
+{
+    type Node = From;
+    type Result = To::Result;
+
+    fn create_node(&mut self, node: From) -> Signal {
+        if let Some(input) = node.input_index() {
+            self.builder.create(To::Node::input(input))
+        } else if node.is_leaf() {
+            self.builder.constant(false)
+        } else {
+            (self.decomposer)(&node, &mut self.builder)
+        }
+    }
+
+    fn done(self, outputs: &[Signal]) -> To::Result {
+        self.builder.into_receiver().done(outputs)
+    }
+
+    fn set_num_inputs(&mut self, num_inputs: u64) {
+        self.builder.receiver_mut().set_num_inputs(num_inputs)
+    }
+
+    fn set_registers(&mut self, registers: &[Register]) {
+        self.builder.receiver_mut().set_registers(registers)
+    }
+
+    fn set_input_name(&mut self, input: u64, name: &str) {
+        self.builder.receiver_mut().set_input_name(input, name)
+    }
+
+    fn set_output_name(&mut self, output: usize, name: &str) {
+        self.builder.receiver_mut().set_output_name(output, name)
+    }
+
+    fn add_choice(&mut self, node: Signal, choice: Signal) {
+        self.builder.receiver_mut().add_choice(node, choice)
+    }
+}
+
+/// Builds a gate of any network type with the gates of the network of the given builder. The
+/// inputs of the gate have to be signals of the builder's network.
+///
+/// Gates with a known [`GateFunction`] are built with the corresponding operations of the
+/// [`NetworkBuilder`], which use native gates if possible. Other gates, such as the look-up tables
+/// of [`Klut`](crate::Klut) networks, are decomposed into multiplexers on their truth table.
+///
+/// # Panics
+/// Panics if the node is not a gate or its function is unknown.
+pub fn decompose_gate<N: Node, R: Receiver>(node: &N, builder: &mut NetworkBuilder<R>) -> Signal {
+    let inputs = node.inputs();
+    let function = node.gate_type().and_then(|gate| gate.function());
+    match (function, inputs) {
+        (Some(GateFunction::And), _) => builder.and_all(inputs),
+        (Some(GateFunction::Or), _) => builder.or_all(inputs),
+        (Some(GateFunction::Xor), _) => builder.xor_all(inputs),
+        (Some(GateFunction::Nand), _) => !builder.and_all(inputs),
+        (Some(GateFunction::Nor), _) => !builder.or_all(inputs),
+        (Some(GateFunction::Xnor), _) => !builder.xor_all(inputs),
+        (Some(GateFunction::Maj), [a, b, c]) => builder.maj(*a, *b, *c),
+        (Some(GateFunction::Ite), [i, t, e]) => builder.mux(*i, *t, *e),
+        _ => {
+            let num_vars = inputs.len() as u32;
+            let vars = Vec::from_iter((0..num_vars).map(|var| TruthTable::nth_var(num_vars, var)));
+            let function = node
+                .evaluate(&vars)
+                .expect("decomposed gates should have a known function");
+            decompose_function(&function, inputs, builder)
+        }
+    }
+}
+
+/// Builds the given function of the inputs by Shannon decomposition on the last variable it
+/// depends on.
+fn decompose_function<R: Receiver>(
+    function: &TruthTable,
+    inputs: &[Signal],
+    builder: &mut NetworkBuilder<R>,
+) -> Signal {
+    if function.is_const0() {
+        return builder.constant(false);
+    }
+    if function.is_const1() {
+        return builder.constant(true);
+    }
+    let var = (0..function.num_vars())
+        .rev()
+        .find(|var| function.has_var(*var))
+        .expect("non-constant functions should depend on a variable");
+    let input = inputs[var as usize];
+    let positive = function.cofactor(var, true);
+    let negative = function.cofactor(var, false);
+    match (
+        positive.is_const0() || positive.is_const1(),
+        negative.is_const0() || negative.is_const1(),
+    ) {
+        (true, true) => input.maybe_invert(positive.is_const0()),
+        (true, false) => {
+            let negative = decompose_function(&negative, inputs, builder);
+            match positive.is_const1() {
+                true => builder.or(input, negative),
+                false => builder.and(!input, negative),
+            }
+        }
+        (false, true) => {
+            let positive = decompose_function(&positive, inputs, builder);
+            match negative.is_const1() {
+                true => builder.or(!input, positive),
+                false => builder.and(input, positive),
+            }
+        }
+        (false, false) if positive == !negative.clone() => {
+            let negative = decompose_function(&negative, inputs, builder);
+            builder.xor(input, negative)
+        }
+        (false, false) => {
+            let positive = decompose_function(&positive, inputs, builder);
+            let negative = decompose_function(&negative, inputs, builder);
+            builder.mux(input, positive, negative)
+        }
+    }
+}
+
+/// Converts the given network into a network of another type by decomposing each gate with
+/// [`decompose_gate`]. The interface of the network is kept.
+///
+/// # Example
+/// ```
+/// use eggmock::{convert_network, ripple_carry_adder, simulate_truth_tables, wallace_multiplier};
+/// use eggmock::{Aig, Aoig, Mig, MutableNetwork, Xag, Xmg};
+///
+/// let xag = ripple_carry_adder(MutableNetwork::<Xag>::default(), 4);
+/// let expected = simulate_truth_tables(&xag);
+/// let aig: MutableNetwork<Aig> = convert_network(&xag);
+/// let xmg: MutableNetwork<Xmg> = convert_network(&xag);
+/// let mig: MutableNetwork<Mig> = convert_network(&aig);
+/// let aoig: MutableNetwork<Aoig> = convert_network(&aig);
+/// assert_eq!(simulate_truth_tables(&aig), expected);
+/// assert_eq!(simulate_truth_tables(&xmg), expected);
+/// assert_eq!(simulate_truth_tables(&mig), expected);
+/// assert_eq!(simulate_truth_tables(&aoig), expected);
+/// assert_eq!(simulate_truth_tables(&convert_network::<Aig, _>(&mig)), expected);
+/// assert_eq!(simulate_truth_tables(&convert_network::<Aig, _>(&aoig)), expected);
+///
+/// let xmg = wallace_multiplier(MutableNetwork::<Xmg>::default(), 3);
+/// let mig: MutableNetwork<Mig> = convert_network(&xmg);
+/// assert_eq!(simulate_truth_tables(&mig), simulate_truth_tables(&xmg));
+/// ```
+pub fn convert_network<To, N>(network: &N) -> MutableNetwork<To>
+where
+    To: Node,
+    N: Network + ?Sized,
+{
+    network.send(MutableNetwork::default().convert())
+}
@@ -0,0 +1,290 @@
+use rustc_hash::FxHashMap;
+
+use crate::{Id, Network, NetworkWithBackwardEdges, Node, Signal, TopologicalView, TruthTable};
+
+/// Parameters for [`enumerate_cuts`].
+#[derive(Debug, Clone)]
+pub struct CutEnumerationParams {
+    /// The maximum number of leaves of a cut.
+    pub cut_size: usize,
+    /// The maximum number of cuts that are kept for each node (not including the trivial cut).
+    pub cut_limit: usize,
+    /// Whether to compute the truth table of each cut.
+    pub compute_truth_tables: bool,
+}
+
+impl Default for CutEnumerationParams {
+    fn default() -> Self {
+        Self {
+            cut_size: 4,
+            cut_limit: 25,
+            compute_truth_tables: true,
+        }
+    }
+}
+
+/// A cut of a node, i.e. a set of leaves such that every path from a PI to the node passes through
+/// a leaf.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cut {
+    leaves: Vec<Id>,
+    signature: u64,
+    pub(crate) truth_table: Option<TruthTable>,
+}
+
+impl Cut {
+    pub(crate) fn new(leaves: Vec<Id>) -> Self {
+        let signature = leaves
+            .iter()
+            .fold(0, |sig, leaf| sig | 1 << (u32::from(*leaf) % 64));
+        Self {
+            leaves,
+            signature,
+            truth_table: None,
+        }
+    }
+
+    /// Returns the leaves of this cut in ascending order.
+    pub fn leaves(&self) -> &[Id] {
+        &self.leaves
+    }
+
+    pub fn size(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Returns the function of the node in terms of the leaves, where leaf `i` is variable `i`.
+    /// Only available if the truth tables were computed during enumeration.
+    pub fn truth_table(&self) -> Option<&TruthTable> {
+        self.truth_table.as_ref()
+    }
+
+    /// Returns true iff this cut dominates the given cut, i.e. its leaves are a subset of the leaves
+    /// of the other cut.
+    pub fn dominates(&self, other: &Cut) -> bool {
+        self.signature & other.signature == self.signature
+            && self.leaves.len() <= other.leaves.len()
+            && self
+                .leaves
+                .iter()
+                .all(|l| other.leaves.binary_search(l).is_ok())
+    }
+
+    /// Merges the leaves of both cuts. Returns [`None`] if the result would exceed `cut_size`.
+    pub(crate) fn merge(&self, other: &Cut, cut_size: usize) -> Option<Cut> {
+        if (self.signature | other.signature).count_ones() as usize > cut_size {
+            return None;
+        }
+        let mut leaves = Vec::with_capacity(cut_size);
+        let (mut i, mut j) = (0, 0);
+        while i < self.leaves.len() || j < other.leaves.len() {
+            let next = match (self.leaves.get(i), other.leaves.get(j)) {
+                (Some(a), Some(b)) if a == b => {
+                    i += 1;
+                    j += 1;
+                    *a
+                }
+                (Some(a), Some(b)) if a < b => {
+                    i += 1;
+                    *a
+                }
+                (Some(a), None) => {
+                    i += 1;
+                    *a
+                }
+                (_, Some(b)) => {
+                    j += 1;
+                    *b
+                }
+                (None, None) => unreachable!(),
+            };
+            if leaves.len() == cut_size {
+                return None;
+            }
+            leaves.push(next);
+        }
+        Some(Cut::new(leaves))
+    }
+}
+
+/// The cuts of all nodes of a network as computed by [`enumerate_cuts`].
+#[derive(Debug, Clone)]
+pub struct NetworkCuts {
+    cuts: FxHashMap<Id, Vec<Cut>>,
+}
+
+impl NetworkCuts {
+    /// Returns the cuts of the given node. The non-trivial cuts are ordered by priority and the
+    /// trivial cut (containing only the node itself) comes last. Constants only have the empty cut.
+    pub fn cuts(&self, id: Id) -> &[Cut] {
+        self.cuts.get(&id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Returns an iterator over all nodes and their cuts.
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &[Cut])> {
+        self.cuts.iter().map(|(id, cuts)| (*id, cuts.as_slice()))
+    }
+}
+
+/// Enumerates the k-feasible cuts of all nodes that are reachable from the outputs of the given
+/// network.
+///
+/// The nodes are processed in topological order, which is derived from the fanout information of
+/// the network. For each node, only the `cut_limit` cuts with the highest priority are kept: cuts
+/// with fewer leaves are preferred and ties are broken in favour of cuts whose leaves have more
+/// fanouts. Cuts that are dominated by another cut of the same node are removed.
+///
+/// # Example
+/// ```
+/// use eggmock::{cut_function, enumerate_cuts, ripple_carry_adder, simulate_truth_tables};
+/// use eggmock::{CutEnumerationParams, MutableNetwork, Network, Node, Xag};
+///
+/// let adder = ripple_carry_adder(MutableNetwork::<Xag>::default(), 2);
+/// let expected = simulate_truth_tables(&adder);
+/// let cuts = enumerate_cuts(&adder, &CutEnumerationParams::default());
+/// let mut checked = 0;
+/// for (output, function) in adder.outputs().zip(&expected) {
+///     let id = output.node_id();
+///     let node_cuts = cuts.cuts(id);
+///     assert_eq!(node_cuts.last().unwrap().leaves(), &[id]);
+///     for (index, cut) in node_cuts.iter().enumerate() {
+///         let truth_table = cut.truth_table().unwrap();
+///         assert_eq!(*truth_table, cut_function(&adder, id, cut.leaves()));
+///         // the cuts of a node do not dominate each other, but the trivial cut of a leaf
+///         // dominates every cut that contains the leaf
+///         for (other_index, other) in node_cuts.iter().enumerate() {
+///             assert_eq!(other.dominates(cut), index == other_index);
+///         }
+///         for leaf in cut.leaves() {
+///             assert!(cuts.cuts(*leaf).last().unwrap().dominates(cut));
+///         }
+///
+///         // cuts on the primary inputs compute the function of the output
+///         let inputs = cut.leaves().iter().map(|leaf| adder.node(*leaf).input_index());
+///         let Some(mapping) = inputs.map(|input| Some(input? as u32)).collect::<Option<Vec<_>>>()
+///         else {
+///             continue;
+///         };
+///         let function_of_node = truth_table.remap(4, &mapping);
+///         match output.is_inverted() {
+///             true => assert_eq!(!function_of_node, *function),
+///             false => assert_eq!(function_of_node, *function),
+///         }
+///         checked += 1;
+///     }
+/// }
+/// assert!(checked >= 3);
+/// ```
+pub fn enumerate_cuts<N>(network: &N, params: &CutEnumerationParams) -> NetworkCuts
+where
+    N: NetworkWithBackwardEdges + ?Sized,
+{
+    let mut cuts: FxHashMap<Id, Vec<Cut>> = FxHashMap::default();
+    for &id in TopologicalView::new(network).order() {
+        let node = network.node(id);
+        let mut fanins = Vec::from_iter(node.inputs().iter().map(Signal::node_id));
+        fanins.sort_unstable();
+        fanins.dedup();
+
+        let mut node_cuts = Vec::new();
+        if node.is_leaf() {
+            if node.input_index().is_none() {
+                let mut cut = Cut::new(Vec::new());
+                if params.compute_truth_tables {
+                    cut.truth_table = Some(TruthTable::new(0));
+                }
+                cuts.insert(id, vec![cut]);
+                continue;
+            }
+        } else {
+            let mut candidates = vec![Cut::new(Vec::new())];
+            for fanin in &fanins {
+                let mut merged: Vec<Cut> = Vec::new();
+                for partial in &candidates {
+                    for cut in &cuts[fanin] {
+                        let Some(cut) = partial.merge(cut, params.cut_size) else {
+                            continue;
+                        };
+                        if !merged.iter().any(|other| other.dominates(&cut)) {
+                            merged.retain(|other| !cut.dominates(other));
+                            merged.push(cut);
+                        }
+                    }
+                }
+                candidates = merged;
+            }
+            candidates.sort_by_cached_key(|cut| {
+                let fanouts: usize = cut
+                    .leaves
+                    .iter()
+                    .map(|leaf| network.node_outputs(*leaf).count())
+                    .sum();
+                (cut.size(), usize::MAX - fanouts)
+            });
+            candidates.truncate(params.cut_limit);
+            node_cuts = candidates;
+        }
+        node_cuts.push(Cut::new(vec![id]));
+
+        if params.compute_truth_tables {
+            for cut in &mut node_cuts {
+                cut.truth_table = Some(cut_function(network, id, &cut.leaves));
+            }
+        }
+        cuts.insert(id, node_cuts);
+    }
+    NetworkCuts { cuts }
+}
+
+/// Computes the function of the given node in terms of the given leaves by simulating the cone
+/// between them.
+pub fn cut_function<N>(network: &N, root: Id, leaves: &[Id]) -> TruthTable
+where
+    N: Network + ?Sized,
+{
+    let num_vars = leaves.len() as u32;
+    let mut values: FxHashMap<Id, TruthTable> = leaves
+        .iter()
+        .enumerate()
+        .map(|(var, leaf)| (*leaf, TruthTable::nth_var(num_vars, var as u32)))
+        .collect();
+    let mut stack = vec![root];
+    while let Some(&id) = stack.last() {
+        if values.contains_key(&id) {
+            stack.pop();
+            continue;
+        }
+        let node = network.node(id);
+        let missing = Vec::from_iter(
+            node.inputs()
+                .iter()
+                .map(Signal::node_id)
+                .filter(|input| !values.contains_key(input)),
+        );
+        if !missing.is_empty() {
+            stack.extend(missing);
+            continue;
+        }
+        stack.pop();
+        let value = if node.is_leaf() {
+            assert!(
+                node.input_index().is_none(),
+                "cut leaves should separate the root from the primary inputs"
+            );
+            TruthTable::new(num_vars)
+        } else {
+            let inputs = Vec::from_iter(node.inputs().iter().map(|signal| {
+                let value = values[&signal.node_id()].clone();
+                if signal.is_inverted() {
+                    !value
+                } else {
+                    value
+                }
+            }));
+            node.evaluate(&inputs)
+                .expect("gate function should be known")
+        };
+        values.insert(id, value);
+    }
+    values.remove(&root).unwrap()
+}
@@ -1,4 +1,5 @@
-use egg::{Analysis, CostFunction, EGraph, Extractor, Language};
+use egg::{Analysis, CostFunction, EGraph, Extractor, Language, RecExpr};
+use rustc_hash::{FxHashMap, FxHashSet};
 use std::ops::Index;
 
 use crate::{Id, Network, NetworkLanguage, Receiver, Signal};
@@ -15,6 +16,9 @@ fn egraph_id_for_signal<L: NetworkLanguage, A: Analysis<L>>(
     }
 }
 
+/// Adds the received network to the e-graph and returns the ids of the outputs. Register outputs
+/// are added as (pseudo-)PIs and register inputs are part of the returned outputs, so rewriting
+/// only considers the combinational part of a sequential network.
 impl<L: NetworkLanguage, A: Analysis<L>> Receiver for EGraph<L, A> {
     type Node = L::Node;
     type Result = (Self, Vec<egg::Id>);
@@ -53,6 +57,254 @@ impl<L: NetworkLanguage, CF: CostFunction<L>, A: Analysis<L>> Network
     }
 }
 
+/// Builds an expression of the received network and returns the ids of the outputs in it. Unlike
+/// in an e-graph, nots are added for every inverted signal, since expressions are not deduplicated.
+///
+/// # Example
+/// ```
+/// use eggmock::egg::{Language, RecExpr};
+/// use eggmock::{read_expression, simulate_truth_tables, Aig, AigLanguage, MutableNetwork, Network};
+///
+/// let (aig, _) = read_expression(MutableNetwork::<Aig>::default(), "a & !b; !a").unwrap();
+/// let (expr, roots) = aig.send(RecExpr::<AigLanguage>::default());
+/// let first = expr[roots[0]].build_recexpr(|id| expr[id].clone());
+/// assert_eq!(first.to_string(), "(and 0 (! 1))");
+///
+/// let network = (expr, roots);
+/// assert_eq!(simulate_truth_tables(&network), simulate_truth_tables(&aig));
+/// ```
+impl<L: NetworkLanguage> Receiver for RecExpr<L> {
+    type Node = L::Node;
+    type Result = (Self, Vec<egg::Id>);
+
+    fn create_node(&mut self, node: Self::Node) -> Signal {
+        let node = L::from_node(node, |signal| rec_expr_id_for_signal(self, signal));
+        Signal::new(Id::from(self.add(node)), false)
+    }
+
+    fn done(mut self, outputs: &[Signal]) -> Self::Result {
+        let outputs = Vec::from_iter(
+            outputs
+                .iter()
+                .map(|signal| rec_expr_id_for_signal(&mut self, *signal)),
+        );
+        (self, outputs)
+    }
+}
+
+fn rec_expr_id_for_signal<L: NetworkLanguage>(expr: &mut RecExpr<L>, signal: Signal) -> egg::Id {
+    let child_id = signal.node_id().into();
+    if signal.is_inverted() {
+        expr.add(L::not(child_id))
+    } else {
+        child_id
+    }
+}
+
+/// An expression with multiple roots, which are the outputs of the network.
+impl<L: NetworkLanguage> Network for (RecExpr<L>, Vec<egg::Id>) {
+    type Node = L::Node;
+
+    fn outputs(&self) -> impl Iterator<Item = Signal> {
+        self.1.iter().map(|o| self.0.to_signal(*o))
+    }
+
+    fn node(&self, id: Id) -> Self::Node {
+        self.0[id.into()]
+            .to_node(|id| self.0.to_signal(id))
+            .expect("id should point to a non-not node")
+    }
+}
+
+/// A network given by an e-graph and an explicit selection of one e-node per e-class, e.g. the
+/// result of an external extractor.
+pub struct EGraphSelection<'g, L: NetworkLanguage, A: Analysis<L>> {
+    egraph: &'g EGraph<L, A>,
+    selection: FxHashMap<egg::Id, L>,
+    roots: Vec<egg::Id>,
+}
+
+impl<'g, L: NetworkLanguage, A: Analysis<L>> EGraphSelection<'g, L, A> {
+    /// Creates the network with the given roots as outputs from the given selection, which maps
+    /// e-classes to one of their e-nodes. The ids do not have to be canonical, but the e-graph has
+    /// to be rebuilt.
+    ///
+    /// # Panics
+    /// Panics if a selected e-node is not part of its e-class, if an e-class that is reachable
+    /// from the roots has no selected e-node or if the selected e-nodes that are reachable from the
+    /// roots form a cycle.
+    ///
+    /// # Example
+    /// ```
+    /// use std::panic::{catch_unwind, AssertUnwindSafe};
+    ///
+    /// use eggmock::egg::EGraph;
+    /// use eggmock::{simulate_truth_tables, AigLanguage, EGraphSelection, TruthTable};
+    ///
+    /// // a & b is equivalent to its double negation
+    /// let mut egraph = EGraph::<AigLanguage, ()>::default();
+    /// let [a, b] = [0, 1].map(|input| egraph.add(AigLanguage::Input(input)));
+    /// let and = egraph.add(AigLanguage::And([a, b]));
+    /// let nand = egraph.add(AigLanguage::Not(and));
+    /// let not_nand = egraph.add(AigLanguage::Not(nand));
+    /// egraph.union(and, not_nand);
+    /// egraph.rebuild();
+    ///
+    /// let inputs = [(a, AigLanguage::Input(0)), (b, AigLanguage::Input(1))];
+    /// let selection = EGraphSelection::new(
+    ///     &egraph,
+    ///     inputs.into_iter().chain([
+    ///         (and, AigLanguage::And([a, b])),
+    ///         (nand, AigLanguage::Not(and)),
+    ///     ]),
+    ///     [nand],
+    /// );
+    /// let expected = !(TruthTable::nth_var(2, 0) & TruthTable::nth_var(2, 1));
+    /// assert_eq!(simulate_truth_tables(&selection), [expected]);
+    ///
+    /// let message = |selection: Vec<(_, _)>, roots: Vec<_>| {
+    ///     let error = catch_unwind(AssertUnwindSafe(|| {
+    ///         EGraphSelection::new(&egraph, selection, roots);
+    ///     }))
+    ///     .unwrap_err();
+    ///     error.downcast::<String>().unwrap()
+    /// };
+    /// // the inputs are not selected
+    /// let unselected = message(vec![(and, AigLanguage::And([a, b]))], vec![and]);
+    /// assert!(unselected.contains("should have a selected e-node"));
+    /// // selecting the double negation for `and` results in a cycle, which is also detected if
+    /// // another root is not selected
+    /// let cycle = vec![(and, AigLanguage::Not(nand)), (nand, AigLanguage::Not(and))];
+    /// assert!(message(cycle, vec![a, nand]).contains("cycle"));
+    /// ```
+    pub fn new(
+        egraph: &'g EGraph<L, A>,
+        selection: impl IntoIterator<Item = (egg::Id, L)>,
+        roots: impl IntoIterator<Item = egg::Id>,
+    ) -> Self {
+        let selection = Self::new_unchecked(egraph, selection, roots);
+        match selection.validate() {
+            Ok(()) => selection,
+            Err(InvalidSelection::Unselected(class)) => {
+                panic!("the reachable e-class {class} should have a selected e-node")
+            }
+            Err(InvalidSelection::Cycle(class)) => {
+                panic!("the selection should not contain a cycle through e-class {class}")
+            }
+        }
+    }
+
+    /// Like [`new`](Self::new) but does not check that the selection describes a network.
+    pub(crate) fn new_unchecked(
+        egraph: &'g EGraph<L, A>,
+        selection: impl IntoIterator<Item = (egg::Id, L)>,
+        roots: impl IntoIterator<Item = egg::Id>,
+    ) -> Self {
+        let selection = FxHashMap::from_iter(selection.into_iter().map(|(class, node)| {
+            let class = egraph.find(class);
+            let node = node.map_children(|child| egraph.find(child));
+            assert!(
+                egraph[class].nodes.contains(&node),
+                "selected e-node {node:?} should be part of e-class {class}"
+            );
+            (class, node)
+        }));
+        let roots = Vec::from_iter(roots.into_iter().map(|root| egraph.find(root)));
+        Self {
+            egraph,
+            selection,
+            roots,
+        }
+    }
+
+    /// Checks that every e-class that is reachable from the roots has a selected e-node and that
+    /// the selected e-nodes do not form a cycle, which would make the network infinite. All roots
+    /// are checked for cycles before an unselected e-class is reported.
+    pub(crate) fn validate(&self) -> Result<(), InvalidSelection> {
+        let mut done = FxHashSet::default();
+        let mut on_stack = FxHashSet::default();
+        let mut unselected = None;
+        for root in &self.roots {
+            if done.contains(root) {
+                continue;
+            }
+            // depth-first search where each entry holds the index of the next child to visit
+            let mut stack = vec![(*root, 0)];
+            on_stack.insert(*root);
+            while let Some(&(class, next)) = stack.last() {
+                let children = match self.selection.get(&class) {
+                    Some(node) => node.children(),
+                    None => {
+                        unselected.get_or_insert(class);
+                        &[]
+                    }
+                };
+                let Some(child) = children.get(next).copied() else {
+                    on_stack.remove(&class);
+                    done.insert(class);
+                    stack.pop();
+                    continue;
+                };
+                stack.last_mut().unwrap().1 += 1;
+                if done.contains(&child) {
+                    continue;
+                }
+                if !on_stack.insert(child) {
+                    return Err(InvalidSelection::Cycle(child));
+                }
+                stack.push((child, 0));
+            }
+        }
+        match unselected {
+            Some(class) => Err(InvalidSelection::Unselected(class)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn egraph(&self) -> &'g EGraph<L, A> {
+        self.egraph
+    }
+
+    /// Returns the selected e-node of the given e-class or [`None`] if there is none.
+    pub fn selected(&self, class: egg::Id) -> Option<&L> {
+        self.selection.get(&self.egraph.find(class))
+    }
+}
+
+impl<L: NetworkLanguage, A: Analysis<L>> Index<egg::Id> for EGraphSelection<'_, L, A> {
+    type Output = L;
+
+    /// # Panics
+    /// Panics if the e-class has no selected e-node.
+    fn index(&self, class: egg::Id) -> &L {
+        self.selection
+            .get(&class)
+            .expect("e-class should have a selected e-node")
+    }
+}
+
+impl<L: NetworkLanguage, A: Analysis<L>> Network for EGraphSelection<'_, L, A> {
+    type Node = L::Node;
+
+    fn outputs(&self) -> impl Iterator<Item = Signal> {
+        self.roots.iter().map(|o| self.to_signal(*o))
+    }
+
+    fn node(&self, id: Id) -> Self::Node {
+        self[id.into()]
+            .to_node(|id| self.to_signal(id))
+            .expect("id should point to a non-not node")
+    }
+}
+
+/// The reason why an [`EGraphSelection`] does not describe a network.
+pub(crate) enum InvalidSelection {
+    /// The e-class is reachable from the roots but has no selected e-node.
+    Unselected(egg::Id),
+    /// The e-class is part of a cycle of selected e-nodes.
+    Cycle(egg::Id),
+}
+
 pub trait EggIdToSignal {
     fn to_signal(&self, id: egg::Id) -> Signal;
 }
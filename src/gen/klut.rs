@@ -0,0 +1,59 @@
+use super::*;
+use crate::Klut;
+
+/// Generates the FFI for *mockturtle*'s `klut_network`. In contrast to the other network types,
+/// the gates of k-LUT networks have a varying number of inputs and carry a truth table, so the
+/// gate-related parts are not derived from the [`GateType`](crate::GateType).
+pub fn klut_ffi() -> String {
+    let ntk = Klut::NTK_TYPENAME;
+    let ntk_type = format!("mockturtle::{}", Klut::NTK_MOCKTURTLE_TYPENAME);
+    let gate_fields = "\n  signal ( *create_lut )( void* data, signal const* inputs, size_t inputs_size, uint64_t const* function, size_t function_size );";
+    let gate_cases = format!(
+        "
+  else if ( ntk.is_function( node ) )
+  {{
+    std::vector<signal> fanins;
+    fanins.reserve( ntk.fanin_size( node ) );
+    ntk.foreach_fanin( node, [&]( {ntk_type}::signal const& fanin ) {{
+      fanins.emplace_back( send_{ntk}_signal( ntk, fanin, receiver ) );
+    }} );
+    auto const& function = ntk.node_function( node );
+    std::vector<uint64_t> const words( function.cbegin(), function.cend() );
+    dst_sig = receiver.create_lut( receiver.data, fanins.data(), fanins.size(), words.data(), words.size() );
+  }}"
+    );
+    let struct_initializers =
+        format!("\n      .create_lut = _impl::receive_{ntk}_create_lut<Ntk>,");
+    let gate_methods = formatdoc!(
+        r#"
+        template<class Ntk>
+        signal receive_{ntk}_create_lut( void* data, signal const* inputs, size_t inputs_size, uint64_t const* function, size_t function_size )
+        {{
+          auto const ntk = static_cast<Ntk*>( data );
+          std::vector<typename Ntk::signal> children;
+          children.reserve( inputs_size );
+          for ( size_t i = 0; i < inputs_size; i++ )
+          {{
+            children.emplace_back( map_signal_{ntk}( *ntk, inputs[i] ) );
+          }}
+          kitty::dynamic_truth_table tt( static_cast<uint32_t>( inputs_size ) );
+          std::copy( function, function + function_size, tt.begin() );
+          return {ntk}_map_signal( *ntk, ntk->create_node( children, tt ) );
+        }}
+        "#
+    );
+    formatdoc!(
+        r#"
+        #include <{}>
+        namespace eggmock {{
+        {}{}{}{}{}
+        }}
+        "#,
+        Klut::NTK_MOCKTURTLE_INCLUDE,
+        transfer::receiver_struct_for(ntk, gate_fields),
+        transfer::send_helper_for(ntk, &ntk_type, &gate_cases),
+        transfer::receive_helper_for(ntk, &ntk_type, &struct_initializers, &gate_methods),
+        rewrite::rewrite_struct::<Klut>(),
+        rewrite::rewrite_helper::<Klut>()
+    )
+}
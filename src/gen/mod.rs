@@ -1,6 +1,9 @@
+mod klut;
 mod rewrite;
 mod transfer;
 
+pub use klut::klut_ffi;
+
 use crate::{Aig, Mig, Node, Xag, Xmg};
 use indoc::formatdoc;
 
@@ -21,15 +24,79 @@ pub fn network_ffi<N: Node>() -> String {
     )
 }
 
+/// Generates the structs and helpers for rewrites between each pair of different network types
+/// in the header. Has to follow the FFI of all of these networks.
+fn converting_rewrites_ffi() -> String {
+    fn pair<I: Node, O: Node>() -> String {
+        if I::NTK_TYPENAME == O::NTK_TYPENAME {
+            return String::new();
+        }
+        rewrite::converting_rewrite::<I, O>()
+    }
+    fn from<I: Node>() -> String {
+        pair::<I, Mig>() + &pair::<I, Aig>() + &pair::<I, Xag>() + &pair::<I, Xmg>()
+    }
+    formatdoc!(
+        r#"
+        namespace eggmock {{
+        {}{}{}{}
+        }}
+        "#,
+        from::<Mig>(),
+        from::<Aig>(),
+        from::<Xag>(),
+        from::<Xmg>()
+    )
+}
+
+/// Generates the declarations and helpers of the rule file rewrites, see
+/// [`RuleRewriter`](crate::RuleRewriter). Has to follow the FFI of all of these networks.
+fn rule_file_rewrites_ffi() -> String {
+    formatdoc!(
+        r#"
+        namespace eggmock {{
+        {}{}{}{}
+        }}
+        "#,
+        rewrite::rule_file_rewrite::<Mig>(),
+        rewrite::rule_file_rewrite::<Aig>(),
+        rewrite::rule_file_rewrite::<Xag>(),
+        rewrite::rule_file_rewrite::<Xmg>()
+    )
+}
+
+/// Generates the declarations and helpers of the isolated rewrites, see
+/// [`IsolatedRewriter`](crate::IsolatedRewriter). Has to follow the FFI of all of these networks.
+fn isolated_rewrites_ffi() -> String {
+    formatdoc!(
+        r#"
+        namespace eggmock {{
+        {}{}{}{}
+        }}
+        "#,
+        rewrite::isolated_rewrite::<Mig>(),
+        rewrite::isolated_rewrite::<Aig>(),
+        rewrite::isolated_rewrite::<Xag>(),
+        rewrite::isolated_rewrite::<Xmg>()
+    )
+}
+
 pub fn ffi_header() -> String {
     formatdoc!(
         r#"
         // Automatically generated by eggmock v{}
         #pragma once
 
+        #include <algorithm>
         #include <cstddef>
         #include <cstdint>
         #include <stdexcept>
+        #include <string>
+        #include <type_traits>
+        #include <utility>
+        #include <vector>
+
+        #include <mockturtle/traits.hpp>
 
         namespace eggmock
         {{
@@ -54,8 +121,48 @@ pub fn ffi_header() -> String {
             return signal( _v ^ ( static_cast<uint32_t>( 1 ) << 31 ) );
           }}
         }};
+
+        namespace _impl
+        {{
+        template<class Ntk, class = void>
+        struct has_register_at : std::false_type
+        {{
+        }};
+        template<class Ntk>
+        struct has_register_at<Ntk, std::void_t<decltype( std::declval<Ntk>().register_at( 0u ).init )>> : std::true_type
+        {{
+        }};
+        template<class Ntk>
+        inline constexpr bool has_register_at_v = has_register_at<Ntk>::value;
+
+        template<class Ntk, class = void>
+        struct has_set_register : std::false_type
+        {{
+        }};
+        template<class Ntk>
+        struct has_set_register<Ntk, std::void_t<decltype( std::declval<Ntk>().set_register( 0u, std::declval<Ntk>().register_at( 0u ) ) )>> : std::true_type
+        {{
+        }};
+        template<class Ntk>
+        inline constexpr bool has_set_register_v = has_set_register<Ntk>::value;
+
+        template<class Ntk, class = void>
+        struct has_add_choice : std::false_type
+        {{
+        }};
+        template<class Ntk>
+        struct has_add_choice<Ntk, std::void_t<decltype( std::declval<Ntk>().add_choice( std::declval<typename Ntk::node>(), std::declval<typename Ntk::node>() ) )>> : std::true_type
+        {{
+        }};
+        template<class Ntk>
+        inline constexpr bool has_add_choice_v = has_add_choice<Ntk>::value;
+        }} // namespace _impl
         }}
 
+        {}
+        {}
+        {}
+        {}
         {}
         {}
         {}
@@ -65,6 +172,10 @@ pub fn ffi_header() -> String {
         network_ffi::<Mig>(),
         network_ffi::<Aig>(),
         network_ffi::<Xag>(),
-        network_ffi::<Xmg>()
+        network_ffi::<Xmg>(),
+        converting_rewrites_ffi(),
+        rule_file_rewrites_ffi(),
+        isolated_rewrites_ffi(),
+        klut_ffi()
     )
 }
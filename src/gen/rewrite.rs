@@ -19,10 +19,11 @@ pub fn rewrite_helper<N: Node>() -> String {
     let ntk_type = format!("mockturtle::{}", N::NTK_MOCKTURTLE_TYPENAME);
     formatdoc!(
         r#"
-        inline {ntk_type} rewrite_{ntk}( {ntk_type} const& in_ntk, {ntk}_receiver<{ntk}_rewrite> const& receiver )
+        template<class Ntk = {ntk_type}>
+        Ntk rewrite_{ntk}( Ntk const& in_ntk, {ntk}_receiver<{ntk}_rewrite> const& receiver )
         {{
           auto rewrite = send_{ntk}( in_ntk, receiver );
-          {ntk_type} out_ntk;
+          Ntk out_ntk;
           auto callback = receive_{ntk}( out_ntk );
           rewrite.rewrite( rewrite.data, callback );
           return out_ntk;
@@ -30,3 +31,107 @@ pub fn rewrite_helper<N: Node>() -> String {
         "#
     )
 }
+
+/// Generates the rewrite struct and helper for rewrites of `I` networks into `O` networks, see
+/// [`RewriterFFI`](crate::RewriterFFI).
+pub fn converting_rewrite<I: Node, O: Node>() -> String {
+    let input = I::NTK_TYPENAME;
+    let output = O::NTK_TYPENAME;
+    let input_type = format!("mockturtle::{}", I::NTK_MOCKTURTLE_TYPENAME);
+    let output_type = format!("mockturtle::{}", O::NTK_MOCKTURTLE_TYPENAME);
+    formatdoc!(
+        r#"
+        struct {input}_{output}_rewrite
+        {{
+          void* data;
+          void ( *rewrite )( void* data, {output}_receiver<void> callback );
+        }};
+
+        template<class Ntk = {output_type}, class InNtk = {input_type}>
+        Ntk rewrite_{input}_to_{output}( InNtk const& in_ntk, {input}_receiver<{input}_{output}_rewrite> const& receiver )
+        {{
+          auto rewrite = send_{input}( in_ntk, receiver );
+          Ntk out_ntk;
+          auto callback = receive_{output}( out_ntk );
+          rewrite.rewrite( rewrite.data, callback );
+          return out_ntk;
+        }}
+        "#
+    )
+}
+
+/// Generates the declaration of the rule file rewrite for `N` networks and a helper that calls it,
+/// see [`RuleRewriter`](crate::RuleRewriter).
+pub fn rule_file_rewrite<N: Node>() -> String {
+    let ntk = N::NTK_TYPENAME;
+    let ntk_type = format!("mockturtle::{}", N::NTK_MOCKTURTLE_TYPENAME);
+    formatdoc!(
+        r#"
+        extern "C"
+        {{
+          bool eggmock_{ntk}_rule_file_rewrite( char const* path, size_t path_size, {ntk}_receiver<{ntk}_rewrite>* receiver, void* error, void ( *set_error )( void* error, char const* message, size_t message_size ) );
+        }}
+
+        template<class Ntk = {ntk_type}>
+        Ntk rewrite_{ntk}_with_rule_file( Ntk const& in_ntk, std::string const& path )
+        {{
+          {ntk}_receiver<{ntk}_rewrite> receiver;
+          std::string error;
+          auto set_error = []( void* error, char const* message, size_t message_size ) {{
+            static_cast<std::string*>( error )->assign( message, message_size );
+          }};
+          if ( !eggmock_{ntk}_rule_file_rewrite( path.data(), path.size(), &receiver, &error, set_error ) )
+          {{
+            throw std::runtime_error( "eggmock: cannot load rules from " + path + ": " + error );
+          }}
+          return rewrite_{ntk}( in_ntk, receiver );
+        }}
+        "#
+    )
+}
+
+/// Generates the declaration of the isolated rewrite for `N` networks and a helper that calls it
+/// and throws if the worker fails, see [`IsolatedRewriterFFI`](crate::IsolatedRewriterFFI).
+pub fn isolated_rewrite<N: Node>() -> String {
+    let ntk = N::NTK_TYPENAME;
+    let ntk_type = format!("mockturtle::{}", N::NTK_MOCKTURTLE_TYPENAME);
+    formatdoc!(
+        r#"
+        struct {ntk}_isolated_rewrite
+        {{
+          void* data;
+          bool ( *rewrite )( void* data, {ntk}_receiver<void> callback, void* error, void ( *set_error )( void* error, char const* message, size_t message_size ) );
+        }};
+
+        extern "C"
+        {{
+          {ntk}_receiver<{ntk}_isolated_rewrite> eggmock_{ntk}_isolated_rewrite( char const* program, size_t program_size, char const* const* args, size_t const* arg_sizes, size_t num_args, uint64_t time_limit_ms, uint64_t memory_limit );
+        }}
+
+        template<class Ntk = {ntk_type}>
+        Ntk rewrite_{ntk}_isolated( Ntk const& in_ntk, std::string const& program, std::vector<std::string> const& args = {{}}, uint64_t time_limit_ms = 0, uint64_t memory_limit = 0 )
+        {{
+          std::vector<char const*> arg_data;
+          std::vector<size_t> arg_sizes;
+          for ( auto const& arg : args )
+          {{
+            arg_data.push_back( arg.data() );
+            arg_sizes.push_back( arg.size() );
+          }}
+          auto receiver = eggmock_{ntk}_isolated_rewrite( program.data(), program.size(), arg_data.data(), arg_sizes.data(), args.size(), time_limit_ms, memory_limit );
+          auto rewrite = send_{ntk}( in_ntk, receiver );
+          Ntk out_ntk;
+          auto callback = receive_{ntk}( out_ntk );
+          std::string error;
+          auto set_error = []( void* error, char const* message, size_t message_size ) {{
+            static_cast<std::string*>( error )->assign( message, message_size );
+          }};
+          if ( !rewrite.rewrite( rewrite.data, callback, &error, set_error ) )
+          {{
+            throw std::runtime_error( "eggmock: rewriting in worker failed: " + error );
+          }}
+          return out_ntk;
+        }}
+        "#
+    )
+}
@@ -12,6 +12,12 @@ pub fn receiver_struct<N: Node>() -> String {
         )
         .as_str();
     }
+    receiver_struct_for(ntk, &additional_fields)
+}
+
+/// Generates the receiver struct of the network type `ntk` with the given fields for creating
+/// gates.
+pub(super) fn receiver_struct_for(ntk: &str, additional_fields: &str) -> String {
     formatdoc!(
         r#"
         template<class result>
@@ -20,6 +26,11 @@ pub fn receiver_struct<N: Node>() -> String {
           void* data;
           signal ( *create_input )( void* data, uint64_t name );
           signal ( *create_const )( void* data, bool value );{additional_fields}
+          void ( *set_num_inputs )( void* data, uint64_t num_inputs );
+          void ( *set_registers )( void* data, uint8_t const* inits, size_t num_registers );
+          void ( *set_input_name )( void* data, uint64_t input, char const* name, size_t name_size );
+          void ( *set_output_name )( void* data, size_t output, char const* name, size_t name_size );
+          void ( *add_choice )( void* data, signal node, signal choice );
           result ( *done )( void* data, signal const* roots, size_t roots_size );
         }};
         "#
@@ -53,6 +64,12 @@ pub fn send_helper<N: Node>() -> String {
         )
         .as_str();
     }
+    send_helper_for(ntk, &ntk_type, &gate_cases)
+}
+
+/// Generates the functions that send networks of type `ntk` where `gate_cases` contains the
+/// `else if` branches that send the gates.
+pub(super) fn send_helper_for(ntk: &str, ntk_type: &str, gate_cases: &str) -> String {
     formatdoc!(
         r#"
         namespace _impl
@@ -67,9 +84,9 @@ pub fn send_helper<N: Node>() -> String {
           {{
             dst_sig = signal( ntk.value( node ) );
           }}
-          else if ( ntk.is_pi( node ) )
+          else if ( ntk.is_ci( node ) )
           {{
-            dst_sig = receiver.create_input( receiver.data, ntk.pi_index( node ) );
+            dst_sig = receiver.create_input( receiver.data, ntk.ci_index( node ) );
           }}
           else if ( ntk.is_constant( node ) )
           {{
@@ -89,21 +106,61 @@ pub fn send_helper<N: Node>() -> String {
           return dst_sig;
         }}
         }} // namespace _impl
-        template<class result>
-        result send_{ntk} ( {ntk_type} const& ntk, {ntk}_receiver<result> const& receiver )
+        template<class result, class Ntk = {ntk_type}>
+        result send_{ntk} ( Ntk const& ntk, {ntk}_receiver<result> const& receiver )
         {{
           ntk.clear_values();
           ntk.clear_visited();
+          receiver.set_num_inputs( receiver.data, ntk.num_pis() );
+          if ( ntk.num_registers() > 0 )
+          {{
+            std::vector<uint8_t> inits( ntk.num_registers(), 3 );
+            if constexpr ( _impl::has_register_at_v<Ntk> )
+            {{
+              for ( uint32_t index = 0; index < ntk.num_registers(); index++ )
+              {{
+                inits[index] = ntk.register_at( index ).init;
+              }}
+            }}
+            receiver.set_registers( receiver.data, inits.data(), inits.size() );
+          }}
           ntk.foreach_node( [&] ( auto const& node ) {{
             _impl::send_{ntk}_signal( ntk, ntk.make_signal( node ), receiver );
           }} );
 
           std::vector<signal> outputs;
-          outputs.reserve( ntk.num_pos() );
+          outputs.reserve( ntk.num_pos() + ntk.num_registers() );
           ntk.foreach_po( [&] ( auto const& src_sig ) {{
             signal sig = _impl::send_{ntk}_signal( ntk, src_sig, receiver );
             outputs.emplace_back( sig );
           }} );
+          ntk.foreach_ri( [&] ( auto const& src_sig ) {{
+            signal sig = _impl::send_{ntk}_signal( ntk, src_sig, receiver );
+            outputs.emplace_back( sig );
+          }} );
+
+          if constexpr ( mockturtle::has_has_name_v<Ntk> && mockturtle::has_get_name_v<Ntk> )
+          {{
+            ntk.foreach_ci( [&] ( auto const& node, uint32_t index ) {{
+              auto const sig = ntk.make_signal( node );
+              if ( ntk.has_name( sig ) )
+              {{
+                auto const name = ntk.get_name( sig );
+                receiver.set_input_name( receiver.data, index, name.data(), name.size() );
+              }}
+            }} );
+          }}
+          if constexpr ( mockturtle::has_has_output_name_v<Ntk> && mockturtle::has_get_output_name_v<Ntk> )
+          {{
+            for ( uint32_t index = 0; index < ntk.num_pos(); index++ )
+            {{
+              if ( ntk.has_output_name( index ) )
+              {{
+                auto const name = ntk.get_output_name( index );
+                receiver.set_output_name( receiver.data, index, name.data(), name.size() );
+              }}
+            }}
+          }}
           return receiver.done( receiver.data, outputs.data(), outputs.size() );
         }}
         "#,
@@ -115,6 +172,43 @@ pub fn receive_helper<N: Node>() -> String {
     let ntk_type = format!("mockturtle::{}", N::NTK_MOCKTURTLE_TYPENAME);
 
     let mut struct_initializers = String::new();
+    let mut gate_methods = String::new();
+    for gate in N::Gates::VARIANTS {
+        let gate_name = gate.name();
+        let id_signals = (1..=gate.fanin())
+            .map(|id| format!("map_signal_{ntk}( *ntk, input{id} )"))
+            .fold("".to_string(), |acc, x| acc + ", " + x.as_str());
+        struct_initializers +=
+            format!("\n      .create_{gate_name} = _impl::receive_{ntk}_create_{gate_name}<Ntk>,")
+                .as_str();
+        gate_methods += formatdoc!(
+            r#"
+            template<class Ntk>
+            signal receive_{ntk}_create_{gate_name}( void* data, {ids} )
+            {{
+              auto const ntk = static_cast<Ntk*>( data );
+              return {ntk}_map_signal(
+                  *ntk,
+                  ntk->{create}( {id_signals} ) );
+            }}
+            "#,
+            ids = signal_parameters(gate),
+            create = gate.mockturtle_create(),
+            id_signals = &id_signals[2..]
+        )
+        .as_str();
+    }
+    receive_helper_for(ntk, &ntk_type, &struct_initializers, &gate_methods)
+}
+
+/// Generates the functions that receive networks of type `ntk` where `gate_methods` contains the
+/// implementations of the gate fields of the receiver and `struct_initializers` assigns them.
+pub(super) fn receive_helper_for(
+    ntk: &str,
+    ntk_type: &str,
+    struct_initializers: &str,
+    gate_methods: &str,
+) -> String {
     let mut impl_methods = formatdoc!(
         r#"
         inline signal {ntk}_map_signal( {ntk_type} const& ntk, {ntk_type}::signal const& s ) {{
@@ -128,56 +222,107 @@ pub fn receive_helper<N: Node>() -> String {
           return sig;
         }}
 
-        inline signal receive_{ntk}_create_input( void* data, uint64_t name )
+        template<class Ntk>
+        signal receive_{ntk}_create_input( void* data, uint64_t name )
         {{
-          auto const ntk = static_cast<{ntk_type}*>( data );
-          while ( ntk->num_pis() <= name )
+          auto const ntk = static_cast<Ntk*>( data );
+          while ( ntk->num_cis() <= name )
           {{
             ntk->create_pi();
           }}
-          return signal( ntk->node_to_index( ntk->pi_at( name ) ), false );
+          return signal( ntk->node_to_index( ntk->ci_at( name ) ), false );
         }}
 
-        inline signal receive_{ntk}_create_const( void* data, bool value )
+        template<class Ntk>
+        signal receive_{ntk}_create_const( void* data, bool value )
         {{
-          auto const ntk = static_cast<{ntk_type}*>( data );
+          auto const ntk = static_cast<Ntk*>( data );
           return {ntk}_map_signal( *ntk, ntk->get_constant(value) ) ;
         }}
 
-        inline void receive_{ntk}_done( void* data, signal const* roots, size_t roots_size )
+        template<class Ntk>
+        void receive_{ntk}_set_num_inputs( void* data, uint64_t num_inputs )
+        {{
+          auto const ntk = static_cast<Ntk*>( data );
+          while ( ntk->num_pis() < num_inputs )
+          {{
+            ntk->create_pi();
+          }}
+        }}
+
+        template<class Ntk>
+        void receive_{ntk}_set_registers( void* data, uint8_t const* inits, size_t num_registers )
         {{
-          auto const ntk = static_cast<{ntk_type}*>( data );
-          for ( size_t i = 0; i < roots_size; i++ )
+          auto const ntk = static_cast<Ntk*>( data );
+          for ( size_t i = 0; i < num_registers; i++ )
+          {{
+            ntk->create_ro();
+            if constexpr ( _impl::has_set_register_v<Ntk> )
+            {{
+              auto const index = ntk->num_cis() - ntk->num_pis() - 1;
+              auto reg = ntk->register_at( index );
+              reg.init = inits[i];
+              ntk->set_register( index, reg );
+            }}
+          }}
+        }}
+
+        template<class Ntk>
+        void receive_{ntk}_set_input_name( void* data, uint64_t input, char const* name, size_t name_size )
+        {{
+          if constexpr ( mockturtle::has_set_name_v<Ntk> )
+          {{
+            auto const ntk = static_cast<Ntk*>( data );
+            while ( ntk->num_cis() <= input )
+            {{
+              ntk->create_pi();
+            }}
+            ntk->set_name( ntk->make_signal( ntk->ci_at( input ) ), std::string( name, name_size ) );
+          }}
+        }}
+
+        template<class Ntk>
+        void receive_{ntk}_set_output_name( void* data, size_t output, char const* name, size_t name_size )
+        {{
+          if constexpr ( mockturtle::has_set_output_name_v<Ntk> )
+          {{
+            auto const ntk = static_cast<Ntk*>( data );
+            ntk->set_output_name( output, std::string( name, name_size ) );
+          }}
+        }}
+
+        template<class Ntk>
+        void receive_{ntk}_add_choice( void* data, signal node, signal choice )
+        {{
+          // the choices of mockturtle are equivalences of nodes, so inverted ones cannot be stored
+          if constexpr ( _impl::has_add_choice_v<Ntk> )
+          {{
+            if ( node.is_complemented() != choice.is_complemented() )
+            {{
+              return;
+            }}
+            auto const ntk = static_cast<Ntk*>( data );
+            ntk->add_choice( ntk->index_to_node( node.id() ), ntk->index_to_node( choice.id() ) );
+          }}
+        }}
+
+        template<class Ntk>
+        void receive_{ntk}_done( void* data, signal const* roots, size_t roots_size )
+        {{
+          auto const ntk = static_cast<Ntk*>( data );
+          auto const num_pos = roots_size - ( ntk->num_cis() - ntk->num_pis() );
+          for ( size_t i = 0; i < num_pos; i++ )
           {{
             ntk->create_po( map_signal_{ntk}( *ntk, roots[i] ) );
           }}
+          for ( size_t i = num_pos; i < roots_size; i++ )
+          {{
+            ntk->create_ri( map_signal_{ntk}( *ntk, roots[i] ) );
+          }}
         }}
         "#
     );
-    for gate in N::Gates::VARIANTS {
-        let gate_name = gate.name();
-        let id_signals = (1..=gate.fanin())
-            .map(|id| format!("map_signal_{ntk}( *ntk, input{id} )"))
-            .fold("".to_string(), |acc, x| acc + ", " + x.as_str());
-        struct_initializers +=
-            format!("\n      .create_{gate_name} = _impl::receive_{ntk}_create_{gate_name},")
-                .as_str();
-        impl_methods += formatdoc!(
-            r#"
-            inline signal receive_{ntk}_create_{gate_name}( void* data, {ids} )
-            {{
-              auto const ntk = static_cast<{ntk_type}*>( data );
-              return {ntk}_map_signal(
-                  *ntk,
-                  ntk->{create}( {id_signals} ) );
-            }}
-            "#,
-            ids = signal_parameters(gate),
-            create = gate.mockturtle_create(),
-            id_signals = &id_signals[2..]
-        )
-        .as_str();
-    }
+    impl_methods += gate_methods;
 
     formatdoc!(
         r#"
@@ -185,13 +330,19 @@ pub fn receive_helper<N: Node>() -> String {
         {{
         {impl_methods}
         }} // namespace _impl
-        inline {ntk}_receiver<void> receive_{ntk}({ntk_type}& ntk)
+        template<class Ntk = {ntk_type}>
+        {ntk}_receiver<void> receive_{ntk}( Ntk& ntk )
         {{
           return {{
               .data = &ntk,
-              .create_input = _impl::receive_{ntk}_create_input,
-              .create_const = _impl::receive_{ntk}_create_const,{struct_initializers}
-              .done = _impl::receive_{ntk}_done,
+              .create_input = _impl::receive_{ntk}_create_input<Ntk>,
+              .create_const = _impl::receive_{ntk}_create_const<Ntk>,{struct_initializers}
+              .set_num_inputs = _impl::receive_{ntk}_set_num_inputs<Ntk>,
+              .set_registers = _impl::receive_{ntk}_set_registers<Ntk>,
+              .set_input_name = _impl::receive_{ntk}_set_input_name<Ntk>,
+              .set_output_name = _impl::receive_{ntk}_set_output_name<Ntk>,
+              .add_choice = _impl::receive_{ntk}_add_choice<Ntk>,
+              .done = _impl::receive_{ntk}_done<Ntk>,
           }};
         }}
         "#
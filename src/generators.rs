@@ -0,0 +1,429 @@
+use crate::{NetworkBuilder, Receiver, Signal};
+
+/// Arithmetic building blocks. Words are given as slices of signals with the least significant
+/// bit first. Full adders use majority gates for the carry and XOR gates for the sum, so they are
+/// built natively in networks that have such gates.
+impl<R: Receiver> NetworkBuilder<R> {
+    /// Returns the sum and the carry of two bits.
+    pub fn half_adder(&mut self, a: Signal, b: Signal) -> (Signal, Signal) {
+        (self.xor(a, b), self.and(a, b))
+    }
+
+    /// Returns the sum and the carry of three bits.
+    pub fn full_adder(&mut self, a: Signal, b: Signal, c: Signal) -> (Signal, Signal) {
+        let sum = self.xor(a, b);
+        (self.xor(sum, c), self.maj(a, b, c))
+    }
+
+    /// Adds two words of the same width by chaining full adders. Returns the sum and the carry
+    /// out. `carry` is the carry in or [`None`] if there is none.
+    pub fn ripple_carry_add(
+        &mut self,
+        a: &[Signal],
+        b: &[Signal],
+        mut carry: Option<Signal>,
+    ) -> (Vec<Signal>, Signal) {
+        assert_eq!(a.len(), b.len(), "summands should have the same width");
+        let mut sum = Vec::with_capacity(a.len());
+        for (a, b) in a.iter().zip(b) {
+            let (bit, carry_out) = match carry {
+                Some(carry) => self.full_adder(*a, *b, carry),
+                None => self.half_adder(*a, *b),
+            };
+            sum.push(bit);
+            carry = Some(carry_out);
+        }
+        let carry = carry.unwrap_or_else(|| self.constant(false));
+        (sum, carry)
+    }
+
+    /// Adds two words of the same width with 4-bit carry-lookahead units, whose carries are
+    /// computed in two-level logic from the generate and propagate signals of their bits. The
+    /// units are chained. See [`ripple_carry_add`](Self::ripple_carry_add).
+    pub fn carry_lookahead_add(
+        &mut self,
+        a: &[Signal],
+        b: &[Signal],
+        mut carry: Option<Signal>,
+    ) -> (Vec<Signal>, Signal) {
+        assert_eq!(a.len(), b.len(), "summands should have the same width");
+        let (generate, propagate) = self.generate_propagate(a, b);
+        let mut sum = Vec::with_capacity(a.len());
+        for unit in (0..a.len()).step_by(4) {
+            let unit = unit..(unit + 4).min(a.len());
+            let unit_carry = carry;
+            for bit in unit.clone() {
+                sum.push(match carry {
+                    Some(carry) => self.xor(propagate[bit], carry),
+                    None => propagate[bit],
+                });
+                // carry into the next bit: g_j | p_j g_(j-1) | ... | p_j ... p_start c_in
+                let mut terms = Vec::new();
+                for generator in unit.start..=bit {
+                    let mut term = vec![generate[generator]];
+                    term.extend_from_slice(&propagate[generator + 1..=bit]);
+                    terms.push(self.and_all(&term));
+                }
+                if let Some(unit_carry) = unit_carry {
+                    let mut term = propagate[unit.start..=bit].to_vec();
+                    term.push(unit_carry);
+                    terms.push(self.and_all(&term));
+                }
+                carry = Some(self.or_all(&terms));
+            }
+        }
+        let carry = carry.unwrap_or_else(|| self.constant(false));
+        (sum, carry)
+    }
+
+    /// Adds two words of the same width with a Kogge-Stone parallel prefix adder, which computes
+    /// all carries in a logarithmic number of levels. See
+    /// [`ripple_carry_add`](Self::ripple_carry_add).
+    pub fn kogge_stone_add(
+        &mut self,
+        a: &[Signal],
+        b: &[Signal],
+        carry: Option<Signal>,
+    ) -> (Vec<Signal>, Signal) {
+        assert_eq!(a.len(), b.len(), "summands should have the same width");
+        if a.is_empty() {
+            return (Vec::new(), carry.unwrap_or_else(|| self.constant(false)));
+        }
+        let (mut generate, propagate) = self.generate_propagate(a, b);
+        if let Some(carry) = carry {
+            let propagated = self.and(propagate[0], carry);
+            generate[0] = self.or(generate[0], propagated);
+        }
+        // generate[i] and group_propagate[i] describe the bits from i - distance + 1 to i
+        let mut group_propagate = propagate.clone();
+        let mut distance = 1;
+        while distance < a.len() {
+            let mut next_generate = generate.clone();
+            let mut next_propagate = group_propagate.clone();
+            for i in distance..a.len() {
+                let propagated = self.and(group_propagate[i], generate[i - distance]);
+                next_generate[i] = self.or(generate[i], propagated);
+                if i >= 2 * distance {
+                    next_propagate[i] = self.and(group_propagate[i], group_propagate[i - distance]);
+                }
+            }
+            generate = next_generate;
+            group_propagate = next_propagate;
+            distance *= 2;
+        }
+        let mut sum = Vec::with_capacity(a.len());
+        sum.push(match carry {
+            Some(carry) => self.xor(propagate[0], carry),
+            None => propagate[0],
+        });
+        for i in 1..a.len() {
+            sum.push(self.xor(propagate[i], generate[i - 1]));
+        }
+        (sum, generate[a.len() - 1])
+    }
+
+    /// Multiplies two words with an array multiplier, which adds the partial products row by row
+    /// with ripple-carry adders. The product has the width of both factors combined.
+    pub fn array_multiply(&mut self, a: &[Signal], b: &[Signal]) -> Vec<Signal> {
+        let width = a.len() + b.len();
+        let Some((first, rest)) = b.split_first() else {
+            return vec![self.constant(false); width];
+        };
+        let mut accumulator = Vec::from_iter(a.iter().map(|a| self.and(*a, *first)));
+        for (row, b) in rest.iter().enumerate() {
+            let shift = row + 1;
+            let mut columns = vec![Vec::new(); (a.len() + shift + 1).min(width)];
+            for (i, bit) in accumulator.iter().enumerate() {
+                columns[i].push(*bit);
+            }
+            for (i, a) in a.iter().enumerate() {
+                let partial = self.and(*a, *b);
+                columns[i + shift].push(partial);
+            }
+            accumulator = self.add_columns(columns);
+        }
+        accumulator.resize_with(width, || self.constant(false));
+        accumulator
+    }
+
+    /// Multiplies two words with a Wallace tree multiplier, which reduces the partial products
+    /// with layers of full adders before adding the two remaining rows. The product has the width
+    /// of both factors combined.
+    pub fn wallace_multiply(&mut self, a: &[Signal], b: &[Signal]) -> Vec<Signal> {
+        let mut columns = vec![Vec::new(); a.len() + b.len()];
+        for (i, a) in a.iter().enumerate() {
+            for (j, b) in b.iter().enumerate() {
+                let partial = self.and(*a, *b);
+                columns[i + j].push(partial);
+            }
+        }
+        self.add_columns(columns)
+    }
+
+    /// Returns whether `a` is smaller than `b`, interpreting both words as unsigned numbers.
+    pub fn less_than(&mut self, a: &[Signal], b: &[Signal]) -> Signal {
+        assert_eq!(
+            a.len(),
+            b.len(),
+            "compared words should have the same width"
+        );
+        // the borrow of a - b
+        let mut borrow = None;
+        for (a, b) in a.iter().zip(b) {
+            borrow = Some(match borrow {
+                Some(borrow) => self.maj(!*a, *b, borrow),
+                None => self.and(!*a, *b),
+            });
+        }
+        borrow.unwrap_or_else(|| self.constant(false))
+    }
+
+    /// Returns whether the two words are equal.
+    pub fn equal(&mut self, a: &[Signal], b: &[Signal]) -> Signal {
+        assert_eq!(
+            a.len(),
+            b.len(),
+            "compared words should have the same width"
+        );
+        let bits = Vec::from_iter(a.iter().zip(b).map(|(a, b)| self.xnor(*a, *b)));
+        self.and_all(&bits)
+    }
+
+    /// Returns whether the unsigned number `a` is at least `value`.
+    pub fn at_least(&mut self, a: &[Signal], value: u64) -> Signal {
+        if a.len() < 64 && value >> a.len() != 0 {
+            return self.constant(false);
+        }
+        // `None` stands for constant true
+        let mut result = None;
+        for (i, bit) in a.iter().enumerate() {
+            result = match (value >> i) & 1 == 1 {
+                true => Some(match result {
+                    Some(result) => self.and(*bit, result),
+                    None => *bit,
+                }),
+                false => result.map(|result| self.or(*bit, result)),
+            };
+        }
+        result.unwrap_or_else(|| self.constant(true))
+    }
+
+    /// Selects the data signal whose index is given by the `select` word using a tree of 2:1
+    /// multiplexers.
+    ///
+    /// # Panics
+    /// Panics if there are not `2^select.len()` data signals.
+    pub fn multiplex(&mut self, select: &[Signal], data: &[Signal]) -> Signal {
+        assert_eq!(
+            data.len(),
+            1 << select.len(),
+            "there should be a data signal for every value of the select word"
+        );
+        let mut data = data.to_vec();
+        for select in select {
+            data = Vec::from_iter(
+                data.chunks(2)
+                    .map(|pair| self.mux(*select, pair[1], pair[0])),
+            );
+        }
+        data[0]
+    }
+
+    /// Counts the signals that are true. Returns a word that is just wide enough to hold the
+    /// number of signals.
+    pub fn count_ones(&mut self, signals: &[Signal]) -> Vec<Signal> {
+        let width = (usize::BITS - signals.len().leading_zeros()) as usize;
+        let mut columns = vec![Vec::new(); width];
+        if let Some(column) = columns.first_mut() {
+            column.extend_from_slice(signals);
+        }
+        self.add_columns(columns)
+    }
+
+    /// Returns whether more than half of the signals are true. Uses a single majority gate for
+    /// three signals and compares the number of true signals otherwise.
+    pub fn majority(&mut self, signals: &[Signal]) -> Signal {
+        match *signals {
+            [signal] => signal,
+            [a, b, c] => self.maj(a, b, c),
+            _ => {
+                let count = self.count_ones(signals);
+                self.at_least(&count, signals.len() as u64 / 2 + 1)
+            }
+        }
+    }
+
+    fn generate_propagate(&mut self, a: &[Signal], b: &[Signal]) -> (Vec<Signal>, Vec<Signal>) {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| (self.and(*a, *b), self.xor(*a, *b)))
+            .unzip()
+    }
+
+    /// Adds the bits of the given columns, where column `i` has weight `2^i`, by reducing the
+    /// columns to at most two bits with layers of full adders and adding the remaining bits with
+    /// a ripple-carry adder. Carries out of the last column are dropped.
+    fn add_columns(&mut self, mut columns: Vec<Vec<Signal>>) -> Vec<Signal> {
+        while columns.iter().any(|column| column.len() > 2) {
+            let mut next = vec![Vec::new(); columns.len()];
+            for (i, column) in columns.iter().enumerate() {
+                for bits in column.chunks(3) {
+                    match *bits {
+                        [a, b, c] => {
+                            let (sum, carry) = self.full_adder(a, b, c);
+                            next[i].push(sum);
+                            if let Some(column) = next.get_mut(i + 1) {
+                                column.push(carry);
+                            }
+                        }
+                        _ => next[i].extend_from_slice(bits),
+                    }
+                }
+            }
+            columns = next;
+        }
+        let mut result = Vec::with_capacity(columns.len());
+        let mut carry = None;
+        for mut column in columns {
+            column.extend(carry.take());
+            match *column {
+                [] => result.push(self.constant(false)),
+                [bit] => result.push(bit),
+                [a, b] => {
+                    let (sum, carry_out) = self.half_adder(a, b);
+                    result.push(sum);
+                    carry = Some(carry_out);
+                }
+                [a, b, c] => {
+                    let (sum, carry_out) = self.full_adder(a, b, c);
+                    result.push(sum);
+                    carry = Some(carry_out);
+                }
+                _ => unreachable!("columns should have been reduced to two bits"),
+            }
+        }
+        result
+    }
+
+    fn named_word(&mut self, prefix: &str, width: usize) -> Vec<Signal> {
+        Vec::from_iter((0..width).map(|i| self.named_pi(&format!("{prefix}{i}"))))
+    }
+
+    fn named_outputs(&mut self, prefix: &str, word: &[Signal]) {
+        for (i, signal) in word.iter().enumerate() {
+            self.named_po(*signal, &format!("{prefix}{i}"));
+        }
+    }
+}
+
+type Adder<R> =
+    fn(&mut NetworkBuilder<R>, &[Signal], &[Signal], Option<Signal>) -> (Vec<Signal>, Signal);
+
+fn adder<R: Receiver>(receiver: R, width: usize, add: Adder<R>) -> R::Result {
+    let mut builder = NetworkBuilder::new(receiver);
+    let a = builder.named_word("a", width);
+    let b = builder.named_word("b", width);
+    let (sum, carry) = add(&mut builder, &a, &b, None);
+    builder.named_outputs("s", &sum);
+    builder.named_po(carry, "cout");
+    builder.done()
+}
+
+/// Generates a ripple-carry adder of two `width`-bit numbers.
+///
+/// The inputs are `a0..a{width-1}` followed by `b0..b{width-1}`, least significant bit first. The
+/// outputs are the sum bits `s0..s{width-1}` followed by the carry out `cout`.
+pub fn ripple_carry_adder<R: Receiver>(receiver: R, width: usize) -> R::Result {
+    adder(receiver, width, NetworkBuilder::ripple_carry_add)
+}
+
+/// Generates a carry-lookahead adder of two `width`-bit numbers with 4-bit lookahead units. See
+/// [`ripple_carry_adder`] for the interface.
+pub fn carry_lookahead_adder<R: Receiver>(receiver: R, width: usize) -> R::Result {
+    adder(receiver, width, NetworkBuilder::carry_lookahead_add)
+}
+
+/// Generates a Kogge-Stone adder of two `width`-bit numbers. See [`ripple_carry_adder`] for the
+/// interface.
+pub fn kogge_stone_adder<R: Receiver>(receiver: R, width: usize) -> R::Result {
+    adder(receiver, width, NetworkBuilder::kogge_stone_add)
+}
+
+type Multiplier<R> = fn(&mut NetworkBuilder<R>, &[Signal], &[Signal]) -> Vec<Signal>;
+
+fn multiplier<R: Receiver>(receiver: R, width: usize, multiply: Multiplier<R>) -> R::Result {
+    let mut builder = NetworkBuilder::new(receiver);
+    let a = builder.named_word("a", width);
+    let b = builder.named_word("b", width);
+    let product = multiply(&mut builder, &a, &b);
+    builder.named_outputs("p", &product);
+    builder.done()
+}
+
+/// Generates an array multiplier of two `width`-bit numbers.
+///
+/// The inputs are `a0..a{width-1}` followed by `b0..b{width-1}`, least significant bit first. The
+/// outputs are the `2 * width` product bits `p0..p{2*width-1}`.
+pub fn array_multiplier<R: Receiver>(receiver: R, width: usize) -> R::Result {
+    multiplier(receiver, width, NetworkBuilder::array_multiply)
+}
+
+/// Generates a Wallace tree multiplier of two `width`-bit numbers. See [`array_multiplier`] for
+/// the interface.
+pub fn wallace_multiplier<R: Receiver>(receiver: R, width: usize) -> R::Result {
+    multiplier(receiver, width, NetworkBuilder::wallace_multiply)
+}
+
+/// Generates an unsigned comparator of two `width`-bit numbers.
+///
+/// The inputs are `a0..a{width-1}` followed by `b0..b{width-1}`, least significant bit first. The
+/// outputs are `lt`, `eq` and `gt`, which indicate whether `a` is smaller than, equal to or
+/// greater than `b`.
+pub fn comparator<R: Receiver>(receiver: R, width: usize) -> R::Result {
+    let mut builder = NetworkBuilder::new(receiver);
+    let a = builder.named_word("a", width);
+    let b = builder.named_word("b", width);
+    let less = builder.less_than(&a, &b);
+    let equal = builder.equal(&a, &b);
+    let greater = builder.less_than(&b, &a);
+    builder.named_po(less, "lt");
+    builder.named_po(equal, "eq");
+    builder.named_po(greater, "gt");
+    builder.done()
+}
+
+/// Generates a multiplexer that selects one of `2^select_width` data inputs.
+///
+/// The inputs are the select bits `s0..s{select_width-1}`, least significant bit first, followed by
+/// the data inputs `d0..d{2^select_width-1}`. The output `y` is the selected data input.
+pub fn multiplexer<R: Receiver>(receiver: R, select_width: usize) -> R::Result {
+    let mut builder = NetworkBuilder::new(receiver);
+    let select = builder.named_word("s", select_width);
+    let data = builder.named_word("d", 1 << select_width);
+    let output = builder.multiplex(&select, &data);
+    builder.named_po(output, "y");
+    builder.done()
+}
+
+/// Generates a circuit that counts the number of true inputs.
+///
+/// The inputs are `x0..x{width-1}`. The outputs are the bits `c0..` of the count, least
+/// significant bit first, and just enough of them to represent `width`.
+pub fn popcount<R: Receiver>(receiver: R, width: usize) -> R::Result {
+    let mut builder = NetworkBuilder::new(receiver);
+    let inputs = builder.named_word("x", width);
+    let count = builder.count_ones(&inputs);
+    builder.named_outputs("c", &count);
+    builder.done()
+}
+
+/// Generates a circuit that computes whether more than half of its `width` inputs are true.
+///
+/// The inputs are `x0..x{width-1}` and the output is `y`.
+pub fn majority<R: Receiver>(receiver: R, width: usize) -> R::Result {
+    let mut builder = NetworkBuilder::new(receiver);
+    let inputs = builder.named_word("x", width);
+    let output = builder.majority(&inputs);
+    builder.named_po(output, "y");
+    builder.done()
+}
@@ -0,0 +1,566 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use super::netlist::{Netlist, Unresolved};
+use super::MAX_INPUTS;
+use crate::{
+    Aig, ChoiceNetwork, Id, Network, NetworkBuilder, Node, Receiver, Register, Signal,
+    TopologicalView,
+};
+
+/// An error that occurred while reading an AIGER file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AigerError {
+    /// The byte offset in the source at which the error occurred.
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for AigerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "position {}: {}", self.position, self.message)
+    }
+}
+
+impl Error for AigerError {}
+
+/// Writes the given choice network in the binary AIGER format.
+///
+/// The choices are stored in the `q` extension that is understood by ABC (e.g. `&r` followed by
+/// `&if` or `&nf`). Only choices between AND gates are written. In the written AIG, the choices of
+/// a node directly precede it and are linked to it in order of decreasing variable index, as
+/// expected by ABC. Note that ABC recomputes the phases of the choices itself.
+pub fn write_aiger_with_choices(
+    network: &ChoiceNetwork<Aig>,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    let num_inputs = network.num_inputs() as u32;
+    let mut literals: Vec<u32> = vec![0; network.size()];
+    let mut ands: Vec<[u32; 3]> = Vec::new();
+    let mut siblings: Vec<(u32, u32)> = Vec::new();
+
+    for id in network.ids() {
+        if network.is_choice(id) {
+            continue;
+        }
+        match network.node(id) {
+            Aig::Input(index) => literals[u32::from(id) as usize] = 2 * (index as u32 + 1),
+            Aig::False => literals[u32::from(id) as usize] = 0,
+            Aig::And(_) => {
+                let mut previous = None;
+                for choice in network.choices(id) {
+                    if !matches!(network.node(choice.node_id()), Aig::And(_)) {
+                        continue;
+                    }
+                    let var = add_and(network, choice.node_id(), &mut literals, &mut ands);
+                    if let Some(previous) = previous {
+                        siblings.push((var, previous));
+                    }
+                    previous = Some(var);
+                }
+                let var = add_and(network, id, &mut literals, &mut ands);
+                if let Some(previous) = previous {
+                    siblings.push((var, previous));
+                }
+            }
+        }
+    }
+
+    let outputs = Vec::from_iter(network.outputs());
+    writeln!(
+        writer,
+        "aig {} {} 0 {} {}",
+        num_inputs + ands.len() as u32,
+        num_inputs,
+        outputs.len(),
+        ands.len()
+    )?;
+    for output in outputs {
+        let literal = literals[u32::from(output.node_id()) as usize] ^ output.is_inverted() as u32;
+        writeln!(writer, "{literal}")?;
+    }
+    for [lhs, rhs0, rhs1] in ands {
+        write_delta(&mut writer, lhs - rhs0)?;
+        write_delta(&mut writer, rhs0 - rhs1)?;
+    }
+    if !siblings.is_empty() {
+        writer.write_all(b"cq")?;
+        writer.write_all(&(4 * (2 * siblings.len() as u32 + 1)).to_be_bytes())?;
+        writer.write_all(&(siblings.len() as u32).to_be_bytes())?;
+        for (node, sibling) in siblings {
+            writer.write_all(&node.to_be_bytes())?;
+            writer.write_all(&sibling.to_be_bytes())?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// Appends the AND gate with the given id and returns its variable index.
+fn add_and(
+    network: &ChoiceNetwork<Aig>,
+    id: Id,
+    literals: &mut [u32],
+    ands: &mut Vec<[u32; 3]>,
+) -> u32 {
+    let Aig::And(inputs) = network.node(id) else {
+        unreachable!("only AND gates are added")
+    };
+    let [a, b] = inputs.map(|s| literals[u32::from(s.node_id()) as usize] ^ s.is_inverted() as u32);
+    let lhs = 2 * (network.num_inputs() as u32 + 1 + ands.len() as u32);
+    ands.push([lhs, a.max(b), a.min(b)]);
+    literals[u32::from(id) as usize] = lhs;
+    lhs / 2
+}
+
+fn write_delta(writer: &mut impl Write, mut delta: u32) -> io::Result<()> {
+    while delta & !0x7f != 0 {
+        writer.write_all(&[(delta & 0x7f) as u8 | 0x80])?;
+        delta >>= 7;
+    }
+    writer.write_all(&[delta as u8])
+}
+
+/// Writes the given AIG in the binary AIGER format, including its registers as latches and the
+/// names of its primary inputs, register outputs and primary outputs as symbols.
+///
+/// # Example
+/// ```
+/// use eggmock::{read_aiger, ripple_carry_adder, simulate_truth_tables, write_aiger};
+/// use eggmock::{Aig, MutableNetwork, Network};
+///
+/// let adder = ripple_carry_adder(MutableNetwork::<Aig>::default(), 4);
+/// let mut bytes = Vec::new();
+/// write_aiger(&adder, &mut bytes).unwrap();
+/// let read = read_aiger(MutableNetwork::<Aig>::default(), &bytes).unwrap();
+/// assert_eq!(simulate_truth_tables(&read), simulate_truth_tables(&adder));
+/// assert_eq!(read.input_name(0), adder.input_name(0));
+/// ```
+pub fn write_aiger<N: Network<Node = Aig> + ?Sized>(
+    network: &N,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    let num_inputs = network.num_inputs();
+    let num_registers = network.num_registers();
+    let outputs = Vec::from_iter(network.outputs());
+    let Some(num_outputs) = outputs.len().checked_sub(num_registers as usize) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the network has fewer outputs than registers",
+        ));
+    };
+
+    let mut literals: FxHashMap<Id, u64> = FxHashMap::default();
+    let mut ands: Vec<[u64; 3]> = Vec::new();
+    for id in TopologicalView::new(network).order() {
+        let literal = match network.node(*id) {
+            Aig::Input(index) => 2 * (index + 1),
+            Aig::False => 0,
+            Aig::And(inputs) => {
+                let [a, b] = inputs.map(|s| literals[&s.node_id()] ^ s.is_inverted() as u64);
+                let lhs = 2 * (num_inputs + num_registers + 1 + ands.len() as u64);
+                ands.push([lhs, a.max(b), a.min(b)]);
+                lhs
+            }
+        };
+        literals.insert(*id, literal);
+    }
+    let literal = |signal: Signal| literals[&signal.node_id()] ^ signal.is_inverted() as u64;
+
+    writeln!(
+        writer,
+        "aig {} {num_inputs} {num_registers} {num_outputs} {}",
+        num_inputs + num_registers + ands.len() as u64,
+        ands.len()
+    )?;
+    for (index, next) in outputs[num_outputs..].iter().enumerate() {
+        write!(writer, "{}", literal(*next))?;
+        match network.register(index as u64).init {
+            Some(false) => writeln!(writer)?,
+            Some(true) => writeln!(writer, " 1")?,
+            None => writeln!(writer, " {}", 2 * (num_inputs + 1 + index as u64))?,
+        }
+    }
+    for output in &outputs[..num_outputs] {
+        writeln!(writer, "{}", literal(*output))?;
+    }
+    for [lhs, rhs0, rhs1] in ands {
+        write_delta(&mut writer, (lhs - rhs0) as u32)?;
+        write_delta(&mut writer, (rhs0 - rhs1) as u32)?;
+    }
+    for input in 0..num_inputs {
+        if let Some(name) = network.input_name(input) {
+            writeln!(writer, "i{input} {name}")?;
+        }
+    }
+    for latch in 0..num_registers {
+        if let Some(name) = network.input_name(num_inputs + latch) {
+            writeln!(writer, "l{latch} {name}")?;
+        }
+    }
+    for output in 0..num_outputs {
+        if let Some(name) = network.output_name(output) {
+            writeln!(writer, "o{output} {name}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads a combinational or sequential AIG in the ASCII (`aag`) or binary (`aig`) AIGER format
+/// and sends it to the given receiver.
+///
+/// Latches become registers (see [`Register`]) and the input, latch and output symbols become
+/// names, where latch symbols name the register outputs. Comments are ignored. Bad state, constraint, justice and fairness properties
+/// are not supported. In ASCII files, the AND gates may be defined in any order. For networks
+/// without AND gates, they are built as described in [`NetworkBuilder`].
+///
+/// # Example
+/// ```
+/// use eggmock::{read_aiger, Aig, MutableNetwork, Network};
+///
+/// let source = "aag 3 1 1 1 1\n2\n4 6\n6\n6 2 4\ni0 enable\nl0 state\no0 out\n";
+/// let aig = read_aiger(MutableNetwork::<Aig>::default(), source.as_bytes()).unwrap();
+/// assert_eq!((aig.num_inputs(), aig.num_registers()), (1, 1));
+/// assert_eq!(aig.input_name(0), Some("enable"));
+/// assert_eq!(aig.input_name(1), Some("state"));
+/// assert_eq!(aig.output_name(0), Some("out"));
+/// ```
+pub fn read_aiger<R: Receiver>(mut receiver: R, source: &[u8]) -> Result<R::Result, AigerError> {
+    let mut reader = Reader {
+        source,
+        position: 0,
+    };
+    let binary = match source.get(..3) {
+        Some(b"aag") => false,
+        Some(b"aig") => true,
+        _ => return Err(reader.error("not an AIGER file")),
+    };
+    reader.position = 3;
+    let max_var = reader.number()?;
+    let num_inputs = reader.number()?;
+    let num_latches = reader.number()?;
+    let num_outputs = reader.number()?;
+    let num_ands = reader.number()?;
+    while !reader.at_line_end() {
+        if reader.number()? != 0 {
+            return Err(reader.error(
+                "bad state, constraint, justice and fairness properties are not supported",
+            ));
+        }
+    }
+    reader.end_of_line()?;
+    let num_vars = num_inputs
+        .checked_add(num_latches)
+        .and_then(|vars| vars.checked_add(num_ands));
+    if num_vars.is_none_or(|vars| vars > max_var) {
+        return Err(
+            reader.error("the maximum variable index is smaller than the number of variables")
+        );
+    }
+    if num_inputs + num_latches > MAX_INPUTS {
+        return Err(reader.error("too many inputs and latches"));
+    }
+    if binary && num_vars != Some(max_var) {
+        return Err(reader.error("binary AIGER files require M = I + L + A"));
+    }
+
+    let mut defined = FxHashSet::default();
+    let mut define = |position: usize, literal: u64| {
+        if literal < 2 || literal % 2 == 1 || literal / 2 > max_var {
+            return Err(AigerError {
+                position,
+                message: format!("invalid literal {literal} for a variable definition"),
+            });
+        }
+        if !defined.insert(literal / 2) {
+            return Err(AigerError {
+                position,
+                message: format!("variable {} is defined twice", literal / 2),
+            });
+        }
+        Ok(literal)
+    };
+
+    // the indices of the inputs and latch outputs by their variables, which are implicit in binary
+    // files and only created once they are used
+    let mut input_indices = FxHashMap::default();
+    if !binary {
+        for input in 0..num_inputs {
+            let position = reader.skip_spaces();
+            let literal = reader.number()?;
+            reader.end_of_line()?;
+            input_indices.insert(define(position, literal)? / 2, input);
+        }
+    }
+
+    let mut latches = Vec::new();
+    let mut registers = Vec::new();
+    for latch in 0..num_latches {
+        let literal = if binary {
+            2 * (num_inputs + latch + 1)
+        } else {
+            let position = reader.skip_spaces();
+            let literal = define(position, reader.number()?)?;
+            input_indices.insert(literal / 2, num_inputs + latch);
+            literal
+        };
+        let next = (reader.skip_spaces(), reader.literal(max_var)?);
+        let init = if reader.at_line_end() {
+            Some(false)
+        } else {
+            match reader.number()? {
+                0 => Some(false),
+                1 => Some(true),
+                init if init == literal => None,
+                init => return Err(reader.error(format!("invalid initial value {init}"))),
+            }
+        };
+        reader.end_of_line()?;
+        latches.push((literal, next));
+        registers.push(Register { init });
+    }
+
+    let mut outputs = Vec::new();
+    for _ in 0..num_outputs {
+        outputs.push((reader.skip_spaces(), reader.literal(max_var)?));
+        reader.end_of_line()?;
+    }
+
+    let mut netlist = Netlist::new();
+    for and in 0..num_ands {
+        let position = if binary {
+            reader.position
+        } else {
+            reader.skip_spaces()
+        };
+        let (lhs, rhs) = if binary {
+            let lhs = 2 * (num_inputs + num_latches + and + 1);
+            let rhs0 = lhs.checked_sub(reader.delta()?).filter(|rhs0| *rhs0 < lhs);
+            let rhs0 = rhs0.ok_or_else(|| reader.error("invalid delta"))?;
+            let rhs1 = rhs0.checked_sub(reader.delta()?);
+            let rhs1 = rhs1.ok_or_else(|| reader.error("invalid delta"))?;
+            (lhs, [rhs0, rhs1])
+        } else {
+            let lhs = define(position, reader.number()?)?;
+            let rhs = [reader.literal(max_var)?, reader.literal(max_var)?];
+            reader.end_of_line()?;
+            (lhs, rhs)
+        };
+        netlist.definitions.insert(lhs / 2, (position, rhs));
+    }
+
+    let mut input_names = Vec::new();
+    let mut output_names = Vec::new();
+    let mut latch_names = Vec::new();
+    while let Some(kind) = reader.source.get(reader.position).copied() {
+        let position = reader.position;
+        let (names, count, kind) = match kind {
+            b'i' => (&mut input_names, num_inputs, "input"),
+            b'o' => (&mut output_names, num_outputs, "output"),
+            b'l' => (&mut latch_names, num_latches, "latch"),
+            b'c' => break,
+            _ => return Err(reader.error("expected a symbol or the comment section")),
+        };
+        reader.position += 1;
+        let index = reader.number()?;
+        if index >= count {
+            return Err(AigerError {
+                position,
+                message: format!("symbol of unknown {kind} {index}"),
+            });
+        }
+        if reader.source.get(reader.position) != Some(&b' ') {
+            return Err(reader.error("expected a symbol name"));
+        }
+        reader.position += 1;
+        let name = reader.rest_of_line();
+        let name = std::str::from_utf8(name).map_err(|_| AigerError {
+            position,
+            message: "symbol name is not valid UTF-8".to_string(),
+        })?;
+        names.push((index, name));
+    }
+
+    receiver.set_num_inputs(num_inputs);
+    if num_latches != 0 {
+        receiver.set_registers(&registers);
+    }
+    let mut builder = NetworkBuilder::new(receiver);
+    let input_index = |var: u64| match binary {
+        true => (1..=num_inputs + num_latches).contains(&var).then(|| var - 1),
+        false => input_indices.get(&var).copied(),
+    };
+    let used_literals = netlist
+        .definitions
+        .values()
+        .flat_map(|(_, rhs)| rhs)
+        .chain(outputs.iter().map(|(_, literal)| literal))
+        .chain(latches.iter().map(|(_, (_, next))| next));
+    let mut used_inputs = Vec::from_iter(
+        used_literals.filter_map(|literal| Some((input_index(literal / 2)?, literal / 2))),
+    );
+    used_inputs.sort_unstable();
+    used_inputs.dedup();
+    for (index, var) in used_inputs {
+        let input = builder.create(R::Node::input(index));
+        netlist.signals.insert(var, input);
+    }
+    let mut signals = Vec::new();
+    let latch_outputs = latches.iter().map(|(_, next)| next);
+    for (position, literal) in outputs.iter().chain(latch_outputs) {
+        let signal = if *literal < 2 {
+            builder.constant(*literal == 1)
+        } else {
+            let resolved = netlist.resolve(
+                &(literal / 2),
+                &mut builder,
+                |(_, rhs)| Vec::from_iter(rhs.iter().map(|rhs| rhs / 2).filter(|var| *var != 0)),
+                |(_, rhs), signals, builder| {
+                    let [a, b] = rhs.map(|rhs| match rhs / 2 {
+                        0 => builder.constant(rhs == 1),
+                        var => signals[&var].maybe_invert(rhs % 2 == 1),
+                    });
+                    builder.and(a, b)
+                },
+            );
+            let definition_position = |var| netlist.definitions[&var].0;
+            match resolved {
+                Ok(signal) => signal.maybe_invert(literal % 2 == 1),
+                Err(Unresolved::Undefined { key, user }) => {
+                    return Err(AigerError {
+                        position: user.map_or(*position, definition_position),
+                        message: format!("variable {key} is used but not defined"),
+                    })
+                }
+                Err(Unresolved::Cycle(key)) => {
+                    return Err(AigerError {
+                        position: definition_position(key),
+                        message: format!("variable {key} depends on itself"),
+                    })
+                }
+            }
+        };
+        signals.push(signal);
+    }
+    for (input, name) in input_names {
+        builder.receiver_mut().set_input_name(input, name);
+    }
+    for (latch, name) in latch_names {
+        builder
+            .receiver_mut()
+            .set_input_name(num_inputs + latch, name);
+    }
+    for (output, name) in output_names {
+        builder
+            .receiver_mut()
+            .set_output_name(output as usize, name);
+    }
+    Ok(builder.into_receiver().done(&signals))
+}
+
+struct Reader<'s> {
+    source: &'s [u8],
+    position: usize,
+}
+
+impl<'s> Reader<'s> {
+    fn error(&self, message: impl Into<String>) -> AigerError {
+        AigerError {
+            position: self.position,
+            message: message.into(),
+        }
+    }
+
+    /// Skips spaces and returns the new position.
+    fn skip_spaces(&mut self) -> usize {
+        while matches!(self.source.get(self.position), Some(b' ' | b'\t')) {
+            self.position += 1;
+        }
+        self.position
+    }
+
+    fn at_line_end(&mut self) -> bool {
+        self.skip_spaces();
+        matches!(self.source.get(self.position), None | Some(b'\n' | b'\r'))
+    }
+
+    fn end_of_line(&mut self) -> Result<(), AigerError> {
+        if !self.at_line_end() {
+            return Err(self.error("expected the end of the line"));
+        }
+        if self.source.get(self.position) == Some(&b'\r') {
+            self.position += 1;
+        }
+        match self.source.get(self.position) {
+            Some(b'\n') => self.position += 1,
+            Some(_) => return Err(self.error("expected the end of the line")),
+            None => {}
+        }
+        Ok(())
+    }
+
+    fn number(&mut self) -> Result<u64, AigerError> {
+        let start = self.skip_spaces();
+        while self
+            .source
+            .get(self.position)
+            .is_some_and(u8::is_ascii_digit)
+        {
+            self.position += 1;
+        }
+        let digits = std::str::from_utf8(&self.source[start..self.position]).unwrap();
+        if digits.is_empty() {
+            return Err(self.error("expected a number"));
+        }
+        digits.parse().map_err(|_| AigerError {
+            position: start,
+            message: "number is too large".to_string(),
+        })
+    }
+
+    /// Reads a literal that refers to a variable up to `max_var`.
+    fn literal(&mut self, max_var: u64) -> Result<u64, AigerError> {
+        let start = self.skip_spaces();
+        let literal = self.number()?;
+        if literal / 2 > max_var {
+            return Err(AigerError {
+                position: start,
+                message: format!("literal {literal} exceeds the maximum variable index"),
+            });
+        }
+        Ok(literal)
+    }
+
+    fn delta(&mut self) -> Result<u64, AigerError> {
+        let start = self.position;
+        let mut delta = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self
+                .source
+                .get(self.position)
+                .ok_or_else(|| self.error("unexpected end of input"))?;
+            self.position += 1;
+            delta |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(delta);
+            }
+        }
+        Err(AigerError {
+            position: start,
+            message: "delta is too large".to_string(),
+        })
+    }
+
+    /// Returns the rest of the current line without the line break and moves to the next line.
+    fn rest_of_line(&mut self) -> &'s [u8] {
+        let rest = &self.source[self.position..];
+        let length = rest.iter().position(|c| *c == b'\n').unwrap_or(rest.len());
+        self.position += (length + 1).min(rest.len());
+        rest[..length]
+            .strip_suffix(b"\r")
+            .unwrap_or(&rest[..length])
+    }
+}
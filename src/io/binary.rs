@@ -0,0 +1,368 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+
+use rustc_hash::FxHashMap;
+
+use super::MAX_INPUTS;
+use crate::{GateType, Id, Network, Node, Receiver, Register, Signal, TopologicalView};
+
+const MAGIC: &[u8; 4] = b"EGMK";
+const VERSION: u64 = 1;
+
+/// An error that occurred while reading a network in the binary format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryError {
+    /// The byte offset in the source at which the error occurred.
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for BinaryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "position {}: {}", self.position, self.message)
+    }
+}
+
+impl Error for BinaryError {}
+
+/// Writes the given network in eggmock's compact binary format, which can be read with
+/// [`read_binary`].
+///
+/// All numbers are unsigned LEB128 varints. The nodes are numbered with the constant as `0`,
+/// followed by the primary inputs and register outputs and then the gates in topological order,
+/// and signals are literals `2 * node + inverted` as in the AIGER format. The file consists of
+/// - the magic bytes `EGMK` and the format version (currently `1`),
+/// - the [`NTK_TYPENAME`](Node::NTK_TYPENAME) of the network type as a string, i.e. its length in
+///   bytes followed by its UTF-8 bytes,
+/// - the number of primary inputs and of registers, followed by one byte per register with its
+///   initial value (see [`Register::mockturtle_init`]),
+/// - the number of gates and, for each gate, the index of its gate type in
+///   [`GateType::VARIANTS`] followed by the delta `2 * gate - literal` for each of its inputs,
+/// - the number of outputs and their literals,
+/// - the number of named primary inputs and register outputs and a pair of input index and name
+///   for each and the same for the named outputs.
+///
+/// Fails with [`io::ErrorKind::InvalidInput`] if the network contains gates that cannot be
+/// recreated from their gate type with [`Node::from_gate`], such as [`Klut`](crate::Klut)
+/// look-up tables.
+///
+/// # Example
+/// ```
+/// use eggmock::{read_binary, simulate_truth_tables, wallace_multiplier, write_binary};
+/// use eggmock::{MutableNetwork, Network, Xmg};
+///
+/// let xmg = wallace_multiplier(MutableNetwork::<Xmg>::default(), 3);
+/// let mut bytes = Vec::new();
+/// write_binary(&xmg, &mut bytes).unwrap();
+/// let read = read_binary(MutableNetwork::<Xmg>::default(), &bytes).unwrap();
+/// assert_eq!(simulate_truth_tables(&read), simulate_truth_tables(&xmg));
+/// assert_eq!(read.input_name(0), Some("a0"));
+/// ```
+pub fn write_binary<N: Network + ?Sized>(network: &N, mut writer: impl Write) -> io::Result<()> {
+    let num_inputs = network.num_inputs();
+    let num_registers = network.num_registers();
+    let mut indices: FxHashMap<Id, u64> = FxHashMap::default();
+    let mut gates = Vec::new();
+    for id in TopologicalView::new(network).order() {
+        let node = network.node(*id);
+        let index = if let Some(input) = node.input_index() {
+            input + 1
+        } else if let Some(gate) = node.gate_type() {
+            if N::Node::from_gate(gate, node.inputs()).as_ref() != Some(&node) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{node:?} cannot be recreated from its gate type"),
+                ));
+            }
+            gates.push((gate, node));
+            num_inputs + num_registers + gates.len() as u64
+        } else {
+            0
+        };
+        indices.insert(*id, index);
+    }
+    let literal = |signal: Signal| 2 * indices[&signal.node_id()] + signal.is_inverted() as u64;
+
+    writer.write_all(MAGIC)?;
+    write_number(&mut writer, VERSION)?;
+    write_string(&mut writer, N::Node::NTK_TYPENAME)?;
+    write_number(&mut writer, num_inputs)?;
+    write_number(&mut writer, num_registers)?;
+    for register in 0..num_registers {
+        writer.write_all(&[network.register(register).mockturtle_init()])?;
+    }
+
+    write_number(&mut writer, gates.len() as u64)?;
+    for (gate_index, (gate, node)) in gates.iter().enumerate() {
+        let index = num_inputs + num_registers + gate_index as u64 + 1;
+        let type_index = <N::Node as Node>::Gates::VARIANTS
+            .iter()
+            .position(|variant| variant == gate)
+            .expect("gate types should be part of their variants");
+        write_number(&mut writer, type_index as u64)?;
+        for input in node.inputs() {
+            write_number(&mut writer, 2 * index - literal(*input))?;
+        }
+    }
+
+    let outputs = Vec::from_iter(network.outputs());
+    write_number(&mut writer, outputs.len() as u64)?;
+    for output in &outputs {
+        write_number(&mut writer, literal(*output))?;
+    }
+
+    let input_names = Vec::from_iter(
+        (0..num_inputs + num_registers)
+            .filter_map(|input| Some((input, network.input_name(input)?))),
+    );
+    write_number(&mut writer, input_names.len() as u64)?;
+    for (input, name) in input_names {
+        write_number(&mut writer, input)?;
+        write_string(&mut writer, name)?;
+    }
+    let output_names = Vec::from_iter(
+        (0..outputs.len()).filter_map(|output| Some((output, network.output_name(output)?))),
+    );
+    write_number(&mut writer, output_names.len() as u64)?;
+    for (output, name) in output_names {
+        write_number(&mut writer, output as u64)?;
+        write_string(&mut writer, name)?;
+    }
+    Ok(())
+}
+
+/// Returns whether every gate type of `N` can be recreated with [`Node::from_gate`], i.e. whether
+/// [`write_binary`] supports networks of type `N` at all.
+pub(crate) fn binary_supports<N: Node>() -> bool {
+    N::Gates::VARIANTS.iter().all(|gate| {
+        let inputs = vec![Signal::new(Id::from(0), false); gate.fanin() as usize];
+        N::from_gate(*gate, &inputs).is_some()
+    })
+}
+
+fn write_number(writer: &mut impl Write, mut number: u64) -> io::Result<()> {
+    loop {
+        let byte = (number & 0x7f) as u8;
+        number >>= 7;
+        if number == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn write_string(writer: &mut impl Write, string: &str) -> io::Result<()> {
+    write_number(writer, string.len() as u64)?;
+    writer.write_all(string.as_bytes())
+}
+
+/// Reads a network in the binary format written by [`write_binary`] and sends it to the given
+/// receiver. The network type of the receiver has to match the one of the written network.
+pub fn read_binary<R: Receiver>(mut receiver: R, source: &[u8]) -> Result<R::Result, BinaryError> {
+    let mut reader = Reader {
+        source,
+        position: 0,
+    };
+    if !source.starts_with(MAGIC) {
+        return Err(reader.error("not an eggmock binary network"));
+    }
+    reader.position = MAGIC.len();
+    let version = reader.number()?;
+    if version != VERSION {
+        return Err(reader.error(format!("unsupported format version {version}")));
+    }
+    let network_type = reader.string()?;
+    if network_type != R::Node::NTK_TYPENAME {
+        return Err(reader.error(format!(
+            "expected network type `{}` but found `{network_type}`",
+            R::Node::NTK_TYPENAME
+        )));
+    }
+
+    let num_inputs = reader.number()?;
+    let num_registers = reader.number()?;
+    if num_inputs
+        .checked_add(num_registers)
+        .is_none_or(|inputs| inputs > MAX_INPUTS)
+    {
+        return Err(reader.error("too many inputs"));
+    }
+    // each register has an initial value byte
+    if num_registers > reader.remaining() {
+        return Err(reader.error("unexpected end of input"));
+    }
+    receiver.set_num_inputs(num_inputs);
+    if num_registers != 0 {
+        let registers = (0..num_registers)
+            .map(|_| Ok(Register::from_mockturtle_init(reader.byte()?)))
+            .collect::<Result<Vec<_>, _>>()?;
+        receiver.set_registers(&registers);
+    }
+    // the inputs are only created once they are used, as unused inputs take no space
+    let mut nodes = Nodes {
+        num_inputs: num_inputs + num_registers,
+        inputs: FxHashMap::default(),
+        constant: None,
+        gates: Vec::new(),
+    };
+
+    let num_gates = reader.number()?;
+    // each gate has a gate type
+    if num_gates > reader.remaining() {
+        return Err(reader.error("unexpected end of input"));
+    }
+    for _ in 0..num_gates {
+        let index = nodes.len() + 1;
+        let type_position = reader.position;
+        let gate = <R::Node as Node>::Gates::VARIANTS
+            .get(reader.number()? as usize)
+            .ok_or_else(|| BinaryError {
+                position: type_position,
+                message: "unknown gate type".to_string(),
+            })?;
+        let inputs = (0..gate.fanin())
+            .map(|_| {
+                let delta_position = reader.position;
+                let delta = reader.number()?;
+                if delta == 0 || delta > 2 * index {
+                    return Err(BinaryError {
+                        position: delta_position,
+                        message: "gates may only have preceding nodes as inputs".to_string(),
+                    });
+                }
+                Ok(nodes.signal(2 * index - delta, &mut receiver))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let node = R::Node::from_gate(*gate, &inputs).ok_or_else(|| BinaryError {
+            position: type_position,
+            message: format!("{gate:?} gates cannot be read"),
+        })?;
+        nodes.gates.push(receiver.create_node(node));
+    }
+
+    let num_outputs = reader.number()?;
+    let mut outputs = Vec::new();
+    for _ in 0..num_outputs {
+        let literal_position = reader.position;
+        let literal = reader.number()?;
+        if literal / 2 > nodes.len() {
+            return Err(BinaryError {
+                position: literal_position,
+                message: format!("output refers to unknown node {}", literal / 2),
+            });
+        }
+        outputs.push(nodes.signal(literal, &mut receiver));
+    }
+
+    for _ in 0..reader.number()? {
+        let input = reader.number()?;
+        let name = reader.string()?;
+        if input >= num_inputs + num_registers {
+            return Err(reader.error(format!("name of unknown input {input}")));
+        }
+        receiver.set_input_name(input, name);
+    }
+    for _ in 0..reader.number()? {
+        let output = reader.number()?;
+        let name = reader.string()?;
+        if output >= num_outputs {
+            return Err(reader.error(format!("name of unknown output {output}")));
+        }
+        receiver.set_output_name(output as usize, name);
+    }
+    if reader.position != source.len() {
+        return Err(reader.error("unexpected data after the network"));
+    }
+    Ok(receiver.done(&outputs))
+}
+
+struct Reader<'s> {
+    source: &'s [u8],
+    position: usize,
+}
+
+impl<'s> Reader<'s> {
+    fn error(&self, message: impl Into<String>) -> BinaryError {
+        BinaryError {
+            position: self.position,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the number of bytes that have not been read yet.
+    fn remaining(&self) -> u64 {
+        (self.source.len() - self.position) as u64
+    }
+
+    fn byte(&mut self) -> Result<u8, BinaryError> {
+        let byte = *self
+            .source
+            .get(self.position)
+            .ok_or_else(|| self.error("unexpected end of input"))?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn number(&mut self) -> Result<u64, BinaryError> {
+        let start = self.position;
+        let mut number = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            number |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(number);
+            }
+        }
+        Err(BinaryError {
+            position: start,
+            message: "number is too large".to_string(),
+        })
+    }
+
+    fn string(&mut self) -> Result<&'s str, BinaryError> {
+        let start = self.position;
+        let length = self.number()? as usize;
+        let bytes = self
+            .source
+            .get(self.position..self.position.saturating_add(length))
+            .ok_or_else(|| self.error("unexpected end of input"))?;
+        self.position += length;
+        std::str::from_utf8(bytes).map_err(|_| BinaryError {
+            position: start,
+            message: "string is not valid UTF-8".to_string(),
+        })
+    }
+}
+
+/// The signals of the nodes that have been read so far.
+struct Nodes {
+    num_inputs: u64,
+    /// The signals of the primary inputs and register outputs that have been created.
+    inputs: FxHashMap<u64, Signal>,
+    constant: Option<Signal>,
+    gates: Vec<Signal>,
+}
+
+impl Nodes {
+    /// Returns the number of nodes, not including the constant.
+    fn len(&self) -> u64 {
+        self.num_inputs + self.gates.len() as u64
+    }
+
+    /// Returns the signal for the given literal, which has to refer to a known node, and creates
+    /// the constant or input if it is used for the first time.
+    fn signal<R: Receiver>(&mut self, literal: u64, receiver: &mut R) -> Signal {
+        let signal = match literal / 2 {
+            0 => *self
+                .constant
+                .get_or_insert_with(|| receiver.create_node(R::Node::constant_false())),
+            node if node <= self.num_inputs => *self
+                .inputs
+                .entry(node - 1)
+                .or_insert_with(|| receiver.create_node(R::Node::input(node - 1))),
+            node => self.gates[(node - self.num_inputs - 1) as usize],
+        };
+        signal.maybe_invert(literal % 2 == 1)
+    }
+}
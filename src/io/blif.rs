@@ -0,0 +1,441 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use super::netlist::{Netlist, Unresolved};
+use crate::{
+    GateFunction, GateType, Id, Network, NetworkBuilder, Node, Receiver, Register, TopologicalView,
+    TruthTable,
+};
+
+/// An error that occurred while reading a BLIF file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlifError {
+    /// The line in the source at which the error occurred, starting at 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for BlifError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for BlifError {}
+
+/// A single-output cover of a `.names` command.
+struct Cover {
+    line: usize,
+    inputs: Vec<String>,
+    /// The input patterns of `0`, `1` and `-`.
+    rows: Vec<Vec<u8>>,
+    /// Whether the rows describe the on-set (`1`) or the off-set (`0`) of the function.
+    on_set: bool,
+}
+
+struct Latch {
+    line: usize,
+    input: String,
+    output: String,
+    register: Register,
+}
+
+/// Reads a combinational or sequential network from a flat BLIF file with a single model and
+/// sends it to the given receiver.
+///
+/// The logic is given by `.names` covers, which are built as sums of products with the gates
+/// described in [`NetworkBuilder`]. `.latch`es become registers (see [`Register`]) whose type and
+/// control signal are ignored. Signals may be used before they are defined. Hierarchical models
+/// (`.subckt`), mapped gates (`.gate`) and external don't-cares are not supported.
+///
+/// # Example
+/// ```
+/// use eggmock::{read_blif, simulate_truth_tables, Aig, MutableNetwork, Network, TruthTable};
+///
+/// let blif = "
+/// .model example
+/// .inputs a b c
+/// .outputs f
+/// .names t c f
+/// 1- 1
+/// -1 1
+/// .names a b t
+/// 11 1
+/// .end
+/// ";
+/// let aig = read_blif(MutableNetwork::<Aig>::default(), blif).unwrap();
+/// assert_eq!(aig.output_name(0), Some("f"));
+/// assert_eq!(simulate_truth_tables(&aig), [TruthTable::from_hex(3, "f8").unwrap()]);
+/// ```
+pub fn read_blif<R: Receiver>(mut receiver: R, source: &str) -> Result<R::Result, BlifError> {
+    let mut inputs: Vec<String> = Vec::new();
+    let mut outputs: Vec<(usize, String)> = Vec::new();
+    let mut latches: Vec<Latch> = Vec::new();
+    let mut covers: FxHashMap<String, Cover> = FxHashMap::default();
+    let mut defined: FxHashSet<String> = FxHashSet::default();
+    let mut current: Option<String> = None;
+    let mut model = false;
+    let mut ended = false;
+
+    for (line, text) in logical_lines(source) {
+        let error = |message: String| BlifError { line, message };
+        let mut tokens = text.split_whitespace();
+        let Some(first) = tokens.next() else {
+            continue;
+        };
+        if ended {
+            return Err(error(match first {
+                ".model" => "files with multiple models are not supported".to_string(),
+                _ => "unexpected content after `.end`".to_string(),
+            }));
+        }
+        if !first.starts_with('.') {
+            let cover = current
+                .as_ref()
+                .and_then(|output| covers.get_mut(output))
+                .ok_or_else(|| error("cover row outside of `.names`".to_string()))?;
+            let (pattern, value) = match (cover.inputs.len(), tokens.next()) {
+                (0, None) => ("", first),
+                (_, Some(value)) if !cover.inputs.is_empty() => (first, value),
+                _ => return Err(error("invalid cover row".to_string())),
+            };
+            if tokens.next().is_some() {
+                return Err(error("invalid cover row".to_string()));
+            }
+            if pattern.len() != cover.inputs.len()
+                || !pattern.bytes().all(|c| matches!(c, b'0' | b'1' | b'-'))
+            {
+                return Err(error(format!(
+                    "expected a pattern of `0`, `1` and `-` for {} inputs",
+                    cover.inputs.len()
+                )));
+            }
+            let on_set = match value {
+                "1" => true,
+                "0" => false,
+                _ => return Err(error(format!("invalid output value `{value}`"))),
+            };
+            if !cover.rows.is_empty() && cover.on_set != on_set {
+                return Err(error(
+                    "covers may not mix on-set and off-set rows".to_string(),
+                ));
+            }
+            cover.on_set = on_set;
+            cover.rows.push(pattern.as_bytes().to_vec());
+            continue;
+        }
+
+        current = None;
+        let mut define = |name: &str| match defined.insert(name.to_string()) {
+            true => Ok(()),
+            false => Err(error(format!("`{name}` is defined twice"))),
+        };
+        match first {
+            ".model" if model => {
+                return Err(error(
+                    "files with multiple models are not supported".to_string(),
+                ))
+            }
+            ".model" => model = true,
+            ".inputs" => {
+                for name in tokens {
+                    define(name)?;
+                    inputs.push(name.to_string());
+                }
+            }
+            ".outputs" => outputs.extend(tokens.map(|name| (line, name.to_string()))),
+            ".names" => {
+                let mut signals = Vec::from_iter(tokens.map(str::to_string));
+                let output = signals
+                    .pop()
+                    .ok_or_else(|| error("`.names` without an output".to_string()))?;
+                define(&output)?;
+                covers.insert(
+                    output.clone(),
+                    Cover {
+                        line,
+                        inputs: signals,
+                        rows: Vec::new(),
+                        on_set: true,
+                    },
+                );
+                current = Some(output);
+            }
+            ".latch" => {
+                let arguments = Vec::from_iter(tokens);
+                let (input, output, init) = match arguments[..] {
+                    [input, output] | [input, output, _, _] => (input, output, "3"),
+                    [input, output, init] | [input, output, _, _, init] => (input, output, init),
+                    _ => return Err(error("invalid `.latch`".to_string())),
+                };
+                define(output)?;
+                let init = match init {
+                    "0" | "1" | "2" | "3" => init.as_bytes()[0] - b'0',
+                    _ => return Err(error(format!("invalid initial value `{init}`"))),
+                };
+                latches.push(Latch {
+                    line,
+                    input: input.to_string(),
+                    output: output.to_string(),
+                    register: Register::from_mockturtle_init(init),
+                });
+            }
+            ".end" => ended = true,
+            command => return Err(error(format!("unsupported command `{command}`"))),
+        }
+    }
+
+    receiver.set_num_inputs(inputs.len() as u64);
+    if !latches.is_empty() {
+        let registers = Vec::from_iter(latches.iter().map(|latch| latch.register));
+        receiver.set_registers(&registers);
+    }
+    let mut builder = NetworkBuilder::new(receiver);
+    let mut netlist = Netlist {
+        definitions: covers,
+        signals: FxHashMap::default(),
+    };
+    let leaves = inputs
+        .iter()
+        .chain(latches.iter().map(|latch| &latch.output));
+    for (index, name) in leaves.enumerate() {
+        let input = builder.create(R::Node::input(index as u64));
+        netlist.signals.insert(name.clone(), input);
+    }
+    for (index, name) in inputs.iter().enumerate() {
+        builder.receiver_mut().set_input_name(index as u64, name);
+    }
+
+    let latch_inputs = latches.iter().map(|latch| (latch.line, &latch.input));
+    let mut signals = Vec::new();
+    for (line, name) in outputs
+        .iter()
+        .map(|(line, name)| (*line, name))
+        .chain(latch_inputs)
+    {
+        let resolved = netlist.resolve(
+            name,
+            &mut builder,
+            |cover| cover.inputs.clone(),
+            |cover, signals, builder| {
+                let inputs = Vec::from_iter(cover.inputs.iter().map(|input| signals[input]));
+                let products = Vec::from_iter(cover.rows.iter().map(|row| {
+                    let literals = Vec::from_iter(
+                        row.iter()
+                            .zip(&inputs)
+                            .filter(|(c, _)| **c != b'-')
+                            .map(|(c, input)| input.maybe_invert(*c == b'0')),
+                    );
+                    builder.and_all(&literals)
+                }));
+                builder.or_all(&products).maybe_invert(!cover.on_set)
+            },
+        );
+        let signal = resolved.map_err(|unresolved| match unresolved {
+            Unresolved::Undefined { key, user } => BlifError {
+                line: user.map_or(line, |user| netlist.definitions[&user].line),
+                message: format!("`{key}` is used but not defined"),
+            },
+            Unresolved::Cycle(key) => BlifError {
+                line: netlist.definitions[&key].line,
+                message: format!("`{key}` depends on itself"),
+            },
+        })?;
+        signals.push(signal);
+    }
+    for (index, (_, name)) in outputs.iter().enumerate() {
+        builder.receiver_mut().set_output_name(index, name);
+    }
+    Ok(builder.into_receiver().done(&signals))
+}
+
+/// Returns the lines of the source with their line numbers, where comments are removed and lines
+/// ending with `\` are joined with the following line.
+fn logical_lines(source: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut pending: Option<(usize, String)> = None;
+    for (index, text) in source.lines().enumerate() {
+        let text = text.split('#').next().unwrap_or_default().trim_end();
+        let (text, continued) = match text.strip_suffix('\\') {
+            Some(text) => (text, true),
+            None => (text, false),
+        };
+        let (line, mut joined) = pending.take().unwrap_or((index + 1, String::new()));
+        joined.push(' ');
+        joined.push_str(text);
+        if continued {
+            pending = Some((line, joined));
+        } else {
+            lines.push((line, joined));
+        }
+    }
+    lines.extend(pending);
+    lines
+}
+
+/// Writes the given network as a BLIF model with the given name.
+///
+/// Every gate becomes a `.names` cover and registers become `.latch`es. Primary inputs and outputs
+/// keep their names, while unnamed ones and internal signals get generated names. Gates with an
+/// unknown [`GateFunction`] are written as the minterms of their [`Node::evaluate`]d truth table.
+///
+/// Fails with [`io::ErrorKind::InvalidInput`] if names are used twice in a way that BLIF cannot
+/// represent, e.g. for two outputs, or if a gate cannot be evaluated.
+pub fn write_blif<N: Network + ?Sized>(
+    network: &N,
+    model_name: &str,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    let num_inputs = network.num_inputs();
+    let num_registers = network.num_registers();
+    let outputs = Vec::from_iter(network.outputs());
+    let num_outputs = outputs.len().saturating_sub(num_registers as usize);
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+
+    let mut used = FxHashSet::default();
+    used.extend((0..num_outputs).filter_map(|output| network.output_name(output)));
+    let mut leaves = Vec::new();
+    for input in 0..num_inputs {
+        if let Some(name) = network.input_name(input) {
+            if leaves.contains(&name.to_string()) {
+                return Err(invalid(format!("multiple inputs are named `{name}`")));
+            }
+            used.insert(name);
+        }
+        leaves.push(network.input_name(input).unwrap_or_default().to_string());
+    }
+    let mut used = FxHashSet::from_iter(used.into_iter().map(str::to_string));
+    let mut fresh = |base: String| {
+        let mut name = base.clone();
+        let mut suffix = 0;
+        while !used.insert(name.clone()) {
+            suffix += 1;
+            name = format!("{base}_{suffix}");
+        }
+        name
+    };
+    for (input, name) in leaves.iter_mut().enumerate() {
+        if name.is_empty() {
+            *name = fresh(format!("pi{input}"));
+        }
+    }
+    leaves.extend((0..num_registers).map(|register| fresh(format!("lo{register}"))));
+
+    let mut output_names = Vec::new();
+    for (index, output) in outputs.iter().enumerate() {
+        let name = match network.output_name(index) {
+            Some(name) if index < num_outputs => {
+                if output_names.iter().any(|other| other == name) {
+                    return Err(invalid(format!("multiple outputs are named `{name}`")));
+                }
+                name.to_string()
+            }
+            _ if index < num_outputs => fresh(format!("po{index}")),
+            _ => fresh(format!("li{}", index - num_outputs)),
+        };
+        let input = network.node(output.node_id()).input_index();
+        let same_input = input.is_some_and(|input| leaves[input as usize] == name);
+        if leaves.contains(&name) && (!same_input || output.is_inverted()) {
+            return Err(invalid(format!("`{name}` is both an input and an output")));
+        }
+        output_names.push(name);
+    }
+
+    writeln!(writer, ".model {model_name}")?;
+    writeln!(
+        writer,
+        ".inputs {}",
+        leaves[..num_inputs as usize].join(" ")
+    )?;
+    writeln!(writer, ".outputs {}", output_names[..num_outputs].join(" "))?;
+    for register in 0..num_registers {
+        writeln!(
+            writer,
+            ".latch {} {} {}",
+            output_names[num_outputs + register as usize],
+            leaves[(num_inputs + register) as usize],
+            network.register(register).mockturtle_init()
+        )?;
+    }
+
+    let mut names: FxHashMap<Id, String> = FxHashMap::default();
+    for id in TopologicalView::new(network).order() {
+        let node = network.node(*id);
+        let name = if let Some(input) = node.input_index() {
+            leaves[input as usize].clone()
+        } else if node.is_leaf() {
+            let name = fresh("const0".to_string());
+            writeln!(writer, ".names {name}")?;
+            name
+        } else {
+            let (rows, on_set) =
+                cover(&node).ok_or_else(|| invalid(format!("{node:?} cannot be evaluated")))?;
+            let name = fresh(format!("n{}", u32::from(*id)));
+            let inputs = Vec::from_iter(
+                node.inputs()
+                    .iter()
+                    .map(|input| names[&input.node_id()].as_str()),
+            );
+            writeln!(writer, ".names {} {name}", inputs.join(" "))?;
+            for row in rows {
+                let row =
+                    String::from_iter(row.iter().zip(node.inputs()).map(|(c, input)| {
+                        match (c, input.is_inverted()) {
+                            (b'0', true) => '1',
+                            (b'1', true) => '0',
+                            (c, _) => *c as char,
+                        }
+                    }));
+                writeln!(writer, "{row} {}", on_set as u8)?;
+            }
+            name
+        };
+        names.insert(*id, name);
+    }
+
+    for (output, name) in outputs.iter().zip(&output_names) {
+        let driver = &names[&output.node_id()];
+        if driver != name {
+            writeln!(writer, ".names {driver} {name}")?;
+            writeln!(writer, "{} 1", !output.is_inverted() as u8)?;
+        }
+    }
+    writeln!(writer, ".end")
+}
+
+/// Returns the rows of a cover of the given gate, ignoring the inversions of its inputs, and
+/// whether they describe its on-set or [`None`] if the gate cannot be evaluated.
+fn cover<N: Node>(node: &N) -> Option<(Vec<Vec<u8>>, bool)> {
+    let fanin = node.inputs().len();
+    let one_hot = || {
+        Vec::from_iter(
+            (0..fanin)
+                .map(|i| Vec::from_iter((0..fanin).map(|j| if i == j { b'1' } else { b'-' }))),
+        )
+    };
+    let function = node.gate_type().and_then(|gate| gate.function());
+    Some(match function {
+        Some(GateFunction::And) => (vec![vec![b'1'; fanin]], true),
+        Some(GateFunction::Nand) => (vec![vec![b'1'; fanin]], false),
+        Some(GateFunction::Or) => (one_hot(), true),
+        Some(GateFunction::Nor) => (one_hot(), false),
+        Some(GateFunction::Maj) if fanin == 3 => (
+            vec![b"11-".to_vec(), b"1-1".to_vec(), b"-11".to_vec()],
+            true,
+        ),
+        Some(GateFunction::Ite) if fanin == 3 => (vec![b"11-".to_vec(), b"0-1".to_vec()], true),
+        _ => {
+            let vars =
+                Vec::from_iter((0..fanin as u32).map(|var| TruthTable::nth_var(fanin as u32, var)));
+            let tt = node.evaluate(&vars)?;
+            let minterms = (0..tt.num_bits()).filter(|minterm| tt.bit(*minterm));
+            let rows = Vec::from_iter(minterms.map(|minterm| {
+                Vec::from_iter((0..fanin).map(|var| b'0' + (minterm >> var & 1) as u8))
+            }));
+            (rows, true)
+        }
+    })
+}
@@ -0,0 +1,269 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use rustc_hash::FxHashMap;
+
+use crate::{NetworkBuilder, Receiver, Signal};
+
+/// An error that occurred while parsing a Boolean expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpressionError {
+    /// The byte offset in the source at which the error occurred.
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for ExpressionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "position {}: {}", self.position, self.message)
+    }
+}
+
+impl Error for ExpressionError {}
+
+/// Parses Boolean expressions and builds them into the given receiver. Returns the result of the
+/// receiver and a mapping from the names of the variables to the indices of the primary inputs
+/// that represent them. The inputs are numbered in the order in which the variables first occur.
+///
+/// The source contains one or more expressions separated by `;`, each of which becomes a primary
+/// output. An expression may be preceded by `<name> =` to name its output. Expressions consist of
+/// - variables, whose names start with a letter or `_` and may contain letters, digits, `_`, `.`,
+///   `[` and `]`,
+/// - the constants `0`, `1`, `false` and `true`,
+/// - `!` or `~` for negation,
+/// - `&` or `*` for conjunction, `^` for exclusive or and `|` or `+` for disjunction, which bind in
+///   this order from strongest to weakest,
+/// - the functions `and`, `or`, `xor`, `nand`, `nor` and `xnor` with any positive number of
+///   arguments, `maj` with three arguments and `ite` or `mux` with a condition, a then and an else
+///   argument.
+///
+/// Operators the target network has no gate for are decomposed as described in
+/// [`NetworkBuilder`], e.g. XORs into ANDs for [`Aig`](crate::Aig)s.
+///
+/// # Example
+/// ```
+/// use eggmock::{read_expression, Aig, MutableNetwork, Network};
+///
+/// let (network, variables) = read_expression(
+///     MutableNetwork::<Aig>::default(),
+///     "f = a & !(b ^ c) | maj(a, b, d)",
+/// )
+/// .unwrap();
+/// assert_eq!(network.num_inputs(), 4);
+/// assert_eq!(variables["d"], 3);
+/// assert_eq!(network.output_name(0), Some("f"));
+/// ```
+pub fn read_expression<R: Receiver>(
+    receiver: R,
+    source: &str,
+) -> Result<(R::Result, FxHashMap<String, u64>), ExpressionError> {
+    let mut parser = Parser {
+        source,
+        position: 0,
+        builder: NetworkBuilder::new(receiver),
+        variables: FxHashMap::default(),
+        inputs: Vec::new(),
+    };
+    loop {
+        let name = parser.output_name();
+        let output = parser.or()?;
+        match name {
+            Some(name) => parser.builder.named_po(output, name),
+            None => parser.builder.po(output),
+        };
+        match parser.peek() {
+            None => break,
+            Some(b';') => {
+                parser.position += 1;
+                if parser.peek().is_none() {
+                    break;
+                }
+            }
+            Some(c) => return Err(parser.error(format!("unexpected `{}`", c as char))),
+        }
+    }
+    Ok((parser.builder.done(), parser.variables))
+}
+
+struct Parser<'s, R: Receiver> {
+    source: &'s str,
+    position: usize,
+    builder: NetworkBuilder<R>,
+    variables: FxHashMap<String, u64>,
+    /// The signals of the primary inputs by their index.
+    inputs: Vec<Signal>,
+}
+
+impl<'s, R: Receiver> Parser<'s, R> {
+    fn error(&self, message: impl Into<String>) -> ExpressionError {
+        ExpressionError {
+            position: self.position,
+            message: message.into(),
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        let bytes = self.source.as_bytes();
+        while bytes.get(self.position)?.is_ascii_whitespace() {
+            self.position += 1;
+        }
+        bytes.get(self.position).copied()
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), ExpressionError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.position += 1;
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!(
+                "expected `{}` but found `{}`",
+                expected as char, c as char
+            ))),
+            None => Err(self.error(format!("expected `{}`", expected as char))),
+        }
+    }
+
+    fn identifier(&mut self) -> Option<&'s str> {
+        match self.peek() {
+            Some(c) if c.is_ascii_alphabetic() || c == b'_' => {}
+            _ => return None,
+        }
+        let start = self.position;
+        let bytes = self.source.as_bytes();
+        while let Some(c) = bytes.get(self.position) {
+            if !(c.is_ascii_alphanumeric() || b"_.[]".contains(c)) {
+                break;
+            }
+            self.position += 1;
+        }
+        Some(&self.source[start..self.position])
+    }
+
+    /// Consumes `<name> =` if the next expression is preceded by it.
+    fn output_name(&mut self) -> Option<&'s str> {
+        let start = self.position;
+        if let Some(name) = self.identifier() {
+            if self.peek() == Some(b'=') {
+                self.position += 1;
+                return Some(name);
+            }
+        }
+        self.position = start;
+        None
+    }
+
+    fn or(&mut self) -> Result<Signal, ExpressionError> {
+        let mut signal = self.xor()?;
+        while let Some(b'|' | b'+') = self.peek() {
+            self.position += 1;
+            let other = self.xor()?;
+            signal = self.builder.or(signal, other);
+        }
+        Ok(signal)
+    }
+
+    fn xor(&mut self) -> Result<Signal, ExpressionError> {
+        let mut signal = self.and()?;
+        while let Some(b'^') = self.peek() {
+            self.position += 1;
+            let other = self.and()?;
+            signal = self.builder.xor(signal, other);
+        }
+        Ok(signal)
+    }
+
+    fn and(&mut self) -> Result<Signal, ExpressionError> {
+        let mut signal = self.unary()?;
+        while let Some(b'&' | b'*') = self.peek() {
+            self.position += 1;
+            let other = self.unary()?;
+            signal = self.builder.and(signal, other);
+        }
+        Ok(signal)
+    }
+
+    fn unary(&mut self) -> Result<Signal, ExpressionError> {
+        match self.peek() {
+            Some(b'!' | b'~') => {
+                self.position += 1;
+                Ok(!self.unary()?)
+            }
+            Some(b'(') => {
+                self.position += 1;
+                let signal = self.or()?;
+                self.expect(b')')?;
+                Ok(signal)
+            }
+            Some(b'0') => {
+                self.position += 1;
+                Ok(self.builder.constant(false))
+            }
+            Some(b'1') => {
+                self.position += 1;
+                Ok(self.builder.constant(true))
+            }
+            Some(c) => {
+                let start = self.position;
+                let Some(name) = self.identifier() else {
+                    return Err(self.error(format!("unexpected `{}`", c as char)));
+                };
+                if self.peek() == Some(b'(') {
+                    self.position += 1;
+                    return self.function(name, start);
+                }
+                Ok(match name {
+                    "false" => self.builder.constant(false),
+                    "true" => self.builder.constant(true),
+                    _ => self.variable(name),
+                })
+            }
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+
+    fn variable(&mut self, name: &str) -> Signal {
+        if let Some(input) = self.variables.get(name) {
+            return self.inputs[*input as usize];
+        }
+        let signal = self.builder.named_pi(name);
+        self.variables
+            .insert(name.to_string(), self.builder.num_inputs() - 1);
+        self.inputs.push(signal);
+        signal
+    }
+
+    /// Parses the arguments of a function call after the opening parenthesis and builds it.
+    fn function(&mut self, name: &str, start: usize) -> Result<Signal, ExpressionError> {
+        let mut arguments = vec![self.or()?];
+        while self.peek() == Some(b',') {
+            self.position += 1;
+            arguments.push(self.or()?);
+        }
+        self.expect(b')')?;
+        let wrong_arity = |expected: &str| ExpressionError {
+            position: start,
+            message: format!("`{name}` expects {expected} but got {}", arguments.len()),
+        };
+        let builder = &mut self.builder;
+        Ok(match (name, arguments.as_slice()) {
+            ("and", _) => builder.and_all(&arguments),
+            ("or", _) => builder.or_all(&arguments),
+            ("xor", _) => builder.xor_all(&arguments),
+            ("nand", _) => !builder.and_all(&arguments),
+            ("nor", _) => !builder.or_all(&arguments),
+            ("xnor", _) => !builder.xor_all(&arguments),
+            ("maj", [a, b, c]) => builder.maj(*a, *b, *c),
+            ("ite" | "mux", [condition, then, otherwise]) => {
+                builder.mux(*condition, *then, *otherwise)
+            }
+            ("maj" | "ite" | "mux", _) => return Err(wrong_arity("three arguments")),
+            _ => {
+                return Err(ExpressionError {
+                    position: start,
+                    message: format!("unknown function `{name}`"),
+                })
+            }
+        })
+    }
+}
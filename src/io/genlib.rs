@@ -0,0 +1,230 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use crate::library::parse_expression;
+use crate::{Cell, CellExpression, CellPin, GateLibrary, PinPhase};
+
+/// An error that occurred while parsing a *genlib* file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenlibError {
+    /// The line (starting at 1) at which the error occurred.
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for GenlibError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for GenlibError {}
+
+/// Parses a gate library in the *genlib* format as used by SIS and ABC.
+///
+/// Each gate is described by a `GATE <name> <area> <output>=<expression>;` statement followed by
+/// `PIN <name> <phase> <input-load> <max-load> <rise-block> <rise-fanout> <fall-block>
+/// <fall-fanout>` statements, where `*` as the pin name applies the timing to all inputs.
+/// Expressions may use `!` or a trailing `'` for negation, `*`, `&` or juxtaposition for
+/// conjunction, `+` or `|` for disjunction, `^` for exclusive or as well as `CONST0` and `CONST1`.
+/// The inputs of the cells are ordered as their `PIN` statements or, for `PIN *`, by their first
+/// occurrence in the expression. Latches are not supported.
+///
+/// # Example
+/// ```
+/// use eggmock::{read_genlib, TruthTable};
+///
+/// // the PIN statements of aoi21 determine the order of its inputs
+/// let library = read_genlib(
+///     "GATE inv 1 Y=!A; PIN * INV 1 999 1 0.2 1 0.2
+///      GATE aoi21 3 Y=!(A*B+C);
+///        PIN C INV 1 999 1.6 0 1.4 0
+///        PIN A INV 1 999 2 0 1.8 0
+///        PIN B INV 1 999 2 0 1.8 0",
+/// )
+/// .unwrap();
+/// let aoi21 = &library.cells()[library.find("aoi21").unwrap()];
+/// assert_eq!(aoi21.area(), 3.0);
+/// assert_eq!(aoi21.output(), "Y");
+/// let pins = Vec::from_iter(aoi21.pins().iter().map(|pin| pin.name.as_str()));
+/// assert_eq!(pins, ["C", "A", "B"]);
+/// // !(x1 & x2 | x0) in terms of the pins C, A and B
+/// assert_eq!(aoi21.function(), &TruthTable::from_hex(3, "15").unwrap());
+/// assert_eq!(aoi21.pins()[1].delay(0.0), 2.0);
+/// let inv = &library.cells()[0];
+/// assert_eq!(inv.pins()[0].delay(5.0), 2.0);
+///
+/// assert_eq!(read_genlib("GATE inv 1 Y=!A;").unwrap_err().line, 1);
+/// ```
+pub fn read_genlib(source: &str) -> Result<GateLibrary, GenlibError> {
+    let mut lexer = Lexer {
+        source,
+        position: 0,
+        line: 1,
+    };
+    let mut cells = Vec::new();
+    while let Some(keyword) = lexer.word() {
+        match keyword {
+            "GATE" => cells.push(read_gate(&mut lexer)?),
+            "LATCH" => return Err(lexer.error("latches are not supported")),
+            other => return Err(lexer.error(format!("expected GATE but found `{other}`"))),
+        }
+    }
+    Ok(GateLibrary::new(cells))
+}
+
+fn read_gate(lexer: &mut Lexer) -> Result<Cell, GenlibError> {
+    let name = lexer
+        .expect_word("gate name")?
+        .trim_matches('"')
+        .to_string();
+    let area = lexer.number("area")?;
+    let line = lexer.line;
+    let statement = lexer
+        .until(';')
+        .ok_or_else(|| lexer.error("missing `;` after the gate function"))?;
+    let (output, expression) = statement.split_once('=').ok_or_else(|| GenlibError {
+        line,
+        message: "expected `<output>=<expression>`".to_string(),
+    })?;
+    let mut expression_pins = Vec::new();
+    let expression = parse_expression(expression, &mut expression_pins)
+        .map_err(|message| GenlibError { line, message })?;
+
+    let mut pins: Vec<CellPin> = Vec::new();
+    let mut wildcard = None;
+    while lexer.peek_word() == Some("PIN") {
+        lexer.word();
+        let pin = read_pin(lexer)?;
+        if pin.name == "*" {
+            wildcard = Some(pin);
+        } else {
+            pins.push(pin);
+        }
+    }
+    if let Some(wildcard) = wildcard {
+        for name in &expression_pins {
+            if !pins.iter().any(|pin| &pin.name == name) {
+                pins.push(CellPin {
+                    name: name.clone(),
+                    ..wildcard.clone()
+                });
+            }
+        }
+    } else if let Some(missing) = expression_pins
+        .iter()
+        .find(|name| !pins.iter().any(|pin| &pin.name == *name))
+    {
+        return Err(lexer.error(format!(
+            "missing PIN statement for input `{missing}` of `{name}`"
+        )));
+    }
+
+    // the variables of the parsed expression refer to the order of appearance
+    let order = Vec::from_iter(
+        expression_pins
+            .drain(..)
+            .map(|name| pins.iter().position(|pin| pin.name == name).unwrap()),
+    );
+    let expression = remap_pins(expression, &order);
+    Ok(Cell::new(name, area, output.trim(), expression, pins))
+}
+
+fn read_pin(lexer: &mut Lexer) -> Result<CellPin, GenlibError> {
+    let name = lexer.expect_word("pin name")?.to_string();
+    let phase = match lexer.expect_word("pin phase")? {
+        "INV" => PinPhase::Inverting,
+        "NONINV" => PinPhase::NonInverting,
+        "UNKNOWN" => PinPhase::Unknown,
+        other => return Err(lexer.error(format!("unknown pin phase `{other}`"))),
+    };
+    Ok(CellPin {
+        name,
+        phase,
+        input_load: lexer.number("input load")?,
+        max_load: lexer.number("max load")?,
+        rise_block_delay: lexer.number("rise block delay")?,
+        rise_fanout_delay: lexer.number("rise fanout delay")?,
+        fall_block_delay: lexer.number("fall block delay")?,
+        fall_fanout_delay: lexer.number("fall fanout delay")?,
+    })
+}
+
+fn remap_pins(expression: CellExpression, order: &[usize]) -> CellExpression {
+    let remap = |e: Box<CellExpression>| Box::new(remap_pins(*e, order));
+    match expression {
+        CellExpression::Const(value) => CellExpression::Const(value),
+        CellExpression::Pin(pin) => CellExpression::Pin(order[pin]),
+        CellExpression::Not(e) => CellExpression::Not(remap(e)),
+        CellExpression::And(a, b) => CellExpression::And(remap(a), remap(b)),
+        CellExpression::Or(a, b) => CellExpression::Or(remap(a), remap(b)),
+        CellExpression::Xor(a, b) => CellExpression::Xor(remap(a), remap(b)),
+    }
+}
+
+struct Lexer<'s> {
+    source: &'s str,
+    position: usize,
+    line: usize,
+}
+
+impl<'s> Lexer<'s> {
+    fn error(&self, message: impl Into<String>) -> GenlibError {
+        GenlibError {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let mut in_comment = false;
+        for c in self.source[self.position..].chars() {
+            match c {
+                '\n' => {
+                    self.line += 1;
+                    in_comment = false;
+                }
+                '#' => in_comment = true,
+                c if in_comment || c.is_whitespace() => {}
+                _ => break,
+            }
+            self.position += c.len_utf8();
+        }
+    }
+
+    fn peek_word(&mut self) -> Option<&'s str> {
+        self.skip_whitespace();
+        let rest = &self.source[self.position..];
+        let end = rest
+            .find(|c: char| c.is_whitespace() || c == '#')
+            .unwrap_or(rest.len());
+        (end > 0).then(|| &rest[..end])
+    }
+
+    fn word(&mut self) -> Option<&'s str> {
+        let word = self.peek_word()?;
+        self.position += word.len();
+        Some(word)
+    }
+
+    fn expect_word(&mut self, what: &str) -> Result<&'s str, GenlibError> {
+        self.word()
+            .ok_or_else(|| self.error(format!("expected {what}")))
+    }
+
+    fn number(&mut self, what: &str) -> Result<f64, GenlibError> {
+        let word = self.expect_word(what)?;
+        word.parse()
+            .map_err(|_| self.error(format!("expected {what} but found `{word}`")))
+    }
+
+    /// Returns the text up to the given character and skips the character.
+    fn until(&mut self, end: char) -> Option<&'s str> {
+        self.skip_whitespace();
+        let rest = &self.source[self.position..];
+        let length = rest.find(end)?;
+        self.line += rest[..length].matches('\n').count();
+        self.position += length + end.len_utf8();
+        Some(&rest[..length])
+    }
+}
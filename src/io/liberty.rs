@@ -0,0 +1,464 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    LibertyCell, LibertyLibrary, LibertyPin, LookupTable, PinDirection, TableVariable, TimingArc,
+    TimingSense,
+};
+
+/// An error that occurred while parsing a Liberty file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LibertyError {
+    /// The line (starting at 1) at which the error occurred.
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for LibertyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for LibertyError {}
+
+/// Parses the subset of a Liberty (`.lib`) file that is relevant for mapping and timing.
+///
+/// Of the library, the `time_unit`, the `capacitive_load_unit` and the `lu_table_template`s are
+/// read. Of each cell, the area and the pins with their direction, capacitance, function and
+/// timing arcs are read, where the `cell_rise`, `cell_fall`, `rise_transition` and
+/// `fall_transition` tables of the non-linear delay model are supported. All other groups and
+/// attributes are skipped, so the file only needs to be syntactically valid.
+///
+/// # Example
+/// ```
+/// use eggmock::{read_liberty, AigGateType, LibertyCostModel};
+///
+/// let library = read_liberty(
+///     r#"library (tiny) {
+///         time_unit : "1ns";
+///         lu_table_template (delay) {
+///             variable_1 : input_net_transition;
+///             variable_2 : total_output_net_capacitance;
+///             index_1 ("0, 1");
+///             index_2 ("0, 2");
+///         }
+///         cell (INV) {
+///             area : 1;
+///             pin (A) { direction : input; capacitance : 0.5; }
+///             pin (Y) {
+///                 direction : output;
+///                 function : "!A";
+///                 timing () {
+///                     related_pin : "A";
+///                     timing_sense : negative_unate;
+///                     cell_rise (delay) { values ("1, 3", "2, 4"); }
+///                     cell_fall (delay) { values ("1, 2", "1, 2"); }
+///                 }
+///             }
+///         }
+///         cell (AND2) {
+///             area : 3;
+///             pin (A, B) { direction : input; capacitance : 1; }
+///             pin (Y) {
+///                 direction : output;
+///                 function : "A & B";
+///                 timing () {
+///                     related_pin : "A B";
+///                     timing_sense : positive_unate;
+///                     cell_rise (delay) { values ("2, 4", "3, 5"); }
+///                 }
+///             }
+///         }
+///     }"#,
+/// )
+/// .unwrap();
+/// assert_eq!(library.time_unit.as_deref(), Some("1ns"));
+/// let inv = library.cell("INV").unwrap();
+/// assert_eq!(inv.area, 1.0);
+/// assert_eq!(inv.pin("A").unwrap().capacitance, 0.5);
+/// // interpolated between the entries of the table and extrapolated beyond its indices
+/// assert_eq!(inv.delay("A", "Y", 0.5, 1.0), Some(2.5));
+/// assert_eq!(inv.delay("A", "Y", 0.5, 4.0), Some(5.5));
+/// assert_eq!(library.cell("AND2").unwrap().delay("B", "Y", 0.5, 1.0), Some(3.5));
+///
+/// let model = LibertyCostModel::<AigGateType>::new(&library, 0.5, 1.0);
+/// let and = model.gate(AigGateType::And).unwrap();
+/// assert_eq!((and.area, and.delay), (3.0, 3.5));
+/// let inverter = model.inverter().unwrap();
+/// assert_eq!((inverter.area, inverter.delay), (1.0, 2.5));
+/// ```
+pub fn read_liberty(source: &str) -> Result<LibertyLibrary, LibertyError> {
+    let mut lexer = Lexer {
+        source,
+        position: 0,
+        line: 1,
+    };
+    let statement = lexer.statement()?;
+    let Some(Statement::Group(library)) = statement else {
+        return Err(lexer.error("expected a library group"));
+    };
+    if library.name != "library" {
+        return Err(LibertyError {
+            line: library.line,
+            message: format!("expected a library group but found `{}`", library.name),
+        });
+    }
+
+    let mut templates: FxHashMap<&str, LookupTable> = FxHashMap::default();
+    for template in library.groups("lu_table_template") {
+        let mut table = LookupTable::default();
+        read_table_attributes(template, &mut table)?;
+        templates.insert(template.argument(), table);
+    }
+
+    let mut cells = Vec::new();
+    for cell in library.groups("cell") {
+        let mut pins = Vec::new();
+        for pin in cell.groups("pin") {
+            let mut timing = Vec::new();
+            for arc in pin.groups("timing") {
+                let table = |name: &str| {
+                    let Some(group) = arc.groups(name).next() else {
+                        return Ok(None);
+                    };
+                    let mut table = templates.get(group.argument()).cloned().unwrap_or_default();
+                    read_table_attributes(group, &mut table)?;
+                    Ok(Some(table))
+                };
+                timing.push(TimingArc {
+                    related_pins: arc
+                        .string("related_pin")
+                        .unwrap_or_default()
+                        .split_whitespace()
+                        .map(str::to_string)
+                        .collect(),
+                    timing_sense: match arc.string("timing_sense") {
+                        Some("positive_unate") => TimingSense::PositiveUnate,
+                        Some("negative_unate") => TimingSense::NegativeUnate,
+                        _ => TimingSense::NonUnate,
+                    },
+                    timing_type: arc.string("timing_type").map(str::to_string),
+                    cell_rise: table("cell_rise")?,
+                    cell_fall: table("cell_fall")?,
+                    rise_transition: table("rise_transition")?,
+                    fall_transition: table("fall_transition")?,
+                });
+            }
+            let direction = match pin.string("direction") {
+                Some("input") => PinDirection::Input,
+                Some("output") => PinDirection::Output,
+                Some("inout") => PinDirection::Inout,
+                _ => PinDirection::Internal,
+            };
+            // a pin group may declare several pins with the same attributes
+            for name in &pin.arguments {
+                pins.push(LibertyPin {
+                    name: name.clone(),
+                    direction,
+                    capacitance: pin.number("capacitance")?.unwrap_or(0.0),
+                    max_capacitance: pin.number("max_capacitance")?,
+                    function: pin.string("function").map(str::to_string),
+                    timing: timing.clone(),
+                });
+            }
+        }
+        cells.push(LibertyCell {
+            name: cell.argument().to_string(),
+            area: cell.number("area")?.unwrap_or(0.0),
+            pins,
+            is_sequential: ["ff", "latch", "ff_bank", "latch_bank", "statetable"]
+                .iter()
+                .any(|name| cell.groups(name).next().is_some()),
+        });
+    }
+
+    let capacitive_load_unit = match library.attribute("capacitive_load_unit") {
+        Some(attribute) => match &attribute.values[..] {
+            [value, unit] => Some((attribute.parse(value)?, unit.clone())),
+            _ => return Err(attribute.error("expected a value and a unit")),
+        },
+        None => None,
+    };
+    Ok(LibertyLibrary {
+        name: library.argument().to_string(),
+        time_unit: library.string("time_unit").map(str::to_string),
+        capacitive_load_unit,
+        cells,
+    })
+}
+
+/// Reads the variables, indices and values of a template or table into the given table.
+fn read_table_attributes(group: &Group, table: &mut LookupTable) -> Result<(), LibertyError> {
+    if let Some(variable) = group.string("variable_1") {
+        table.variable_1 = Some(TableVariable::from_name(variable));
+    }
+    if let Some(variable) = group.string("variable_2") {
+        table.variable_2 = Some(TableVariable::from_name(variable));
+    }
+    if let Some(index) = group.attribute("index_1") {
+        table.index_1 = index.numbers()?;
+    }
+    if let Some(index) = group.attribute("index_2") {
+        table.index_2 = index.numbers()?;
+    }
+    if let Some(values) = group.attribute("values") {
+        table.values = values.numbers()?;
+    }
+    Ok(())
+}
+
+enum Statement {
+    Group(Group),
+    Attribute(Attribute),
+}
+
+/// A group such as `cell (NAND2) { ... }`.
+struct Group {
+    name: String,
+    arguments: Vec<String>,
+    attributes: Vec<Attribute>,
+    groups: Vec<Group>,
+    line: usize,
+}
+
+impl Group {
+    fn argument(&self) -> &str {
+        self.arguments
+            .first()
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    fn groups<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Group> {
+        self.groups.iter().filter(move |group| group.name == name)
+    }
+
+    fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+    }
+
+    fn string(&self, name: &str) -> Option<&str> {
+        Some(self.attribute(name)?.values.first()?.as_str())
+    }
+
+    fn number(&self, name: &str) -> Result<Option<f64>, LibertyError> {
+        let Some(attribute) = self.attribute(name) else {
+            return Ok(None);
+        };
+        match &attribute.values[..] {
+            [value] => attribute.parse(value).map(Some),
+            _ => Err(attribute.error("expected a single number")),
+        }
+    }
+}
+
+/// A simple attribute such as `area : 1.5;` or a complex attribute such as `index_1 ("1, 2");`.
+struct Attribute {
+    name: String,
+    values: Vec<String>,
+    line: usize,
+}
+
+impl Attribute {
+    fn error(&self, message: &str) -> LibertyError {
+        LibertyError {
+            line: self.line,
+            message: format!("{message} for `{}`", self.name),
+        }
+    }
+
+    fn parse(&self, value: &str) -> Result<f64, LibertyError> {
+        value
+            .trim()
+            .parse()
+            .map_err(|_| self.error(&format!("invalid number `{value}`")))
+    }
+
+    /// Parses all values as comma or whitespace separated lists of numbers.
+    fn numbers(&self) -> Result<Vec<f64>, LibertyError> {
+        self.values
+            .iter()
+            .flat_map(|value| value.split(|c: char| c == ',' || c.is_whitespace()))
+            .filter(|value| !value.is_empty())
+            .map(|value| self.parse(value))
+            .collect()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token<'s> {
+    Word(&'s str),
+    String(String),
+    Punctuation(char),
+}
+
+struct Lexer<'s> {
+    source: &'s str,
+    position: usize,
+    line: usize,
+}
+
+impl<'s> Lexer<'s> {
+    fn error(&self, message: impl Into<String>) -> LibertyError {
+        LibertyError {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn rest(&self) -> &'s str {
+        &self.source[self.position..]
+    }
+
+    /// Skips whitespace, comments and line continuations.
+    fn skip_whitespace(&mut self) -> Result<(), LibertyError> {
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("/*") {
+                let end = rest
+                    .find("*/")
+                    .ok_or_else(|| self.error("unterminated comment"))?;
+                self.line += rest[..end].matches('\n').count();
+                self.position += end + 2;
+            } else if rest.starts_with("//") {
+                self.position += rest.find('\n').unwrap_or(rest.len());
+            } else if let Some(c) = rest
+                .chars()
+                .next()
+                .filter(|c| c.is_whitespace() || *c == '\\')
+            {
+                self.line += (c == '\n') as usize;
+                self.position += c.len_utf8();
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token<'s>>, LibertyError> {
+        self.skip_whitespace()?;
+        let rest = self.rest();
+        let Some(c) = rest.chars().next() else {
+            return Ok(None);
+        };
+        if c == '"' {
+            let end = rest[1..]
+                .find('"')
+                .ok_or_else(|| self.error("unterminated string"))?;
+            let content = &rest[1..end + 1];
+            self.line += content.matches('\n').count();
+            self.position += end + 2;
+            return Ok(Some(Token::String(content.replace("\\\n", ""))));
+        }
+        if "(){}:;,".contains(c) {
+            self.position += 1;
+            return Ok(Some(Token::Punctuation(c)));
+        }
+        let end = rest
+            .find(|c: char| c.is_whitespace() || "(){}:;,\"\\".contains(c))
+            .unwrap_or(rest.len());
+        self.position += end;
+        Ok(Some(Token::Word(&rest[..end])))
+    }
+
+    fn peek_token(&mut self) -> Result<Option<Token<'s>>, LibertyError> {
+        let (position, line) = (self.position, self.line);
+        let token = self.next_token();
+        (self.position, self.line) = (position, line);
+        token
+    }
+
+    fn expect(&mut self, punctuation: char) -> Result<(), LibertyError> {
+        match self.next_token()? {
+            Some(Token::Punctuation(c)) if c == punctuation => Ok(()),
+            _ => Err(self.error(format!("expected `{punctuation}`"))),
+        }
+    }
+
+    fn value(&mut self) -> Result<String, LibertyError> {
+        match self.next_token()? {
+            Some(Token::Word(word)) => Ok(word.to_string()),
+            Some(Token::String(string)) => Ok(string),
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    /// Skips an optional semicolon.
+    fn skip_semicolon(&mut self) -> Result<(), LibertyError> {
+        if self.peek_token()? == Some(Token::Punctuation(';')) {
+            self.next_token()?;
+        }
+        Ok(())
+    }
+
+    /// Parses the next statement or returns [`None`] at the end of the input or a group.
+    fn statement(&mut self) -> Result<Option<Statement>, LibertyError> {
+        let name = match self.next_token()? {
+            None | Some(Token::Punctuation('}')) => return Ok(None),
+            Some(Token::Word(name)) => name.to_string(),
+            Some(_) => return Err(self.error("expected an attribute or group name")),
+        };
+        let line = self.line;
+        match self.next_token()? {
+            Some(Token::Punctuation(':')) => {
+                // simple attributes may consist of several words, e.g. expressions
+                let mut values = vec![self.value()?];
+                while let Some(Token::Word(_) | Token::String(_)) = self.peek_token()? {
+                    values.push(self.value()?);
+                }
+                self.skip_semicolon()?;
+                let values = vec![values.join(" ")];
+                Ok(Some(Statement::Attribute(Attribute { name, values, line })))
+            }
+            Some(Token::Punctuation('(')) => {
+                let mut arguments = Vec::new();
+                loop {
+                    match self.peek_token()? {
+                        Some(Token::Punctuation(')')) => {
+                            self.next_token()?;
+                            break;
+                        }
+                        Some(Token::Punctuation(',')) => {
+                            self.next_token()?;
+                        }
+                        _ => arguments.push(self.value()?),
+                    }
+                }
+                if self.peek_token()? != Some(Token::Punctuation('{')) {
+                    self.skip_semicolon()?;
+                    return Ok(Some(Statement::Attribute(Attribute {
+                        name,
+                        values: arguments,
+                        line,
+                    })));
+                }
+                self.expect('{')?;
+                let mut group = Group {
+                    name,
+                    arguments,
+                    attributes: Vec::new(),
+                    groups: Vec::new(),
+                    line,
+                };
+                loop {
+                    if self.peek_token()?.is_none() {
+                        return Err(self.error(format!("unterminated group `{}`", group.name)));
+                    }
+                    match self.statement()? {
+                        Some(Statement::Group(child)) => group.groups.push(child),
+                        Some(Statement::Attribute(attribute)) => group.attributes.push(attribute),
+                        None => break,
+                    }
+                }
+                self.skip_semicolon()?;
+                Ok(Some(Statement::Group(group)))
+            }
+            _ => Err(self.error(format!("expected `:` or `(` after `{name}`"))),
+        }
+    }
+}
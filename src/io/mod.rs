@@ -0,0 +1,24 @@
+mod aiger;
+mod binary;
+mod blif;
+mod expression;
+mod genlib;
+mod liberty;
+mod netlist;
+mod rules;
+mod serialized;
+mod verilog;
+
+pub use aiger::*;
+pub use binary::*;
+pub use blif::*;
+pub use expression::*;
+pub use genlib::*;
+pub use liberty::*;
+pub use rules::*;
+pub use serialized::*;
+pub use verilog::*;
+
+/// The largest number of primary inputs and registers that the readers accept, as a
+/// [`Signal`](crate::Signal) can only refer to `2^31` nodes.
+const MAX_INPUTS: u64 = 1 << 31;
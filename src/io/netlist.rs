@@ -0,0 +1,71 @@
+use std::hash::Hash;
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{NetworkBuilder, Receiver, Signal};
+
+/// The signals of a netlist whose definitions may refer to each other in any order, as in AIGER,
+/// BLIF or Verilog files. The definitions are built on demand once all signals they depend on are
+/// known.
+pub(super) struct Netlist<K, D> {
+    pub definitions: FxHashMap<K, D>,
+    pub signals: FxHashMap<K, Signal>,
+}
+
+/// The reason why a signal of a [`Netlist`] could not be resolved.
+pub(super) enum Unresolved<K> {
+    /// The signal `key` is used by the definition of `user`, or by the requested signal itself if
+    /// `user` is [`None`], but is neither defined nor known.
+    Undefined { key: K, user: Option<K> },
+    /// The definition of the signal depends on itself.
+    Cycle(K),
+}
+
+impl<K: Hash + Eq + Clone, D> Netlist<K, D> {
+    pub fn new() -> Self {
+        Self {
+            definitions: FxHashMap::default(),
+            signals: FxHashMap::default(),
+        }
+    }
+
+    /// Returns the signal with the given key, building its definition and the ones it depends on
+    /// first if necessary. `dependencies` returns the keys a definition depends on and `build`
+    /// creates the signal of a definition once all of them are in the given map.
+    pub fn resolve<R: Receiver>(
+        &mut self,
+        key: &K,
+        builder: &mut NetworkBuilder<R>,
+        dependencies: impl Fn(&D) -> Vec<K>,
+        mut build: impl FnMut(&D, &FxHashMap<K, Signal>, &mut NetworkBuilder<R>) -> Signal,
+    ) -> Result<Signal, Unresolved<K>> {
+        // the keys whose definitions are currently being built, i.e. the current path of the
+        // depth-first search
+        let mut pending = FxHashSet::default();
+        let mut stack = vec![(key.clone(), None, false)];
+        while let Some((key, user, expanded)) = stack.pop() {
+            if self.signals.contains_key(&key) {
+                continue;
+            }
+            let Some(definition) = self.definitions.get(&key) else {
+                return Err(Unresolved::Undefined { key, user });
+            };
+            if expanded {
+                let signal = build(definition, &self.signals, builder);
+                pending.remove(&key);
+                self.signals.insert(key, signal);
+                continue;
+            }
+            if !pending.insert(key.clone()) {
+                return Err(Unresolved::Cycle(key));
+            }
+            stack.push((key.clone(), user, true));
+            for dependency in dependencies(definition).into_iter().rev() {
+                if !self.signals.contains_key(&dependency) {
+                    stack.push((dependency, Some(key.clone()), false));
+                }
+            }
+        }
+        Ok(self.signals[key])
+    }
+}
@@ -0,0 +1,310 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::Path;
+
+use egg::{
+    Analysis, AstSize, Condition, ConditionalApplier, EGraph, Extractor, FromOp, Pattern, Rewrite,
+    Runner, Subst, Var,
+};
+use rustc_hash::FxHashSet;
+
+use crate::{
+    ffi_str, Aig, AigReceiverFFI, Mig, MigReceiverFFI, Network, NetworkLanguage, Node, Receiver,
+    Rewriter, RewriterFFI, Xag, XagReceiverFFI, Xmg, XmgReceiverFFI,
+};
+
+/// An error that occurred while reading rewrite rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleError {
+    /// The line in the source at which the error occurred, starting at 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for RuleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for RuleError {}
+
+/// Reads rewrite rules for networks in the language `L`, e.g. from a file, so that the rules of a
+/// rewriter can be changed without recompiling it (see [`RuleRewriter`](crate::RuleRewriter)).
+///
+/// Each non-empty line contains one rule of the form
+/// ```text
+/// <name>: <lhs> => <rhs> [if <condition>, ...]
+/// ```
+/// where `lhs` and `rhs` are *egg* patterns. With `<=>` instead of `=>`, the rule is
+/// bidirectional and the reverse rule is named `<name>-rev`, as with [`egg::rewrite`]. A rule is
+/// only applied if all of its conditions hold, which are either `<pattern> = <pattern>` or
+/// `<pattern> != <pattern>` to require that both patterns are in the same e-class or not. Note
+/// that checking a condition adds the instantiated patterns to the e-graph. Everything after a
+/// `#` is a comment.
+///
+/// The names have to be unique and all variables have to be bound by the left-hand side and, for
+/// bidirectional rules, by the right-hand side.
+///
+/// # Example
+/// ```
+/// use eggmock::egg::{AstSize, EGraph, Extractor, Runner};
+/// use eggmock::{read_rules, ripple_carry_adder, simulate_truth_tables, Mig, MigLanguage};
+/// use eggmock::MutableNetwork;
+///
+/// let rules = read_rules::<MigLanguage, ()>(
+///     "
+///     commute: (maj ?a ?b ?c) => (maj ?b ?a ?c)  # majority axioms
+///     rotate: (maj ?a ?b ?c) => (maj ?b ?c ?a)
+///     majority: (maj ?a ?b ?c) => ?a if ?a = ?b
+///     distribute: (maj ?x ?y (maj ?u ?v ?z)) <=> (maj (maj ?x ?y ?u) (maj ?x ?y ?v) ?z)
+///     ",
+/// )
+/// .unwrap();
+/// assert_eq!(rules.len(), 5);
+///
+/// let mig = ripple_carry_adder(MutableNetwork::<Mig>::default(), 2);
+/// let (egraph, roots) = ripple_carry_adder(EGraph::<MigLanguage, ()>::default(), 2);
+/// let runner = Runner::default().with_egraph(egraph).with_iter_limit(3).run(&rules);
+/// let extracted = (Extractor::new(&runner.egraph, AstSize), roots);
+/// assert_eq!(simulate_truth_tables(&extracted), simulate_truth_tables(&mig));
+///
+/// let error = read_rules::<MigLanguage, ()>("wrong: (maj ?a ?b ?c) => ?d").unwrap_err();
+/// assert_eq!(error.line, 1);
+/// ```
+pub fn read_rules<L, A>(source: &str) -> Result<Vec<Rewrite<L, A>>, RuleError>
+where
+    L: NetworkLanguage + FromOp + Send + Sync + 'static,
+    <L as FromOp>::Error: Display,
+    A: Analysis<L>,
+{
+    let mut rules = Vec::new();
+    let mut names = FxHashSet::default();
+    for (index, text) in source.lines().enumerate() {
+        let line = index + 1;
+        let error = |message: String| RuleError { line, message };
+        let text = text.split('#').next().unwrap_or_default().trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        let (name, rule) = text
+            .split_once(':')
+            .filter(|(name, _)| !name.trim().is_empty())
+            .ok_or_else(|| error("expected `<name>: <lhs> => <rhs>`".to_string()))?;
+        let name = name.trim();
+        if name.contains(char::is_whitespace) {
+            return Err(error(format!("rule name `{name}` contains whitespace")));
+        }
+        let (lhs, rest, bidirectional) = match find_top_level(rule, "<=>") {
+            Some(arrow) => (&rule[..arrow], &rule[arrow + 3..], true),
+            None => match find_top_level(rule, "=>") {
+                Some(arrow) => (&rule[..arrow], &rule[arrow + 2..], false),
+                None => return Err(error("expected `=>` or `<=>`".to_string())),
+            },
+        };
+        let (rhs, conditions) = match find_top_level(rest, " if ") {
+            Some(position) => (&rest[..position], Some(&rest[position + 4..])),
+            None => (rest, None),
+        };
+        let pattern = |side: &str, text: &str| {
+            text.trim().parse::<Pattern<L>>().map_err(|parse_error| {
+                error(format!("invalid {side} `{}`: {parse_error}", text.trim()))
+            })
+        };
+        let lhs = pattern("left-hand side", lhs)?;
+        let rhs = pattern("right-hand side", rhs)?;
+        let mut parsed_conditions = Vec::new();
+        for condition in conditions.map(split_top_level).unwrap_or_default() {
+            let (left, right, equal) = match find_top_level(condition, "!=") {
+                Some(position) => (&condition[..position], &condition[position + 2..], false),
+                None => match find_top_level(condition, "=") {
+                    Some(position) => (&condition[..position], &condition[position + 1..], true),
+                    None => {
+                        return Err(error(format!(
+                            "expected `=` or `!=` in condition `{}`",
+                            condition.trim()
+                        )))
+                    }
+                },
+            };
+            parsed_conditions.push((
+                pattern("condition", left)?,
+                pattern("condition", right)?,
+                equal,
+            ));
+        }
+        let conditions = Conditions(parsed_conditions);
+
+        let mut directions = vec![(name.to_string(), lhs.clone(), rhs.clone(), "left")];
+        if bidirectional {
+            directions.push((format!("{name}-rev"), rhs, lhs, "right"));
+        }
+        for (name, searcher, applier, side) in directions {
+            if !names.insert(name.clone()) {
+                return Err(error(format!("rule `{name}` is defined twice")));
+            }
+            if let [egg::ENodeOrVar::Var(_)] = searcher.ast.as_ref() {
+                return Err(error(format!(
+                    "the {side}-hand side must not be a variable"
+                )));
+            }
+            let bound = searcher.vars();
+            let unbound = applier
+                .vars()
+                .into_iter()
+                .chain(Condition::<L, A>::vars(&conditions))
+                .find(|var| !bound.contains(var));
+            if let Some(var) = unbound {
+                return Err(error(format!(
+                    "variable `{var}` is not bound by the {side}-hand side"
+                )));
+            }
+            let applier = ConditionalApplier {
+                condition: conditions.clone(),
+                applier,
+            };
+            rules.push(Rewrite::new(name, searcher, applier).map_err(error)?);
+        }
+    }
+    Ok(rules)
+}
+
+/// Returns the position of the first occurrence of `needle` in `text` that is not enclosed in
+/// parentheses.
+fn find_top_level(text: &str, needle: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (position, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 && text[position..].starts_with(needle) => return Some(position),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits the given text at the commas that are not enclosed in parentheses.
+fn split_top_level(mut text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    while let Some(position) = find_top_level(text, ",") {
+        parts.push(&text[..position]);
+        text = &text[position + 1..];
+    }
+    parts.push(text);
+    parts
+}
+
+/// The conditions of a rule as pairs of patterns that have to be in the same e-class (`true`) or
+/// in different ones (`false`).
+#[derive(Clone)]
+struct Conditions<L>(Vec<(Pattern<L>, Pattern<L>, bool)>);
+
+impl<L: NetworkLanguage, A: Analysis<L>> Condition<L, A> for Conditions<L> {
+    fn check(&self, egraph: &mut EGraph<L, A>, _eclass: egg::Id, subst: &Subst) -> bool {
+        self.0.iter().all(|(left, right, equal)| {
+            let left = egraph.add_instantiation(&left.ast, subst);
+            let right = egraph.add_instantiation(&right.ast, subst);
+            (egraph.find(left) == egraph.find(right)) == *equal
+        })
+    }
+
+    fn vars(&self) -> Vec<Var> {
+        Vec::from_iter(
+            self.0
+                .iter()
+                .flat_map(|(left, right, _)| left.vars().into_iter().chain(right.vars())),
+        )
+    }
+}
+
+/// A rewriter that saturates the received network with a set of rules, usually read with
+/// [`read_rules`], and extracts the smallest equivalent network. The runner uses the default
+/// limits of *egg*.
+///
+/// The C++ side can create one for each of the networks with a *mockturtle* counterpart through
+/// `eggmock_{ntk}_rule_file_rewrite`, e.g. `rewrite_mig_with_rule_file( ntk, "rules.txt" )` in the
+/// generated header, which throws a `std::runtime_error` if the rules cannot be read.
+pub struct RuleRewriter<N: Node> {
+    rules: Vec<Rewrite<N::Language, ()>>,
+}
+
+impl<N: Node> RuleRewriter<N> {
+    pub fn new(rules: Vec<Rewrite<N::Language, ()>>) -> Self {
+        Self { rules }
+    }
+
+    /// Reads the rules from the file at the given path. Invalid rules are reported as an error of
+    /// kind [`InvalidData`](io::ErrorKind::InvalidData).
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self>
+    where
+        N::Language: FromOp + Send + Sync,
+        <N::Language as FromOp>::Error: Display,
+    {
+        let source = std::fs::read_to_string(path)?;
+        let rules = read_rules(&source)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        Ok(Self::new(rules))
+    }
+}
+
+impl<N: Node> Rewriter for RuleRewriter<N> {
+    type Node = N;
+    type Intermediate = (EGraph<N::Language, ()>, Vec<egg::Id>);
+
+    fn create_receiver(
+        &mut self,
+    ) -> impl Receiver<Node = Self::Node, Result = Self::Intermediate> + 'static {
+        EGraph::default()
+    }
+
+    fn rewrite(
+        self,
+        (egraph, roots): Self::Intermediate,
+        output: impl Receiver<Node = Self::Node, Result = ()>,
+    ) {
+        let runner = Runner::default().with_egraph(egraph).run(&self.rules);
+        (Extractor::new(&runner.egraph, AstSize), roots).send(output)
+    }
+}
+
+macro_rules! rule_file_rewrite_ffi {
+    ($($node:ident, $receiver:ident, $function:ident;)*) => {$(
+        /// Writes a receiver that rewrites the received network with the rules in the file at
+        /// `path` to `receiver`, see [`RuleRewriter`]. If the rules cannot be read, `set_error` is
+        /// called with `error` and the reason instead and `false` is returned.
+        ///
+        /// # Safety
+        /// `path` has to point to `path_size` readable bytes if `path_size` is not zero and
+        /// `receiver` has to be valid for writes.
+        #[no_mangle]
+        pub unsafe extern "C" fn $function(
+            path: *const libc::c_char,
+            path_size: usize,
+            receiver: *mut $receiver<RewriterFFI<$node>>,
+            error: *mut libc::c_void,
+            set_error: extern "C" fn(*mut libc::c_void, *const libc::c_char, usize),
+        ) -> bool {
+            match RuleRewriter::<$node>::from_file(ffi_str(path, path_size).as_ref()) {
+                Ok(rewriter) => {
+                    receiver.write(RewriterFFI::new(rewriter));
+                    true
+                }
+                Err(reason) => {
+                    let reason = reason.to_string();
+                    set_error(error, reason.as_ptr() as *const libc::c_char, reason.len());
+                    false
+                }
+            }
+        }
+    )*};
+}
+
+rule_file_rewrite_ffi! {
+    Mig, MigReceiverFFI, eggmock_mig_rule_file_rewrite;
+    Aig, AigReceiverFFI, eggmock_aig_rule_file_rewrite;
+    Xag, XagReceiverFFI, eggmock_xag_rule_file_rewrite;
+    Xmg, XmgReceiverFFI, eggmock_xmg_rule_file_rewrite;
+}
@@ -0,0 +1,342 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+
+use egg::{Analysis, EClass, EGraph, Language};
+use rustc_hash::FxHashMap;
+
+use crate::{EGraphSelection, InvalidSelection, NetworkLanguage};
+
+/// An error that occurred while reading an extraction result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractionResultError {
+    /// The byte offset in the source at which the error occurred.
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for ExtractionResultError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "position {}: {}", self.position, self.message)
+    }
+}
+
+impl Error for ExtractionResultError {}
+
+/// Writes the given e-graph in the JSON format of the *egraph-serialize* crate, which is used by
+/// the extractors of *extraction-gym*.
+///
+/// The e-classes are identified by their canonical id and their e-nodes by `<class>.<index>`,
+/// where `index` is the position of the e-node in the e-class. Children refer to the first e-node
+/// of the child e-class. The cost of each e-node is given by `cost`, e.g. `0.0` for nots and
+/// leaves and `1.0` for gates to minimize the number of gates. The e-graph has to be rebuilt.
+pub fn write_serialized_egraph<L, A>(
+    egraph: &EGraph<L, A>,
+    roots: &[egg::Id],
+    mut cost: impl FnMut(&L) -> f64,
+    mut writer: impl Write,
+) -> io::Result<()>
+where
+    L: NetworkLanguage + Display,
+    A: Analysis<L>,
+{
+    let mut classes = Vec::from_iter(egraph.classes());
+    classes.sort_by_key(|class| class.id);
+
+    writeln!(writer, "{{")?;
+    writeln!(writer, "  \"nodes\": {{")?;
+    let mut first = true;
+    for class in classes {
+        for (index, node) in class.nodes.iter().enumerate() {
+            if !first {
+                writeln!(writer, ",")?;
+            }
+            first = false;
+            let children = Vec::from_iter(
+                node.children()
+                    .iter()
+                    .map(|child| format!("\"{}.0\"", egraph.find(*child))),
+            );
+            write!(
+                writer,
+                "    \"{}.{index}\": {{ \"op\": {}, \"children\": [{}], \"eclass\": \"{}\", \"cost\": {} }}",
+                class.id,
+                json_string(&node.to_string()),
+                children.join(", "),
+                class.id,
+                cost(node)
+            )?;
+        }
+    }
+    if !first {
+        writeln!(writer)?;
+    }
+    writeln!(writer, "  }},")?;
+    let roots = Vec::from_iter(
+        roots
+            .iter()
+            .map(|root| format!("\"{}\"", egraph.find(*root))),
+    );
+    writeln!(writer, "  \"root_eclasses\": [{}]", roots.join(", "))?;
+    writeln!(writer, "}}")
+}
+
+/// Encodes the given string as a JSON string literal. Used by the statistics of the `eggmock`
+/// binary.
+#[doc(hidden)]
+pub fn json_string(value: &str) -> String {
+    let mut encoded = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => encoded.push_str("\\\""),
+            '\\' => encoded.push_str("\\\\"),
+            c if (c as u32) < 0x20 => encoded.push_str(&format!("\\u{:04x}", c as u32)),
+            c => encoded.push(c),
+        }
+    }
+    encoded.push('"');
+    encoded
+}
+
+/// Reads the result of an extractor for an e-graph written with [`write_serialized_egraph`] and
+/// returns the selected network with the given roots as outputs.
+///
+/// The result is a JSON object that maps e-class ids to the ids of the selected e-nodes, either at
+/// the top level or in a `"choices"` object. Other members are ignored. Every e-class that is
+/// reachable from the roots needs a selected e-node and the selected e-nodes must not form a cycle.
+/// The e-graph must not have been modified since it was written.
+///
+/// # Example
+/// ```
+/// use eggmock::egg::{AstSize, EGraph, Extractor};
+/// use eggmock::{read_extraction_result, ripple_carry_adder, simulate_truth_tables};
+/// use eggmock::{write_serialized_egraph, AigLanguage, Network, NetworkLanguage};
+///
+/// let (mut egraph, roots) = ripple_carry_adder(EGraph::<AigLanguage, ()>::default(), 2);
+/// egraph.rebuild();
+/// let mut json = Vec::new();
+/// write_serialized_egraph(&egraph, &roots, |node| (!node.is_not()) as u8 as f64, &mut json)
+///     .unwrap();
+///
+/// // an external extractor would choose the e-nodes based on `json`
+/// let extractor = Extractor::new(&egraph, AstSize);
+/// let choices = Vec::from_iter(egraph.classes().map(|class| {
+///     let index = class.nodes.iter().position(|node| node == extractor.find_best_node(class.id));
+///     format!("\"{}\": \"{}.{}\"", class.id, class.id, index.unwrap())
+/// }));
+/// let result = format!("{{ \"choices\": {{ {} }} }}", choices.join(", "));
+///
+/// let network = read_extraction_result(&egraph, &roots, &result).unwrap();
+/// let expected = simulate_truth_tables(&(extractor, roots.clone()));
+/// assert_eq!(simulate_truth_tables(&network), expected);
+/// ```
+pub fn read_extraction_result<'g, L, A>(
+    egraph: &'g EGraph<L, A>,
+    roots: &[egg::Id],
+    source: &str,
+) -> Result<EGraphSelection<'g, L, A>, ExtractionResultError>
+where
+    L: NetworkLanguage,
+    A: Analysis<L>,
+{
+    let classes = FxHashMap::from_iter(egraph.classes().map(|class| (class.id, class)));
+    let mut parser = Parser {
+        source,
+        position: 0,
+        classes,
+        selection: Vec::new(),
+    };
+    parser.choices(true)?;
+    if let Some(c) = parser.peek() {
+        return Err(parser.error(format!("unexpected `{}`", c as char)));
+    }
+    let selection = EGraphSelection::new_unchecked(egraph, parser.selection, roots.iter().copied());
+    selection.validate().map_err(|invalid| {
+        let message = match invalid {
+            InvalidSelection::Unselected(class) => {
+                format!("no e-node selected for e-class `{class}`")
+            }
+            InvalidSelection::Cycle(class) => {
+                format!("the selected e-nodes form a cycle through e-class `{class}`")
+            }
+        };
+        ExtractionResultError {
+            position: source.len(),
+            message,
+        }
+    })?;
+    Ok(selection)
+}
+
+struct Parser<'s, 'g, L: Language, D> {
+    source: &'s str,
+    position: usize,
+    classes: FxHashMap<egg::Id, &'g EClass<L, D>>,
+    selection: Vec<(egg::Id, L)>,
+}
+
+impl<'s, L: Language, D> Parser<'s, '_, L, D> {
+    fn error(&self, message: impl Into<String>) -> ExtractionResultError {
+        ExtractionResultError {
+            position: self.position,
+            message: message.into(),
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        let bytes = self.source.as_bytes();
+        while bytes.get(self.position)?.is_ascii_whitespace() {
+            self.position += 1;
+        }
+        bytes.get(self.position).copied()
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), ExtractionResultError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.position += 1;
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!(
+                "expected `{}` but found `{}`",
+                expected as char, c as char
+            ))),
+            None => Err(self.error(format!("expected `{}`", expected as char))),
+        }
+    }
+
+    /// Parses an object that maps e-classes to e-nodes. If `top_level` is set, the object may
+    /// contain a nested `"choices"` object and members with other values are skipped.
+    fn choices(&mut self, top_level: bool) -> Result<(), ExtractionResultError> {
+        self.expect(b'{')?;
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(());
+        }
+        loop {
+            let key_position = self.position;
+            let key = self.string()?;
+            self.expect(b':')?;
+            match self.peek() {
+                Some(b'"') => self.choice(key, key_position)?,
+                Some(b'{') if top_level && key == "choices" => self.choices(false)?,
+                _ if top_level => self.skip_value()?,
+                _ => return Err(self.error("expected an e-node id")),
+            }
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                _ => return self.expect(b'}'),
+            }
+        }
+    }
+
+    fn choice(
+        &mut self,
+        class: String,
+        class_position: usize,
+    ) -> Result<(), ExtractionResultError> {
+        let class = class
+            .parse::<usize>()
+            .ok()
+            .map(egg::Id::from)
+            .filter(|class| self.classes.contains_key(class))
+            .ok_or_else(|| ExtractionResultError {
+                position: class_position,
+                message: format!("unknown e-class `{class}`"),
+            })?;
+        let node_position = self.position;
+        let node_id = self.string()?;
+        let node = node_id
+            .split_once('.')
+            .filter(|(node_class, _)| node_class.parse() == Ok(usize::from(class)))
+            .and_then(|(_, index)| index.parse::<usize>().ok())
+            .and_then(|index| self.classes[&class].nodes.get(index))
+            .ok_or_else(|| ExtractionResultError {
+                position: node_position,
+                message: format!("`{node_id}` is not an e-node of e-class `{class}`"),
+            })?;
+        self.selection.push((class, node.clone()));
+        Ok(())
+    }
+
+    fn string(&mut self) -> Result<String, ExtractionResultError> {
+        self.expect(b'"')?;
+        let mut value = String::new();
+        let mut chars = self.source[self.position..].char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.position += offset + 1;
+                    return Ok(value);
+                }
+                '\\' => match chars.next() {
+                    Some((_, 'u')) => {
+                        let start = self.position + offset + 2;
+                        let code = self
+                            .source
+                            .get(start..start + 4)
+                            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| ExtractionResultError {
+                                position: start,
+                                message: "invalid unicode escape".to_string(),
+                            })?;
+                        value.push(code);
+                        chars.nth(3);
+                    }
+                    Some((_, escaped)) => value.push(match escaped {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        c => c,
+                    }),
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        self.position = self.source.len();
+        Err(self.error("unterminated string"))
+    }
+
+    fn skip_value(&mut self) -> Result<(), ExtractionResultError> {
+        match self.peek() {
+            Some(b'"') => self.string().map(|_| ()),
+            Some(open @ (b'{' | b'[')) => {
+                let close = if open == b'{' { b'}' } else { b']' };
+                self.position += 1;
+                if self.peek() == Some(close) {
+                    self.position += 1;
+                    return Ok(());
+                }
+                loop {
+                    if open == b'{' {
+                        self.string()?;
+                        self.expect(b':')?;
+                    }
+                    self.skip_value()?;
+                    match self.peek() {
+                        Some(b',') => self.position += 1,
+                        _ => return self.expect(close),
+                    }
+                }
+            }
+            Some(_) => {
+                let rest = &self.source[self.position..];
+                let length = rest
+                    .find(|c: char| c.is_whitespace() || ",:]}".contains(c))
+                    .unwrap_or(rest.len());
+                let literal = &rest[..length];
+                if !matches!(literal, "true" | "false" | "null") && literal.parse::<f64>().is_err()
+                {
+                    return Err(self.error(format!("invalid value `{literal}`")));
+                }
+                self.position += length;
+                Ok(())
+            }
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+}
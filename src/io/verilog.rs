@@ -0,0 +1,774 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use super::netlist::{Netlist, Unresolved};
+use super::MAX_INPUTS;
+use crate::{CellMapping, Klut, Network, NetworkBuilder, Node, Receiver, Signal};
+
+/// An error that occurred while reading a Verilog file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerilogError {
+    /// The line in the source at which the error occurred, starting at 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for VerilogError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for VerilogError {}
+
+/// Writes the given cell mapping as a structural Verilog module with the given name.
+///
+/// Every mapped cell is instantiated with named connections to its pins. The ports are named after
+/// the primary inputs and outputs of the mapping or `x{i}` and `y{i}` if they are unnamed, and
+/// names that are not valid Verilog identifiers or that are reserved words are escaped. Ports with
+/// the same name and internal wires (`n{id}`) and instances (`g{id}`) that collide with a port are
+/// made unique by appending `_{k}`. Constants are written as `1'b0` and `1'b1`.
+///
+/// # Example
+/// ```
+/// use eggmock::egg::EGraph;
+/// use eggmock::{map_cells, read_genlib, write_verilog, CellMappingParams, XagLanguage};
+///
+/// let library = read_genlib(
+///     "GATE nand2 2 Y=!(A*B); PIN * INV 1 999 1 0 1 0
+///      GATE xor2 4 Y=A^B; PIN * UNKNOWN 1 999 2 0 2 0",
+/// )
+/// .unwrap();
+/// let mut egraph = EGraph::<XagLanguage, ()>::default();
+/// let [a, b] = [0, 1].map(|input| egraph.add(XagLanguage::Input(input)));
+/// let xor = egraph.add(XagLanguage::Xor([a, b]));
+/// let and = egraph.add(XagLanguage::And([a, b]));
+/// let nand = egraph.add(XagLanguage::Not(and));
+/// egraph.rebuild();
+///
+/// let params = CellMappingParams::default();
+/// let mut mapping = map_cells(&egraph, &[xor, nand, a], &library, &params).unwrap();
+/// mapping.names_mut().set_input(0, "a");
+/// mapping.names_mut().set_input(1, "wire");
+/// mapping.names_mut().set_output(0, "x");
+/// mapping.names_mut().set_output(1, "n3");
+/// let mut verilog = Vec::new();
+/// write_verilog(&mapping, "example", &mut verilog).unwrap();
+/// assert_eq!(
+///     String::from_utf8(verilog).unwrap(),
+///     "module example (a, \\wire , x, n3, y2);
+///   input a, \\wire ;
+///   output x, n3, y2;
+///   wire n2, n3_1;
+///   xor2 g2 (.A(a), .B(\\wire ), .Y(n2));
+///   nand2 g3 (.A(a), .B(\\wire ), .Y(n3_1));
+///   assign x = n2;
+///   assign n3 = n3_1;
+///   assign y2 = a;
+/// endmodule
+/// "
+/// );
+/// ```
+pub fn write_verilog(
+    mapping: &CellMapping,
+    module_name: &str,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    let mut names = Names::default();
+    let inputs = Vec::from_iter((0..mapping.num_inputs()).map(|input| {
+        names.unique(
+            mapping
+                .input_name(input)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("x{input}")),
+        )
+    }));
+    let outputs = Vec::from_iter(mapping.outputs().enumerate().map(|(index, output)| {
+        let name = mapping
+            .output_name(index)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("y{index}"));
+        (names.unique(name), output)
+    }));
+    let mut wires = FxHashMap::default();
+    let mut instances = Vec::new();
+    for id in mapping.ids().filter(|id| mapping.cell(*id).is_some()) {
+        wires.insert(id, names.unique(format!("n{}", u32::from(id))));
+        instances.push((id, names.unique(format!("g{}", u32::from(id)))));
+    }
+
+    let ports = Vec::from_iter(
+        inputs
+            .iter()
+            .chain(outputs.iter().map(|(name, _)| name))
+            .map(String::as_str),
+    );
+    writeln!(
+        writer,
+        "module {} ({});",
+        identifier(module_name.to_string()),
+        ports.join(", ")
+    )?;
+    if !inputs.is_empty() {
+        writeln!(writer, "  input {};", inputs.join(", "))?;
+    }
+    if !outputs.is_empty() {
+        let names = Vec::from_iter(outputs.iter().map(|(name, _)| name.as_str()));
+        writeln!(writer, "  output {};", names.join(", "))?;
+    }
+    if !instances.is_empty() {
+        let names = Vec::from_iter(instances.iter().map(|(id, _)| wires[id].as_str()));
+        writeln!(writer, "  wire {};", names.join(", "))?;
+    }
+
+    let net = |signal: Signal| {
+        let name = match mapping.node(signal.node_id()) {
+            Klut::Input(input) => inputs[input as usize].clone(),
+            Klut::False => return format!("1'b{}", signal.is_inverted() as u8),
+            Klut::Lut(..) => wires[&signal.node_id()].clone(),
+        };
+        if signal.is_inverted() {
+            format!("~{name}")
+        } else {
+            name
+        }
+    };
+    for (id, instance) in &instances {
+        let cell = mapping.cell(*id).expect("instances are bound to cells");
+        let node = mapping.node(*id);
+        let mut connections = Vec::from_iter(
+            cell.pins()
+                .iter()
+                .zip(node.inputs())
+                .map(|(pin, input)| format!(".{}({})", identifier(pin.name.clone()), net(*input))),
+        );
+        connections.push(format!(
+            ".{}({})",
+            identifier(cell.output().to_string()),
+            wires[id]
+        ));
+        writeln!(
+            writer,
+            "  {} {instance} ({});",
+            identifier(cell.name().to_string()),
+            connections.join(", ")
+        )?;
+    }
+    for (name, output) in &outputs {
+        writeln!(writer, "  assign {name} = {};", net(*output))?;
+    }
+    writeln!(writer, "endmodule")
+}
+
+/// The names that are declared in the scope of a module.
+#[derive(Default)]
+struct Names {
+    used: FxHashSet<String>,
+}
+
+impl Names {
+    /// Returns the given name as an identifier or, if it is already used, the first of `{name}_1`,
+    /// `{name}_2`, ... that is not. An escaped identifier refers to the same net as the plain one,
+    /// so the names are compared without escaping.
+    fn unique(&mut self, name: String) -> String {
+        let mut unique = name.clone();
+        let mut suffix = 0;
+        while self.used.contains(&unique) {
+            suffix += 1;
+            unique = format!("{name}_{suffix}");
+        }
+        self.used.insert(unique.clone());
+        identifier(unique)
+    }
+}
+
+/// The reserved words of Verilog-2005.
+const KEYWORDS: [&str; 124] = [
+    "always", "and", "assign", "automatic", "begin", "buf", "bufif0", "bufif1", "case", "casex",
+    "casez", "cell", "cmos", "config", "deassign", "default", "defparam", "design", "disable",
+    "edge", "else", "end", "endcase", "endconfig", "endfunction", "endgenerate", "endmodule",
+    "endprimitive", "endspecify", "endtable", "endtask", "event", "for", "force", "forever",
+    "fork", "function", "generate", "genvar", "highz0", "highz1", "if", "ifnone", "incdir",
+    "include", "initial", "inout", "input", "instance", "integer", "join", "large", "liblist",
+    "library", "localparam", "macromodule", "medium", "module", "nand", "negedge", "nmos", "nor",
+    "noshowcancelled", "not", "notif0", "notif1", "or", "output", "parameter", "pmos", "posedge",
+    "primitive", "pull0", "pull1", "pulldown", "pullup", "pulsestyle_onevent",
+    "pulsestyle_ondetect", "rcmos", "real", "realtime", "reg", "release", "repeat", "rnmos",
+    "rpmos", "rtran", "rtranif0", "rtranif1", "scalared", "showcancelled", "signed", "small",
+    "specify", "specparam", "strong0", "strong1", "supply0", "supply1", "table", "task", "time",
+    "tran", "tranif0", "tranif1", "tri", "tri0", "tri1", "triand", "trior", "trireg", "unsigned",
+    "use", "uwire", "vectored", "wait", "wand", "weak0", "weak1", "while", "wire", "wor", "xnor",
+    "xor",
+];
+
+/// Escapes the given name if it is not a simple Verilog identifier or if it is a reserved word.
+fn identifier(name: String) -> String {
+    let mut chars = name.chars();
+    let simple = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if simple && !KEYWORDS.contains(&name.as_str()) {
+        name
+    } else {
+        format!("\\{name} ")
+    }
+}
+
+/// Reads a combinational network from a gate-level Verilog module and sends it to the given
+/// receiver.
+///
+/// The supported subset covers the output of logic synthesis tools and of [`write_verilog`] for
+/// networks without cells: a single module with `input`, `output` and `wire` declarations, which
+/// may be vectors, `assign` statements and the gate primitives `and`, `or`, `nand`, `nor`, `xor`,
+/// `xnor`, `not` and `buf`. Expressions consist of single bits (`a` or `a[3]`), the constants `0`,
+/// `1`, `1'b0` and `1'b1`, the operators `~`, `!`, `&`, `&&`, `^`, `~^`, `^~`, `|` and `||` with
+/// Verilog's precedence, the conditional operator `?:` and parentheses. Signals may be used before
+/// they are assigned.
+///
+/// The primary inputs and outputs are numbered in the order of their declarations, where the bits
+/// of vectors are named like `a[3]` and ordered from the least significant index.
+///
+/// # Example
+/// ```
+/// use eggmock::{read_verilog, simulate_truth_tables, Aig, MutableNetwork, Network, TruthTable};
+///
+/// let verilog = "
+/// module example (a, b, y);
+///   input a;
+///   input [1:0] b;
+///   output y;
+///   wire t;
+///   assign y = t | ~b[1];
+///   and g0 (t, a, b[0]);
+/// endmodule
+/// ";
+/// let aig = read_verilog(MutableNetwork::<Aig>::default(), verilog).unwrap();
+/// assert_eq!(aig.input_name(2), Some("b[1]"));
+/// assert_eq!(simulate_truth_tables(&aig), [TruthTable::from_hex(3, "8f").unwrap()]);
+///
+/// // vectors are limited to the number of inputs that a network can have
+/// let wide = "module wide (a, y); input [4294967295:0] a; output y; assign y = a[0]; endmodule";
+/// let error = read_verilog(MutableNetwork::<Aig>::default(), wide).err().unwrap();
+/// assert_eq!(error.to_string(), "line 1: vector is too wide");
+/// ```
+pub fn read_verilog<R: Receiver>(receiver: R, source: &str) -> Result<R::Result, VerilogError> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        position: 0,
+        inputs: Vec::new(),
+        outputs: Vec::new(),
+        vectors: FxHashMap::default(),
+        netlist: Netlist::new(),
+    };
+    parser.module()?;
+
+    let Parser {
+        inputs,
+        outputs,
+        mut netlist,
+        ..
+    } = parser;
+    let mut builder = NetworkBuilder::new(receiver);
+    for (line, name) in &inputs {
+        let input = builder.named_pi(name);
+        if netlist.signals.insert(name.clone(), input).is_some() {
+            return Err(VerilogError {
+                line: *line,
+                message: format!("input `{name}` is declared twice"),
+            });
+        }
+        if let Some((line, _)) = netlist.definitions.get(name) {
+            return Err(VerilogError {
+                line: *line,
+                message: format!("input `{name}` is assigned"),
+            });
+        }
+    }
+    for (line, name) in outputs {
+        let resolved = netlist.resolve(
+            &name,
+            &mut builder,
+            |(_, expression)| {
+                let mut names = Vec::new();
+                expression.signals(&mut names);
+                names
+            },
+            |(_, expression), signals, builder| expression.build(signals, builder),
+        );
+        let output = resolved.map_err(|unresolved| match unresolved {
+            Unresolved::Undefined { key, user } => VerilogError {
+                line: user.map_or(line, |user| netlist.definitions[&user].0),
+                message: format!("`{key}` is used but not assigned"),
+            },
+            Unresolved::Cycle(key) => VerilogError {
+                line: netlist.definitions[&key].0,
+                message: format!("`{key}` depends on itself"),
+            },
+        })?;
+        builder.named_po(output, &name);
+    }
+    Ok(builder.done())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Identifier(String),
+    Number(String),
+    Symbol(&'static str),
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Identifier(name) | Token::Number(name) => write!(f, "`{name}`"),
+            Token::Symbol(symbol) => write!(f, "`{symbol}`"),
+        }
+    }
+}
+
+const SYMBOLS: [&str; 20] = [
+    "~^", "^~", "&&", "||", "(", ")", "[", "]", ":", ";", ",", "=", "~", "!", "&", "|", "^", "?",
+    ".", "#",
+];
+
+/// Splits the source into tokens with their line numbers, skipping comments, attributes and
+/// compiler directives.
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, VerilogError> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut rest = source;
+    loop {
+        let trimmed = rest.trim_start();
+        line += rest[..rest.len() - trimmed.len()].matches('\n').count();
+        rest = trimmed;
+        let Some(c) = rest.chars().next() else {
+            return Ok(tokens);
+        };
+        let skipped = if rest.starts_with("//") || c == '`' {
+            Some(rest.find('\n').unwrap_or(rest.len()))
+        } else if let Some(end) = ["/*", "(*"]
+            .iter()
+            .zip(["*/", "*)"])
+            .find(|(start, _)| rest.starts_with(**start))
+            .map(|(_, end)| end)
+        {
+            let length = rest[2..].find(end).ok_or_else(|| VerilogError {
+                line,
+                message: "unterminated comment".to_string(),
+            })?;
+            Some(length + 4)
+        } else {
+            None
+        };
+        if let Some(length) = skipped {
+            line += rest[..length].matches('\n').count();
+            rest = &rest[length..];
+            continue;
+        }
+
+        let (token, length) = if c == '\\' {
+            let length = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            (Token::Identifier(rest[1..length].to_string()), length)
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let length = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$'))
+                .unwrap_or(rest.len());
+            (Token::Identifier(rest[..length].to_string()), length)
+        } else if c.is_ascii_digit() || c == '\'' {
+            let length = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '\'' || c == '_'))
+                .unwrap_or(rest.len());
+            (Token::Number(rest[..length].to_string()), length)
+        } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            (Token::Symbol(symbol), symbol.len())
+        } else {
+            return Err(VerilogError {
+                line,
+                message: format!("unexpected `{c}`"),
+            });
+        };
+        tokens.push((line, token));
+        rest = &rest[length..];
+    }
+}
+
+/// An expression over single-bit signals.
+enum Expression {
+    Signal(String),
+    Constant(bool),
+    Not(Box<Expression>),
+    And(Vec<Expression>),
+    Or(Vec<Expression>),
+    Xor(Vec<Expression>),
+    Mux(Box<[Expression; 3]>),
+}
+
+impl Expression {
+    /// Appends the names of the signals used by this expression.
+    fn signals(&self, names: &mut Vec<String>) {
+        match self {
+            Expression::Signal(name) => names.push(name.clone()),
+            Expression::Constant(_) => {}
+            Expression::Not(operand) => operand.signals(names),
+            Expression::And(operands) | Expression::Or(operands) | Expression::Xor(operands) => {
+                operands.iter().for_each(|operand| operand.signals(names))
+            }
+            Expression::Mux(operands) => operands.iter().for_each(|operand| operand.signals(names)),
+        }
+    }
+
+    fn build<R: Receiver>(
+        &self,
+        signals: &FxHashMap<String, Signal>,
+        builder: &mut NetworkBuilder<R>,
+    ) -> Signal {
+        let build_all = |operands: &[Expression], builder: &mut NetworkBuilder<R>| {
+            Vec::from_iter(
+                operands
+                    .iter()
+                    .map(|operand| operand.build(signals, builder)),
+            )
+        };
+        match self {
+            Expression::Signal(name) => signals[name],
+            Expression::Constant(value) => builder.constant(*value),
+            Expression::Not(operand) => !operand.build(signals, builder),
+            Expression::And(operands) => {
+                let operands = build_all(operands, builder);
+                builder.and_all(&operands)
+            }
+            Expression::Or(operands) => {
+                let operands = build_all(operands, builder);
+                builder.or_all(&operands)
+            }
+            Expression::Xor(operands) => {
+                let operands = build_all(operands, builder);
+                builder.xor_all(&operands)
+            }
+            Expression::Mux(operands) => {
+                let [condition, then, otherwise] = build_all(&operands[..], builder)[..] else {
+                    unreachable!()
+                };
+                builder.mux(condition, then, otherwise)
+            }
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    /// The input bits with the lines of their declarations.
+    inputs: Vec<(usize, String)>,
+    /// The output bits with the lines of their declarations.
+    outputs: Vec<(usize, String)>,
+    /// The declared vectors with their most and least significant indices.
+    vectors: FxHashMap<String, (u64, u64)>,
+    /// The assigned signals with the lines of their assignments.
+    netlist: Netlist<String, (usize, Expression)>,
+}
+
+impl Parser {
+    fn line(&self) -> usize {
+        match self.tokens.get(self.position) {
+            Some((line, _)) => *line,
+            None => self.tokens.last().map_or(1, |(line, _)| *line),
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> VerilogError {
+        VerilogError {
+            line: self.line(),
+            message: message.into(),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Result<Token, VerilogError> {
+        let token = self.peek().cloned();
+        let token = token.ok_or_else(|| self.error("unexpected end of input"))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Identifier(name)) if name == keyword)
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), VerilogError> {
+        if self.eat(symbol) {
+            return Ok(());
+        }
+        match self.peek() {
+            Some(token) => Err(self.error(format!("expected `{symbol}` but found {token}"))),
+            None => Err(self.error(format!("expected `{symbol}`"))),
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String, VerilogError> {
+        match self.next()? {
+            Token::Identifier(name) => Ok(name),
+            token => {
+                self.position -= 1;
+                Err(self.error(format!("expected an identifier but found {token}")))
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<u64, VerilogError> {
+        match self.next()? {
+            Token::Number(number) if number.bytes().all(|c| c.is_ascii_digit()) => {
+                number.parse().map_err(|_| {
+                    self.position -= 1;
+                    self.error("number is too large")
+                })
+            }
+            token => {
+                self.position -= 1;
+                Err(self.error(format!("expected a number but found {token}")))
+            }
+        }
+    }
+
+    fn module(&mut self) -> Result<(), VerilogError> {
+        if !self.is_keyword("module") {
+            return Err(self.error("expected a module"));
+        }
+        self.position += 1;
+        self.identifier()?;
+        if self.eat("#") {
+            return Err(self.error("parameters are not supported"));
+        }
+        if self.eat("(") && !self.eat(")") {
+            loop {
+                if self.is_keyword("input") || self.is_keyword("output") {
+                    self.declaration(true)?;
+                } else {
+                    self.identifier()?;
+                }
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            self.expect(")")?;
+        }
+        self.expect(";")?;
+
+        loop {
+            let line = self.line();
+            let keyword = self.identifier()?;
+            match keyword.as_str() {
+                "endmodule" => break,
+                "input" | "output" | "wire" => {
+                    self.position -= 1;
+                    self.declaration(false)?;
+                    self.expect(";")?;
+                }
+                "assign" => loop {
+                    let target = self.bit()?;
+                    self.expect("=")?;
+                    let expression = self.conditional()?;
+                    self.define(line, target, expression)?;
+                    if !self.eat(",") {
+                        self.expect(";")?;
+                        break;
+                    }
+                },
+                "and" | "or" | "nand" | "nor" | "xor" | "xnor" | "not" | "buf" => loop {
+                    if !self.eat("(") {
+                        self.identifier()?;
+                        self.expect("(")?;
+                    }
+                    let target = self.bit()?;
+                    let mut operands = Vec::new();
+                    while self.eat(",") {
+                        operands.push(self.conditional()?);
+                    }
+                    self.expect(")")?;
+                    let expression = match keyword.as_str() {
+                        "not" | "buf" if operands.len() != 1 => {
+                            return Err(self.error(format!("`{keyword}` takes a single input")))
+                        }
+                        _ if operands.is_empty() => {
+                            return Err(self.error(format!("`{keyword}` without inputs")))
+                        }
+                        "and" | "nand" => Expression::And(operands),
+                        "or" | "nor" => Expression::Or(operands),
+                        "xor" | "xnor" => Expression::Xor(operands),
+                        _ => operands.pop().unwrap(),
+                    };
+                    let expression = match keyword.as_str() {
+                        "nand" | "nor" | "xnor" | "not" => Expression::Not(Box::new(expression)),
+                        _ => expression,
+                    };
+                    self.define(line, target, expression)?;
+                    if !self.eat(",") {
+                        self.expect(";")?;
+                        break;
+                    }
+                },
+                _ => {
+                    self.position -= 1;
+                    return Err(self.error(format!("unsupported statement `{keyword}`")));
+                }
+            }
+        }
+        if let Some(token) = self.peek() {
+            return Err(self.error(format!("unexpected {token} after the module")));
+        }
+        Ok(())
+    }
+
+    /// Parses an `input`, `output` or `wire` declaration of one or more signals. In the port list
+    /// of a module, a declaration ends before the next one.
+    fn declaration(&mut self, in_port_list: bool) -> Result<(), VerilogError> {
+        let line = self.line();
+        let kind = self.identifier()?;
+        if kind != "wire" && self.is_keyword("wire") {
+            self.position += 1;
+        }
+        let range = if self.eat("[") {
+            let msb = self.number()?;
+            self.expect(":")?;
+            let lsb = self.number()?;
+            if msb.abs_diff(lsb) >= MAX_INPUTS {
+                return Err(self.error("vector is too wide"));
+            }
+            self.expect("]")?;
+            Some((msb, lsb))
+        } else {
+            None
+        };
+        loop {
+            let name = self.identifier()?;
+            let bits = match range {
+                Some((msb, lsb)) => {
+                    self.vectors.insert(name.clone(), (msb, lsb));
+                    Vec::from_iter((msb.min(lsb)..=msb.max(lsb)).map(|i| format!("{name}[{i}]")))
+                }
+                None => vec![name],
+            };
+            if kind == "input" && (self.inputs.len() + bits.len()) as u64 > MAX_INPUTS {
+                return Err(self.error("too many inputs"));
+            }
+            match kind.as_str() {
+                "input" => self.inputs.extend(bits.into_iter().map(|bit| (line, bit))),
+                "output" => self.outputs.extend(bits.into_iter().map(|bit| (line, bit))),
+                _ => {}
+            }
+            let next_is_declaration = matches!(
+                self.tokens.get(self.position + 1),
+                Some((_, Token::Identifier(name))) if name == "input" || name == "output"
+            );
+            if (in_port_list && next_is_declaration) || !self.eat(",") {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Parses a single-bit signal, i.e. a scalar or a bit of a vector.
+    fn bit(&mut self) -> Result<String, VerilogError> {
+        let name = self.identifier()?;
+        if self.eat("[") {
+            let index = self.number()?;
+            self.expect("]")?;
+            if let Some((msb, lsb)) = self.vectors.get(&name) {
+                if index < *msb.min(lsb) || index > *msb.max(lsb) {
+                    return Err(self.error(format!("index {index} is out of range for `{name}`")));
+                }
+            }
+            return Ok(format!("{name}[{index}]"));
+        }
+        if self.vectors.contains_key(&name) {
+            return Err(self.error(format!("vector `{name}` used as a single bit")));
+        }
+        Ok(name)
+    }
+
+    fn define(
+        &mut self,
+        line: usize,
+        target: String,
+        expression: Expression,
+    ) -> Result<(), VerilogError> {
+        if self.netlist.definitions.contains_key(&target) {
+            return Err(VerilogError {
+                line,
+                message: format!("`{target}` is assigned twice"),
+            });
+        }
+        self.netlist.definitions.insert(target, (line, expression));
+        Ok(())
+    }
+
+    fn conditional(&mut self) -> Result<Expression, VerilogError> {
+        let condition = self.binary(0)?;
+        if !self.eat("?") {
+            return Ok(condition);
+        }
+        let then = self.conditional()?;
+        self.expect(":")?;
+        let otherwise = self.conditional()?;
+        Ok(Expression::Mux(Box::new([condition, then, otherwise])))
+    }
+
+    /// Parses the binary operators from the given level on, from the weakest to the strongest:
+    /// `||`, `&&`, `|`, `^` and `~^`, `&`.
+    fn binary(&mut self, level: usize) -> Result<Expression, VerilogError> {
+        const LEVELS: [&[&str]; 5] = [&["||"], &["&&"], &["|"], &["^", "~^", "^~"], &["&"]];
+        let Some(operators) = LEVELS.get(level) else {
+            return self.unary();
+        };
+        let mut lhs = self.binary(level + 1)?;
+        while let Some(operator) = operators.iter().find(|operator| self.eat(operator)) {
+            let rhs = self.binary(level + 1)?;
+            lhs = match *operator {
+                "||" | "|" => Expression::Or(vec![lhs, rhs]),
+                "&&" | "&" => Expression::And(vec![lhs, rhs]),
+                "^" => Expression::Xor(vec![lhs, rhs]),
+                _ => Expression::Not(Box::new(Expression::Xor(vec![lhs, rhs]))),
+            };
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expression, VerilogError> {
+        if self.eat("~") || self.eat("!") {
+            return Ok(Expression::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("(") {
+            let expression = self.conditional()?;
+            self.expect(")")?;
+            return Ok(expression);
+        }
+        match self.peek() {
+            Some(Token::Number(number)) => {
+                let value = match number.as_str() {
+                    "0" | "1'b0" | "1'h0" | "1'd0" | "'b0" => false,
+                    "1" | "1'b1" | "1'h1" | "1'd1" | "'b1" => true,
+                    _ => return Err(self.error(format!("unsupported constant `{number}`"))),
+                };
+                self.position += 1;
+                Ok(Expression::Constant(value))
+            }
+            Some(Token::Identifier(_)) => Ok(Expression::Signal(self.bit()?)),
+            Some(token) => Err(self.error(format!("expected an expression but found {token}"))),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+}
@@ -0,0 +1,363 @@
+use std::error::Error;
+use std::ffi::OsString;
+use std::fmt::{Display, Formatter};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{
+    binary_supports, ffi_str, read_binary, write_binary, Aig, AigReceiverFFI, BinaryError,
+    ConvertingRewriter, Interface, Mig, MigReceiverFFI, MutableNetwork, Network, Node, Receiver,
+    ReceiverFFI, Xag, XagReceiverFFI, Xmg, XmgReceiverFFI,
+};
+
+/// An error that occurred while rewriting in a worker process.
+#[derive(Debug)]
+pub enum WorkerError {
+    /// Communicating with the worker process failed.
+    Io(io::Error),
+    /// The worker did not finish within the time limit and was killed.
+    Timeout(Duration),
+    /// The worker exited unsuccessfully, e.g. because it panicked or exceeded the memory limit.
+    Failed { status: ExitStatus, stderr: String },
+    /// The worker returned something that is not a network of the expected type.
+    InvalidOutput(BinaryError),
+}
+
+impl Display for WorkerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorkerError::Io(error) => write!(f, "could not run worker: {error}"),
+            WorkerError::Timeout(limit) => write!(f, "worker timed out after {limit:?}"),
+            WorkerError::Failed { status, stderr } => {
+                write!(f, "worker failed with {status}")?;
+                match stderr.trim() {
+                    "" => Ok(()),
+                    stderr => write!(f, ": {stderr}"),
+                }
+            }
+            WorkerError::InvalidOutput(error) => write!(f, "invalid worker output: {error}"),
+        }
+    }
+}
+
+impl Error for WorkerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WorkerError::Io(error) => Some(error),
+            WorkerError::InvalidOutput(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for WorkerError {
+    fn from(error: io::Error) -> Self {
+        WorkerError::Io(error)
+    }
+}
+
+/// A rewriter that rewrites networks of type `N` into networks of type `O` in a separate worker
+/// process, so that panics, runaway saturations or excessive memory usage of the rewrite do not
+/// affect the calling process.
+///
+/// The network is passed to the standard input of the worker in the format of [`write_binary`]
+/// and the worker has to write the rewritten network to its standard output in the same format,
+/// which [`run_worker`] does for any [`ConvertingRewriter`]. Networks whose gates cannot be written
+/// in this format, such as [`Klut`](crate::Klut) networks, can therefore not be rewritten in
+/// isolation.
+///
+/// The `eggmock worker` subcommand of the `eggmock` binary is a worker for the built-in rule sets,
+/// e.g. `IsolatedRewriter::<Mig>::new("eggmock").arg("worker").arg("--network").arg("mig")`. Other
+/// workers are small binaries like the following:
+/// ```no_run
+/// # use eggmock::{Aig, MutableNetwork, Network, Receiver, Rewriter};
+/// # struct MyRewriter;
+/// # impl Rewriter for MyRewriter {
+/// #     type Node = Aig;
+/// #     type Intermediate = MutableNetwork<Aig>;
+/// #     fn create_receiver(
+/// #         &mut self,
+/// #     ) -> impl Receiver<Node = Aig, Result = Self::Intermediate> + 'static {
+/// #         MutableNetwork::default()
+/// #     }
+/// #     fn rewrite(self, input: Self::Intermediate, output: impl Receiver<Node = Aig, Result = ()>) {
+/// #         input.send(output)
+/// #     }
+/// # }
+/// fn main() -> std::io::Result<()> {
+///     eggmock::run_worker(MyRewriter, std::io::stdin(), std::io::stdout())
+/// }
+/// ```
+///
+/// Networks are rewritten with [`rewrite_network`](Self::rewrite_network), which reports a failed
+/// rewrite as a [`WorkerError`]. From C++, `rewrite_{ntk}_isolated` throws in that case (see
+/// [`IsolatedRewriterFFI`]).
+pub struct IsolatedRewriter<N, O = N> {
+    program: OsString,
+    args: Vec<OsString>,
+    time_limit: Option<Duration>,
+    memory_limit: Option<u64>,
+    _types: PhantomData<fn(N) -> O>,
+}
+
+impl<N: Node, O: Node> IsolatedRewriter<N, O> {
+    /// Creates a rewriter that runs the given worker program without limits.
+    ///
+    /// # Panics
+    /// Panics if networks of type `N` or `O` cannot be passed to or from the worker, i.e. if
+    /// [`write_binary`] does not support them.
+    pub fn new(program: impl Into<OsString>) -> Self {
+        let typename = N::NTK_TYPENAME;
+        assert!(binary_supports::<N>(), "{typename} networks cannot be passed to a worker");
+        let typename = O::NTK_TYPENAME;
+        assert!(binary_supports::<O>(), "{typename} networks cannot be returned by a worker");
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            time_limit: None,
+            memory_limit: None,
+            _types: PhantomData,
+        }
+    }
+
+    /// Adds an argument that is passed to the worker.
+    pub fn arg(mut self, arg: impl Into<OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Sets the wall-clock time after which the worker is killed.
+    pub fn time_limit(mut self, limit: Duration) -> Self {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    /// Sets the maximum size of the address space of the worker in bytes. Only supported on Unix
+    /// systems and ignored on others.
+    pub fn memory_limit(mut self, bytes: u64) -> Self {
+        self.memory_limit = Some(bytes);
+        self
+    }
+
+    /// Rewrites the given network in a worker process and returns the result.
+    pub fn rewrite_network(
+        &self,
+        network: &(impl Network<Node = N> + ?Sized),
+    ) -> Result<MutableNetwork<O>, WorkerError> {
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .stdin(Stdio::from(input_file(network)?))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        #[cfg(unix)]
+        if let Some(bytes) = self.memory_limit {
+            use std::os::unix::process::CommandExt;
+            let limit = libc::rlimit {
+                rlim_cur: bytes as libc::rlim_t,
+                rlim_max: bytes as libc::rlim_t,
+            };
+            // SAFETY: setrlimit is async-signal-safe and only affects the child
+            unsafe {
+                command.pre_exec(move || {
+                    if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+
+        let mut child = command.spawn()?;
+        let stdout = read_in_background(child.stdout.take());
+        let stderr = read_in_background(child.stderr.take());
+        let deadline = self.time_limit.map(|limit| Instant::now() + limit);
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(WorkerError::Timeout(self.time_limit.unwrap()));
+            }
+            thread::sleep(Duration::from_millis(5));
+        };
+        let stdout = stdout.join().expect("reading should not panic")?;
+        let stderr = stderr.join().expect("reading should not panic")?;
+        if !status.success() {
+            return Err(WorkerError::Failed {
+                status,
+                stderr: String::from_utf8_lossy(&stderr).into_owned(),
+            });
+        }
+        read_binary(MutableNetwork::default(), &stdout).map_err(WorkerError::InvalidOutput)
+    }
+}
+
+/// Writes the network to an unlinked temporary file that serves as the input of the worker.
+/// Unlike a pipe, this cannot raise `SIGPIPE` in the calling process if the worker exits early.
+fn input_file(network: &(impl Network + ?Sized)) -> io::Result<File> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let path: PathBuf = std::env::temp_dir().join(format!(
+        "eggmock-{}-{}.bin",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    let written = write_binary(network, &mut file);
+    let input = File::open(&path);
+    fs::remove_file(&path)?;
+    written?;
+    input
+}
+
+fn read_in_background(
+    source: Option<impl Read + Send + 'static>,
+) -> thread::JoinHandle<io::Result<Vec<u8>>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut source) = source {
+            source.read_to_end(&mut bytes)?;
+        }
+        Ok(bytes)
+    })
+}
+
+/// A struct that contains a data pointer and a function that rewrites the received network in a
+/// worker process, see [`IsolatedRewriter`]. In contrast to [`RewriterFFI`](crate::RewriterFFI),
+/// the rewrite can fail: in that case, `set_error` is called with `error` and the reason, nothing
+/// is passed to the callback and `false` is returned.
+///
+/// Allocated memory is released after a call to the rewrite function. The names of the primary
+/// inputs and outputs as well as the number of primary inputs of the received network are restored
+/// on the rewritten network.
+#[repr(C)]
+pub struct IsolatedRewriterFFI<N: Node, O: Node = N> {
+    data: *mut libc::c_void,
+    rewrite: extern "C" fn(
+        *mut libc::c_void,
+        O::ReceiverFFI<()>,
+        *mut libc::c_void,
+        extern "C" fn(*mut libc::c_void, *const libc::c_char, usize),
+    ) -> bool,
+    _input: PhantomData<fn(N) -> ()>,
+}
+
+impl<N: Node, O: Node> IsolatedRewriterFFI<N, O> {
+    pub fn new(rewriter: IsolatedRewriter<N, O>) -> N::ReceiverFFI<IsolatedRewriterFFI<N, O>> {
+        N::ReceiverFFI::new(MutableNetwork::default().collect_interface().map(
+            |(network, interface)| {
+                let data = Box::into_raw(Box::new((rewriter, network, interface)));
+                IsolatedRewriterFFI {
+                    data: data as *mut libc::c_void,
+                    rewrite: Self::rewrite,
+                    _input: PhantomData,
+                }
+            },
+        ))
+    }
+
+    extern "C" fn rewrite(
+        data: *mut libc::c_void,
+        callback: O::ReceiverFFI<()>,
+        error: *mut libc::c_void,
+        set_error: extern "C" fn(*mut libc::c_void, *const libc::c_char, usize),
+    ) -> bool {
+        let data = unsafe {
+            Box::from_raw(data as *mut (IsolatedRewriter<N, O>, MutableNetwork<N>, Interface))
+        };
+        let (rewriter, network, interface) = *data;
+        match rewriter.rewrite_network(&network) {
+            Ok(rewritten) => {
+                rewritten.send(callback.with_interface(interface));
+                true
+            }
+            Err(reason) => {
+                let reason = reason.to_string();
+                set_error(error, reason.as_ptr() as *const libc::c_char, reason.len());
+                false
+            }
+        }
+    }
+}
+
+/// Runs the given rewriter as the worker of an [`IsolatedRewriter`]: reads a network in the
+/// format of [`write_binary`] from `input`, rewrites it and writes the result to `output` in the
+/// same format. The interface of the network is restored on the result.
+pub fn run_worker<R: ConvertingRewriter>(
+    mut rewriter: R,
+    mut input: impl Read,
+    mut output: impl Write,
+) -> io::Result<()> {
+    let mut bytes = Vec::new();
+    input.read_to_end(&mut bytes)?;
+    let receiver = rewriter.create_receiver().collect_interface();
+    let (network, interface) = read_binary(receiver, &bytes)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    let mut rewritten = None;
+    rewriter.rewrite(
+        network,
+        MutableNetwork::default()
+            .with_interface(interface)
+            .map(|network| rewritten = Some(network)),
+    );
+    let rewritten = rewritten.expect("rewriters should pass their result to the output");
+    write_binary(&rewritten, &mut output)?;
+    output.flush()
+}
+
+macro_rules! isolated_rewrite_ffi {
+    ($($node:ident, $receiver:ident, $function:ident;)*) => {$(
+        /// Creates a receiver that rewrites the received network in a worker process, see
+        /// [`IsolatedRewriter`]. The worker is `program` with the `num_args` arguments in `args`,
+        /// whose sizes are in `arg_sizes`. A time or memory limit of zero means no limit. If the
+        /// worker fails, the rewrite reports the reason instead of returning a network, see
+        /// [`IsolatedRewriterFFI`].
+        ///
+        /// # Safety
+        /// `program` has to point to `program_size` readable bytes if `program_size` is not zero
+        /// and `args` and `arg_sizes` to `num_args` elements each, where the arguments satisfy the
+        /// same requirement as `program`.
+        #[no_mangle]
+        pub unsafe extern "C" fn $function(
+            program: *const libc::c_char,
+            program_size: usize,
+            args: *const *const libc::c_char,
+            arg_sizes: *const usize,
+            num_args: usize,
+            time_limit_ms: u64,
+            memory_limit: u64,
+        ) -> $receiver<IsolatedRewriterFFI<$node>> {
+            let program = ffi_str(program, program_size).into_owned();
+            let mut rewriter = IsolatedRewriter::<$node>::new(program);
+            for arg in 0..num_args {
+                rewriter = rewriter.arg(ffi_str(*args.add(arg), *arg_sizes.add(arg)).into_owned());
+            }
+            if time_limit_ms != 0 {
+                rewriter = rewriter.time_limit(Duration::from_millis(time_limit_ms));
+            }
+            if memory_limit != 0 {
+                rewriter = rewriter.memory_limit(memory_limit);
+            }
+            IsolatedRewriterFFI::new(rewriter)
+        }
+    )*};
+}
+
+isolated_rewrite_ffi! {
+    Mig, MigReceiverFFI, eggmock_mig_isolated_rewrite;
+    Aig, AigReceiverFFI, eggmock_aig_isolated_rewrite;
+    Xag, XagReceiverFFI, eggmock_xag_isolated_rewrite;
+    Xmg, XmgReceiverFFI, eggmock_xmg_isolated_rewrite;
+}
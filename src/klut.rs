@@ -0,0 +1,489 @@
+use std::fmt::{Display, Formatter};
+
+use egg::{FromOp, FromOpError, Language};
+
+use crate::{
+    ffi_str, GateFunction, GateType, NetworkLanguage, Node, Receiver, ReceiverFFI, Register,
+    Signal, SimulationValue, TruthTable,
+};
+
+/// A node of a k-LUT network, i.e. a network in which every gate is a look-up table that may
+/// compute an arbitrary function of its inputs. Corresponds to *mockturtle*'s `klut_network`.
+///
+/// # Example
+/// ```
+/// use eggmock::{convert_network, simulate_truth_tables};
+/// use eggmock::{Aig, Klut, MutableNetwork, Receiver, TruthTable};
+///
+/// // the majority of three inputs and its complement
+/// let mut klut = MutableNetwork::<Klut>::default();
+/// let inputs = [0, 1, 2].map(|input| klut.create_node(Klut::Input(input)));
+/// let majority = TruthTable::from_hex(3, "e8").unwrap();
+/// let maj = klut.create_node(Klut::lut(majority.clone(), inputs));
+/// let klut = klut.done(&[maj, maj.invert()]);
+/// assert_eq!(simulate_truth_tables(&klut), [majority.clone(), !majority]);
+///
+/// // the functions of the LUTs are decomposed when converting to other network types
+/// let aig: MutableNetwork<Aig> = convert_network(&klut);
+/// assert_eq!(simulate_truth_tables(&aig), simulate_truth_tables(&klut));
+/// ```
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Klut {
+    Input(u64),
+    False,
+    /// A look-up table computing the given function, where input `i` is variable `i` of the
+    /// function. Use [`Klut::lut`] to create it.
+    Lut(TruthTable, Box<[Signal]>),
+}
+
+impl Klut {
+    /// Creates a look-up table that computes `function` of the given inputs.
+    ///
+    /// # Panics
+    /// Panics if there are no inputs or if the number of inputs differs from the number of
+    /// variables of the function.
+    pub fn lut(function: TruthTable, inputs: impl Into<Box<[Signal]>>) -> Self {
+        let inputs = inputs.into();
+        assert!(!inputs.is_empty(), "look-up tables should have inputs");
+        assert_eq!(
+            function.num_vars() as usize,
+            inputs.len(),
+            "the function of a look-up table should have one variable per input"
+        );
+        Self::Lut(function, inputs)
+    }
+
+    /// Returns the function of this node if it is a look-up table.
+    pub fn function(&self) -> Option<&TruthTable> {
+        match self {
+            Self::Lut(function, _) => Some(function),
+            _ => None,
+        }
+    }
+}
+
+impl Node for Klut {
+    type Gates = KlutGateType;
+    type Language = KlutLanguage;
+    type ReceiverFFI<R> = KlutReceiverFFI<R>;
+
+    const NTK_TYPENAME: &'static str = "klut";
+    const NTK_MOCKTURTLE_TYPENAME: &'static str = "klut_network";
+    const NTK_MOCKTURTLE_INCLUDE: &'static str = "mockturtle/networks/klut.hpp";
+
+    fn map_input_signals(&self, map: impl FnMut(Signal) -> Signal) -> Self {
+        match self {
+            Self::Input(index) => Self::Input(*index),
+            Self::False => Self::False,
+            Self::Lut(function, inputs) => {
+                Self::Lut(function.clone(), inputs.iter().copied().map(map).collect())
+            }
+        }
+    }
+
+    fn inputs(&self) -> &[Signal] {
+        match self {
+            Self::Lut(_, inputs) => inputs,
+            _ => &[],
+        }
+    }
+
+    fn input_index(&self) -> Option<u64> {
+        match self {
+            Self::Input(index) => Some(*index),
+            _ => None,
+        }
+    }
+
+    fn input(index: u64) -> Self {
+        Self::Input(index)
+    }
+
+    fn constant_false() -> Self {
+        Self::False
+    }
+
+    /// Returns [`None`] as the function of a look-up table cannot be derived from its gate type.
+    /// Use [`from_function`](Node::from_function) or [`Klut::lut`] instead.
+    fn from_gate(_gate: KlutGateType, _inputs: &[Signal]) -> Option<Self> {
+        None
+    }
+
+    /// Creates a look-up table that computes the given function.
+    fn from_function(function: GateFunction, inputs: &[Signal]) -> Option<Self> {
+        if inputs.is_empty() {
+            return None;
+        }
+        let num_vars = inputs.len() as u32;
+        let vars = Vec::from_iter((0..num_vars).map(|var| TruthTable::nth_var(num_vars, var)));
+        Some(Self::lut(function.evaluate(&vars), inputs))
+    }
+
+    fn gate_type(&self) -> Option<KlutGateType> {
+        match self {
+            Self::Lut(..) => Some(KlutGateType::Lut),
+            _ => None,
+        }
+    }
+
+    fn evaluate<V: SimulationValue>(&self, inputs: &[V]) -> Option<V> {
+        Some(self.function()?.evaluate(inputs))
+    }
+}
+
+/// The *egg* language of k-LUT networks. Look-up tables are written as their function in
+/// hexadecimal notation with a `0x` prefix, followed by their inputs (e.g. `(0x8 ?a ?b)` for an
+/// AND of two inputs).
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum KlutLanguage {
+    Input(u64),
+    False,
+    Not(egg::Id),
+    Lut(TruthTable, Box<[egg::Id]>),
+}
+
+impl Language for KlutLanguage {
+    type Discriminant = std::mem::Discriminant<Self>;
+
+    fn discriminant(&self) -> Self::Discriminant {
+        std::mem::discriminant(self)
+    }
+
+    fn matches(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Input(a), Self::Input(b)) => a == b,
+            (Self::False, Self::False) => true,
+            (Self::Not(_), Self::Not(_)) => true,
+            (Self::Lut(a, a_inputs), Self::Lut(b, b_inputs)) => {
+                a == b && a_inputs.len() == b_inputs.len()
+            }
+            _ => false,
+        }
+    }
+
+    fn children(&self) -> &[egg::Id] {
+        match self {
+            Self::Input(_) | Self::False => &[],
+            Self::Not(id) => std::slice::from_ref(id),
+            Self::Lut(_, inputs) => inputs,
+        }
+    }
+
+    fn children_mut(&mut self) -> &mut [egg::Id] {
+        match self {
+            Self::Input(_) | Self::False => &mut [],
+            Self::Not(id) => std::slice::from_mut(id),
+            Self::Lut(_, inputs) => inputs,
+        }
+    }
+}
+
+impl Display for KlutLanguage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Input(index) => write!(f, "{index}"),
+            Self::False => f.write_str("f"),
+            Self::Not(_) => f.write_str("!"),
+            Self::Lut(function, _) => write!(f, "0x{function}"),
+        }
+    }
+}
+
+impl FromOp for KlutLanguage {
+    type Error = FromOpError;
+
+    fn from_op(op: &str, children: Vec<egg::Id>) -> Result<Self, Self::Error> {
+        match (op, children.as_slice()) {
+            ("f", []) => Ok(Self::False),
+            ("!", [child]) => Ok(Self::Not(*child)),
+            (op, []) if op.parse::<u64>().is_ok() => Ok(Self::Input(op.parse().unwrap())),
+            (op, [_, ..]) => match op
+                .strip_prefix("0x")
+                .and_then(|hex| TruthTable::from_hex(children.len() as u32, hex))
+            {
+                Some(function) => Ok(Self::Lut(function, children.into())),
+                None => Err(FromOpError::new(op, children)),
+            },
+            _ => Err(FromOpError::new(op, children)),
+        }
+    }
+}
+
+impl NetworkLanguage for KlutLanguage {
+    type Node = Klut;
+
+    fn from_node(node: Klut, signal_mapper: impl FnMut(Signal) -> egg::Id) -> Self {
+        match node {
+            Klut::Input(index) => Self::Input(index),
+            Klut::False => Self::False,
+            Klut::Lut(function, inputs) => Self::Lut(
+                function,
+                inputs.iter().copied().map(signal_mapper).collect(),
+            ),
+        }
+    }
+
+    fn to_node(&self, id_mapper: impl FnMut(egg::Id) -> Signal) -> Option<Klut> {
+        match self {
+            Self::Input(index) => Some(Klut::Input(*index)),
+            Self::False => Some(Klut::False),
+            Self::Not(_) => None,
+            Self::Lut(function, inputs) => Some(Klut::Lut(
+                function.clone(),
+                inputs.iter().copied().map(id_mapper).collect(),
+            )),
+        }
+    }
+
+    fn is_not(&self) -> bool {
+        matches!(self, Self::Not(_))
+    }
+    fn not(id: egg::Id) -> Self {
+        Self::Not(id)
+    }
+}
+
+/// The gate types of k-LUT networks.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum KlutGateType {
+    /// A look-up table. As look-up tables have a varying number of inputs and functions,
+    /// [`fanin`](GateType::fanin) returns `0` and [`function`](GateType::function) returns
+    /// [`None`] for them. Use [`Klut::function`] instead.
+    Lut,
+}
+
+impl GateType for KlutGateType {
+    type Node = Klut;
+    const VARIANTS: &'static [Self] = &[Self::Lut];
+
+    fn name(&self) -> &'static str {
+        "lut"
+    }
+
+    fn fanin(&self) -> u8 {
+        0
+    }
+
+    fn mockturtle_create(&self) -> &'static str {
+        "create_node"
+    }
+
+    fn mockturtle_is(&self) -> &'static str {
+        "is_function"
+    }
+
+    fn function(&self) -> Option<GateFunction> {
+        None
+    }
+}
+
+/// FFI for transferring k-LUT networks from and to *mockturtle*. Look-up tables are transferred
+/// with their inputs and the 64-bit words of their truth table.
+#[repr(C)]
+pub struct KlutReceiverFFI<R> {
+    data: *mut libc::c_void,
+    create_input: extern "C" fn(*mut libc::c_void, name: u64) -> Signal,
+    create_constant: extern "C" fn(*mut libc::c_void, value: bool) -> Signal,
+    create_lut: extern "C" fn(
+        *mut libc::c_void,
+        inputs: *const Signal,
+        inputs_size: usize,
+        function: *const u64,
+        function_size: usize,
+    ) -> Signal,
+    set_num_inputs: extern "C" fn(*mut libc::c_void, num_inputs: u64),
+    set_registers: extern "C" fn(*mut libc::c_void, inits: *const u8, num_registers: usize),
+    set_input_name:
+        extern "C" fn(*mut libc::c_void, input: u64, name: *const libc::c_char, name_size: usize),
+    set_output_name: extern "C" fn(
+        *mut libc::c_void,
+        output: usize,
+        name: *const libc::c_char,
+        name_size: usize,
+    ),
+    add_choice: extern "C" fn(*mut libc::c_void, node: Signal, choice: Signal),
+    done: extern "C" fn(*mut libc::c_void, outputs: *const Signal, outputs_size: usize) -> R,
+}
+
+impl<R> ReceiverFFI for KlutReceiverFFI<R> {
+    fn new<Recv>(receiver: Recv) -> Self
+    where
+        Recv: Receiver<Node = Klut, Result = R> + 'static,
+    {
+        let data = Box::into_raw(Box::new(receiver));
+        Self {
+            data: data as *mut libc::c_void,
+            create_input: Self::create_input::<Recv>,
+            create_constant: Self::create_constant::<Recv>,
+            create_lut: Self::create_lut::<Recv>,
+            set_num_inputs: Self::set_num_inputs::<Recv>,
+            set_registers: Self::set_registers::<Recv>,
+            set_input_name: Self::set_input_name::<Recv>,
+            set_output_name: Self::set_output_name::<Recv>,
+            add_choice: Self::add_choice::<Recv>,
+            done: Self::done::<Recv>,
+        }
+    }
+}
+
+impl<R> Receiver for KlutReceiverFFI<R> {
+    type Node = Klut;
+    type Result = R;
+
+    fn create_node(&mut self, node: Klut) -> Signal {
+        match node {
+            Klut::Input(index) => (self.create_input)(self.data, index),
+            Klut::False => (self.create_constant)(self.data, false),
+            Klut::Lut(function, inputs) => (self.create_lut)(
+                self.data,
+                inputs.as_ptr(),
+                inputs.len(),
+                function.words().as_ptr(),
+                function.words().len(),
+            ),
+        }
+    }
+
+    fn done(self, outputs: &[Signal]) -> R {
+        (self.done)(self.data, outputs.as_ptr(), outputs.len())
+    }
+
+    fn set_num_inputs(&mut self, num_inputs: u64) {
+        (self.set_num_inputs)(self.data, num_inputs)
+    }
+
+    fn set_registers(&mut self, registers: &[Register]) {
+        let inits = Vec::from_iter(registers.iter().map(Register::mockturtle_init));
+        (self.set_registers)(self.data, inits.as_ptr(), inits.len())
+    }
+
+    fn set_input_name(&mut self, input: u64, name: &str) {
+        (self.set_input_name)(self.data, input, name.as_ptr() as *const _, name.len())
+    }
+
+    fn set_output_name(&mut self, output: usize, name: &str) {
+        (self.set_output_name)(self.data, output, name.as_ptr() as *const _, name.len())
+    }
+
+    fn add_choice(&mut self, node: Signal, choice: Signal) {
+        (self.add_choice)(self.data, node, choice)
+    }
+}
+
+impl<R> KlutReceiverFFI<R> {
+    extern "C" fn create_input<Recv>(data: *mut libc::c_void, name: u64) -> Signal
+    where
+        Recv: Receiver<Node = Klut, Result = R> + 'static,
+    {
+        unsafe { &mut *(data as *mut Recv) }.create_node(Klut::Input(name))
+    }
+
+    extern "C" fn create_constant<Recv>(data: *mut libc::c_void, value: bool) -> Signal
+    where
+        Recv: Receiver<Node = Klut, Result = R> + 'static,
+    {
+        unsafe { &mut *(data as *mut Recv) }
+            .create_node(Klut::False)
+            .maybe_invert(value)
+    }
+
+    extern "C" fn create_lut<Recv>(
+        data: *mut libc::c_void,
+        inputs: *const Signal,
+        inputs_size: usize,
+        function: *const u64,
+        function_size: usize,
+    ) -> Signal
+    where
+        Recv: Receiver<Node = Klut, Result = R> + 'static,
+    {
+        let inputs = unsafe { ffi_slice(inputs, inputs_size) };
+        let function = unsafe { ffi_slice(function, function_size) };
+        let function = TruthTable::from_words(inputs_size as u32, function);
+        let receiver = unsafe { &mut *(data as *mut Recv) };
+        if inputs.is_empty() {
+            // look-up tables without inputs are constants
+            return receiver
+                .create_node(Klut::False)
+                .maybe_invert(function.is_const1());
+        }
+        receiver.create_node(Klut::lut(function, inputs))
+    }
+
+    extern "C" fn set_num_inputs<Recv>(data: *mut libc::c_void, num_inputs: u64)
+    where
+        Recv: Receiver<Node = Klut, Result = R> + 'static,
+    {
+        unsafe { &mut *(data as *mut Recv) }.set_num_inputs(num_inputs)
+    }
+
+    extern "C" fn set_registers<Recv>(
+        data: *mut libc::c_void,
+        inits: *const u8,
+        num_registers: usize,
+    ) where
+        Recv: Receiver<Node = Klut, Result = R> + 'static,
+    {
+        let inits = unsafe { ffi_slice(inits, num_registers) };
+        let registers = Vec::from_iter(
+            inits
+                .iter()
+                .map(|init| Register::from_mockturtle_init(*init)),
+        );
+        unsafe { &mut *(data as *mut Recv) }.set_registers(&registers)
+    }
+
+    extern "C" fn set_input_name<Recv>(
+        data: *mut libc::c_void,
+        input: u64,
+        name: *const libc::c_char,
+        name_size: usize,
+    ) where
+        Recv: Receiver<Node = Klut, Result = R> + 'static,
+    {
+        let name = unsafe { ffi_str(name, name_size) };
+        unsafe { &mut *(data as *mut Recv) }.set_input_name(input, &name)
+    }
+
+    extern "C" fn set_output_name<Recv>(
+        data: *mut libc::c_void,
+        output: usize,
+        name: *const libc::c_char,
+        name_size: usize,
+    ) where
+        Recv: Receiver<Node = Klut, Result = R> + 'static,
+    {
+        let name = unsafe { ffi_str(name, name_size) };
+        unsafe { &mut *(data as *mut Recv) }.set_output_name(output, &name)
+    }
+
+    extern "C" fn add_choice<Recv>(data: *mut libc::c_void, node: Signal, choice: Signal)
+    where
+        Recv: Receiver<Node = Klut, Result = R> + 'static,
+    {
+        unsafe { &mut *(data as *mut Recv) }.add_choice(node, choice)
+    }
+
+    extern "C" fn done<Recv>(
+        data: *mut libc::c_void,
+        outputs: *const Signal,
+        outputs_size: usize,
+    ) -> R
+    where
+        Recv: Receiver<Node = Klut, Result = R> + 'static,
+    {
+        let outputs = unsafe { ffi_slice(outputs, outputs_size) };
+        unsafe { Box::from_raw(data as *mut Recv) }.done(outputs)
+    }
+}
+
+/// # Safety
+/// `data` has to point to `size` valid elements if `size` is not zero.
+unsafe fn ffi_slice<'a, T>(data: *const T, size: usize) -> &'a [T] {
+    if size == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(data, size)
+    }
+}
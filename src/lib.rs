@@ -1,21 +1,55 @@
+mod builder;
+mod cell_mapping;
+mod convert;
+mod cuts;
 mod gen;
+mod generators;
+mod io;
+mod isolation;
+mod klut;
+mod liberty;
+mod library;
+mod lut_mapping;
 mod macros;
+mod mixed;
 mod network;
 mod rewrite;
+#[cfg(feature = "serde")]
+mod serde_impls;
+mod timing;
 mod transfer;
+mod truth_table;
 mod egg_impls;
 
 pub use egg;
 pub use libc;
 pub use paste;
 pub use seq_macro;
+#[cfg(feature = "serde")]
+pub use serde;
 
 pub use network::*;
 pub use rewrite::*;
+pub use timing::*;
 pub use transfer::*;
+pub use truth_table::*;
+pub use cuts::*;
 pub use egg_impls::*;
 
+pub use builder::*;
+pub use cell_mapping::*;
+pub use convert::*;
 pub use gen::*;
+pub use generators::*;
+pub use io::*;
+pub use isolation::*;
+pub use klut::*;
+pub use liberty::*;
+pub use library::*;
+pub use lut_mapping::*;
+pub use mixed::*;
+#[cfg(feature = "serde")]
+pub use serde_impls::*;
 
 define_network! {
     pub enum "mig" = Mig {
@@ -71,3 +105,17 @@ define_network! {
         }
     }
 }
+
+define_network! {
+    /// A network with the gates of all other built-in networks except [`Aoig`]'s n-ary gates. It is
+    /// meant for e-graphs that mix gate types during rewriting, see [`mixed_rules`]. There is no
+    /// corresponding *mockturtle* network, so it is not part of the generated C++ header.
+    pub enum "mixed" = Mixed {
+        gates {
+            "and" = And(2),
+            "or" = Or(2),
+            "xor" = Xor(2),
+            "maj" = Maj(3)
+        }
+    }
+}
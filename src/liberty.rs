@@ -0,0 +1,472 @@
+use std::marker::PhantomData;
+
+use egg::CostFunction;
+use rustc_hash::FxHashMap;
+
+use crate::library::parse_expression;
+use crate::{
+    Cell, CellExpression, CellPin, GateLibrary, GateType, NetworkLanguage, Node, PinPhase, Signal,
+    TruthTable,
+};
+
+/// The subset of a Liberty library that is relevant for mapping and timing, e.g. read with
+/// [`read_liberty`](crate::read_liberty).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LibertyLibrary {
+    pub name: String,
+    /// The unit of all times in the library, e.g. `1ns`.
+    pub time_unit: Option<String>,
+    /// The unit of all capacitances in the library, e.g. `(1, pf)`.
+    pub capacitive_load_unit: Option<(f64, String)>,
+    pub cells: Vec<LibertyCell>,
+}
+
+impl LibertyLibrary {
+    pub fn cell(&self, name: &str) -> Option<&LibertyCell> {
+        self.cells.iter().find(|cell| cell.name == name)
+    }
+
+    /// Converts the combinational single-output cells of this library into a [`GateLibrary`], e.g.
+    /// for [`map_cells`](crate::map_cells).
+    ///
+    /// The non-linear delay tables are linearised for the given input transition time: the block
+    /// delay of a pin is its delay without load and the fanout delay is the slope of the delay up
+    /// to the given load.
+    pub fn to_gate_library(&self, input_transition: f64, load: f64) -> GateLibrary {
+        let cells = self.cells.iter().filter_map(|cell| {
+            let (output, expression) = cell.output_expression()?;
+            let pins = Vec::from_iter(cell.inputs().map(|pin| {
+                let arc = output.arc(&pin.name);
+                let table = |table: Option<&LookupTable>| {
+                    let Some(table) = table else {
+                        return (0.0, 0.0);
+                    };
+                    let block = table.evaluate(input_transition, 0.0);
+                    let fanout = if load > 0.0 {
+                        (table.evaluate(input_transition, load) - block) / load
+                    } else {
+                        0.0
+                    };
+                    (block, fanout)
+                };
+                let (rise_block_delay, rise_fanout_delay) =
+                    table(arc.and_then(|arc| arc.cell_rise.as_ref()));
+                let (fall_block_delay, fall_fanout_delay) =
+                    table(arc.and_then(|arc| arc.cell_fall.as_ref()));
+                CellPin {
+                    name: pin.name.clone(),
+                    phase: match arc.map(|arc| arc.timing_sense) {
+                        Some(TimingSense::PositiveUnate) => PinPhase::NonInverting,
+                        Some(TimingSense::NegativeUnate) => PinPhase::Inverting,
+                        _ => PinPhase::Unknown,
+                    },
+                    input_load: pin.capacitance,
+                    max_load: output.max_capacitance.unwrap_or(f64::INFINITY),
+                    rise_block_delay,
+                    rise_fanout_delay,
+                    fall_block_delay,
+                    fall_fanout_delay,
+                }
+            }));
+            Some(Cell::new(
+                cell.name.clone(),
+                cell.area,
+                output.name.clone(),
+                expression,
+                pins,
+            ))
+        });
+        GateLibrary::new(cells.collect())
+    }
+}
+
+/// A cell of a [`LibertyLibrary`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LibertyCell {
+    pub name: String,
+    pub area: f64,
+    pub pins: Vec<LibertyPin>,
+    /// Whether the cell contains a flip-flop or latch.
+    pub is_sequential: bool,
+}
+
+impl LibertyCell {
+    pub fn pin(&self, name: &str) -> Option<&LibertyPin> {
+        self.pins.iter().find(|pin| pin.name == name)
+    }
+
+    /// Returns the input pins in the order of their declaration.
+    pub fn inputs(&self) -> impl Iterator<Item = &LibertyPin> {
+        self.pins
+            .iter()
+            .filter(|pin| pin.direction == PinDirection::Input)
+    }
+
+    pub fn outputs(&self) -> impl Iterator<Item = &LibertyPin> {
+        self.pins
+            .iter()
+            .filter(|pin| pin.direction == PinDirection::Output)
+    }
+
+    /// Returns the output pin if this is a combinational cell with exactly one output.
+    pub fn single_output(&self) -> Option<&LibertyPin> {
+        let mut outputs = self.outputs();
+        match (outputs.next(), outputs.next()) {
+            (Some(output), None) if !self.is_sequential => Some(output),
+            _ => None,
+        }
+    }
+
+    /// Returns the function of the output pin in terms of the [`inputs`](Self::inputs) or
+    /// [`None`] if the cell has no single output with a known function.
+    pub fn function(&self) -> Option<TruthTable> {
+        let (_, expression) = self.output_expression()?;
+        let num_vars = self.inputs().count() as u32;
+        let vars = Vec::from_iter((0..num_vars).map(|var| TruthTable::nth_var(num_vars, var)));
+        Some(expression.evaluate(&TruthTable::new(num_vars), &vars))
+    }
+
+    /// Returns the single output pin and its function, where pin `i` of the expression is the
+    /// `i`-th input.
+    fn output_expression(&self) -> Option<(&LibertyPin, CellExpression)> {
+        let output = self.single_output()?;
+        let mut names = Vec::from_iter(self.inputs().map(|pin| pin.name.clone()));
+        let num_inputs = names.len();
+        let expression = parse_expression(output.function.as_ref()?, &mut names).ok()?;
+        // functions that refer to internal pins are not supported
+        (names.len() == num_inputs).then_some((output, expression))
+    }
+
+    /// Returns the delay from the input to the output pin for the given transition time at the
+    /// input and capacitive load at the output, i.e. the maximum of the rise and fall delay.
+    pub fn delay(
+        &self,
+        input: &str,
+        output: &str,
+        input_transition: f64,
+        load: f64,
+    ) -> Option<f64> {
+        Some(self.pin(output)?.arc(input)?.delay(input_transition, load))
+    }
+}
+
+/// The direction of a [`LibertyPin`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum PinDirection {
+    #[default]
+    Input,
+    Output,
+    Inout,
+    Internal,
+}
+
+/// A pin of a [`LibertyCell`]. Output pins contain the timing arcs from the input pins.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LibertyPin {
+    pub name: String,
+    pub direction: PinDirection,
+    pub capacitance: f64,
+    pub max_capacitance: Option<f64>,
+    /// The function of an output pin in Liberty syntax.
+    pub function: Option<String>,
+    pub timing: Vec<TimingArc>,
+}
+
+impl LibertyPin {
+    /// Returns the combinational timing arc from the given related pin to this pin.
+    pub fn arc(&self, related_pin: &str) -> Option<&TimingArc> {
+        self.timing.iter().find(|arc| {
+            arc.is_combinational() && arc.related_pins.iter().any(|pin| pin == related_pin)
+        })
+    }
+}
+
+/// The unateness of a [`TimingArc`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum TimingSense {
+    PositiveUnate,
+    NegativeUnate,
+    #[default]
+    NonUnate,
+}
+
+/// A `timing` group of a [`LibertyPin`], which describes the delay from the related pins to the
+/// pin with non-linear delay model tables.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimingArc {
+    pub related_pins: Vec<String>,
+    pub timing_sense: TimingSense,
+    /// The `timing_type` attribute, e.g. `combinational` or `setup_rising`.
+    pub timing_type: Option<String>,
+    pub cell_rise: Option<LookupTable>,
+    pub cell_fall: Option<LookupTable>,
+    pub rise_transition: Option<LookupTable>,
+    pub fall_transition: Option<LookupTable>,
+}
+
+impl TimingArc {
+    pub fn is_combinational(&self) -> bool {
+        matches!(self.timing_type.as_deref(), None | Some("combinational"))
+    }
+
+    /// Returns the maximum of the rise and fall delay.
+    pub fn delay(&self, input_transition: f64, load: f64) -> f64 {
+        Self::max(&self.cell_rise, &self.cell_fall, input_transition, load)
+    }
+
+    /// Returns the maximum of the rise and fall transition time at the output.
+    pub fn transition(&self, input_transition: f64, load: f64) -> f64 {
+        Self::max(
+            &self.rise_transition,
+            &self.fall_transition,
+            input_transition,
+            load,
+        )
+    }
+
+    fn max(a: &Option<LookupTable>, b: &Option<LookupTable>, transition: f64, load: f64) -> f64 {
+        [a, b]
+            .into_iter()
+            .flatten()
+            .map(|table| table.evaluate(transition, load))
+            .fold(0.0, f64::max)
+    }
+}
+
+/// A variable that indexes a [`LookupTable`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TableVariable {
+    InputNetTransition,
+    TotalOutputNetCapacitance,
+    Other(String),
+}
+
+impl TableVariable {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "input_net_transition" | "input_transition_time" => Self::InputNetTransition,
+            "total_output_net_capacitance" => Self::TotalOutputNetCapacitance,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+/// A table of the non-linear delay model with up to two dimensions.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LookupTable {
+    pub variable_1: Option<TableVariable>,
+    pub variable_2: Option<TableVariable>,
+    pub index_1: Vec<f64>,
+    pub index_2: Vec<f64>,
+    /// The values in row-major order, i.e. `values[i * index_2.len() + j]` belongs to
+    /// `index_1[i]` and `index_2[j]`.
+    pub values: Vec<f64>,
+}
+
+impl LookupTable {
+    /// Returns the value for the given indices, which is interpolated (or extrapolated) linearly
+    /// from the neighbouring entries. Dimensions without an index are ignored.
+    pub fn lookup(&self, x1: f64, x2: f64) -> f64 {
+        let columns = self.index_2.len().max(1);
+        let value = |i: usize, j: usize| self.values.get(i * columns + j).copied().unwrap_or(0.0);
+        let (i, t1) = Self::segment(&self.index_1, x1);
+        let (j, t2) = Self::segment(&self.index_2, x2);
+        let i1 = (i + 1).min(self.index_1.len().saturating_sub(1));
+        let j1 = (j + 1).min(self.index_2.len().saturating_sub(1));
+        let low = value(i, j) + (value(i, j1) - value(i, j)) * t2;
+        let high = value(i1, j) + (value(i1, j1) - value(i1, j)) * t2;
+        low + (high - low) * t1
+    }
+
+    /// Returns the value for the given input transition and output load according to the
+    /// variables of the table. Indices with other variables are assumed to be zero.
+    pub fn evaluate(&self, input_transition: f64, load: f64) -> f64 {
+        let value = |variable: &Option<TableVariable>| match variable {
+            Some(TableVariable::InputNetTransition) => input_transition,
+            Some(TableVariable::TotalOutputNetCapacitance) => load,
+            _ => 0.0,
+        };
+        self.lookup(value(&self.variable_1), value(&self.variable_2))
+    }
+
+    /// Returns the index of the segment of the given axis that is used for interpolating at `x`
+    /// and the relative position of `x` in it.
+    fn segment(index: &[f64], x: f64) -> (usize, f64) {
+        if index.len() < 2 {
+            return (0, 0.0);
+        }
+        let segment = index[1..index.len() - 1]
+            .iter()
+            .take_while(|value| **value <= x)
+            .count();
+        let (low, high) = (index[segment], index[segment + 1]);
+        if high == low {
+            (segment, 0.0)
+        } else {
+            (segment, (x - low) / (high - low))
+        }
+    }
+}
+
+/// The area and delay of a gate of a network when implemented by a library cell.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct GateCost {
+    pub area: f64,
+    pub delay: f64,
+}
+
+/// Assigns the area and delay of library cells to the gates of a network type, e.g. to be used
+/// by the cost functions for e-graph extraction ([`area`](Self::area) and [`delay`](Self::delay))
+/// or for timing analysis.
+///
+/// Each gate type and the inverter are implemented by the cell with the smallest area whose
+/// function matches the function of the gate. The delay of a cell is the largest delay of its
+/// input pins for the input transition time and load the model is created with.
+#[derive(Debug, Clone)]
+pub struct LibertyCostModel<G> {
+    gates: FxHashMap<G, GateCost>,
+    inverter: Option<GateCost>,
+}
+
+impl<G: GateType> LibertyCostModel<G> {
+    pub fn new(library: &LibertyLibrary, input_transition: f64, load: f64) -> Self {
+        let cells = Vec::from_iter(library.cells.iter().filter_map(|cell| {
+            let function = cell.function()?;
+            let output = cell.single_output()?;
+            let delay = cell
+                .inputs()
+                .filter_map(|pin| output.arc(&pin.name))
+                .map(|arc| arc.delay(input_transition, load))
+                .fold(0.0, f64::max);
+            Some((
+                function,
+                GateCost {
+                    area: cell.area,
+                    delay,
+                },
+            ))
+        }));
+        let cheapest = |function: &TruthTable| {
+            cells
+                .iter()
+                .filter(|(cell_function, _)| cell_function == function)
+                .map(|(_, cost)| *cost)
+                .min_by(|a, b| (a.area, a.delay).partial_cmp(&(b.area, b.delay)).unwrap())
+        };
+        let mut gates = FxHashMap::default();
+        for gate in G::VARIANTS {
+            let Some(function) = gate.function() else {
+                continue;
+            };
+            let num_vars = gate.fanin() as u32;
+            let vars = Vec::from_iter((0..num_vars).map(|var| TruthTable::nth_var(num_vars, var)));
+            if let Some(cost) = cheapest(&function.evaluate(&vars)) {
+                gates.insert(*gate, cost);
+            }
+        }
+        Self {
+            gates,
+            inverter: cheapest(&!TruthTable::nth_var(1, 0)),
+        }
+    }
+
+    /// Returns the cost of the given gate type or [`None`] if no cell implements it.
+    pub fn gate(&self, gate: G) -> Option<GateCost> {
+        self.gates.get(&gate).copied()
+    }
+
+    /// Returns the cost of an inverter or [`None`] if the library has none.
+    pub fn inverter(&self) -> Option<GateCost> {
+        self.inverter
+    }
+
+    /// Returns the cost of the given e-node, where inputs and constants are free and e-nodes that
+    /// no cell implements have an infinite cost.
+    pub fn node_cost<L>(&self, enode: &L) -> GateCost
+    where
+        L: NetworkLanguage<Node: Node<Gates = G>>,
+    {
+        const INFINITE: GateCost = GateCost {
+            area: f64::INFINITY,
+            delay: f64::INFINITY,
+        };
+        if enode.is_not() {
+            return self.inverter.unwrap_or(INFINITE);
+        }
+        let node = enode
+            .to_node(|_| Signal::new(crate::Id::from(0), false))
+            .expect("only nots have no corresponding node");
+        match node.gate_type() {
+            None => GateCost::default(),
+            Some(gate) => self.gate(gate).unwrap_or(INFINITE),
+        }
+    }
+
+    /// Returns a cost function for e-graph extraction that minimises the total area, where the
+    /// area of shared logic is counted once per use.
+    pub fn area<L>(&self) -> LibertyAreaCost<'_, G, L> {
+        LibertyAreaCost {
+            model: self,
+            _language: PhantomData,
+        }
+    }
+
+    /// Returns a cost function for e-graph extraction that minimises the delay of the longest
+    /// path.
+    pub fn delay<L>(&self) -> LibertyDelayCost<'_, G, L> {
+        LibertyDelayCost {
+            model: self,
+            _language: PhantomData,
+        }
+    }
+}
+
+/// The area cost function of a [`LibertyCostModel`].
+#[derive(Debug)]
+pub struct LibertyAreaCost<'a, G, L> {
+    model: &'a LibertyCostModel<G>,
+    _language: PhantomData<L>,
+}
+
+impl<G, L> CostFunction<L> for LibertyAreaCost<'_, G, L>
+where
+    G: GateType,
+    L: NetworkLanguage<Node: Node<Gates = G>>,
+{
+    type Cost = f64;
+
+    fn cost<C>(&mut self, enode: &L, mut costs: C) -> f64
+    where
+        C: FnMut(egg::Id) -> f64,
+    {
+        let children: f64 = enode.children().iter().map(|child| costs(*child)).sum();
+        self.model.node_cost(enode).area + children
+    }
+}
+
+/// The delay cost function of a [`LibertyCostModel`].
+#[derive(Debug)]
+pub struct LibertyDelayCost<'a, G, L> {
+    model: &'a LibertyCostModel<G>,
+    _language: PhantomData<L>,
+}
+
+impl<G, L> CostFunction<L> for LibertyDelayCost<'_, G, L>
+where
+    G: GateType,
+    L: NetworkLanguage<Node: Node<Gates = G>>,
+{
+    type Cost = f64;
+
+    fn cost<C>(&mut self, enode: &L, mut costs: C) -> f64
+    where
+        C: FnMut(egg::Id) -> f64,
+    {
+        let children = enode
+            .children()
+            .iter()
+            .map(|child| costs(*child))
+            .fold(0.0, f64::max);
+        self.model.node_cost(enode).delay + children
+    }
+}
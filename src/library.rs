@@ -0,0 +1,426 @@
+use std::fmt::{Display, Formatter};
+
+use egg::{FromOp, Pattern};
+
+use crate::{GateFunction, GateType, NetworkLanguage, Node, TruthTable};
+
+/// A library of standard cells, e.g. read from a *genlib* file with
+/// [`read_genlib`](crate::read_genlib).
+#[derive(Debug, Clone, Default)]
+pub struct GateLibrary {
+    cells: Vec<Cell>,
+}
+
+impl GateLibrary {
+    pub fn new(cells: Vec<Cell>) -> Self {
+        Self { cells }
+    }
+
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    /// Returns the index of the cell with the given name.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.cells.iter().position(|cell| cell.name == name)
+    }
+}
+
+/// A combinational cell of a [`GateLibrary`] with a single output.
+#[derive(Debug, Clone)]
+pub struct Cell {
+    name: String,
+    area: f64,
+    output: String,
+    expression: CellExpression,
+    pins: Vec<CellPin>,
+    function: TruthTable,
+}
+
+impl Cell {
+    /// Creates a cell whose output pin computes the given expression. The variables of the
+    /// expression refer to the given input pins.
+    ///
+    /// # Panics
+    /// Panics if the expression refers to a pin that does not exist.
+    pub fn new(
+        name: impl Into<String>,
+        area: f64,
+        output: impl Into<String>,
+        expression: CellExpression,
+        pins: Vec<CellPin>,
+    ) -> Self {
+        let num_vars = pins.len() as u32;
+        let vars = Vec::from_iter((0..num_vars).map(|var| TruthTable::nth_var(num_vars, var)));
+        let function = expression.evaluate(&TruthTable::new(num_vars), &vars);
+        Self {
+            name: name.into(),
+            area,
+            output: output.into(),
+            expression,
+            pins,
+            function,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+
+    /// Returns the name of the output pin.
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    pub fn expression(&self) -> &CellExpression {
+        &self.expression
+    }
+
+    /// Returns the input pins. Pin `i` is variable `i` of the [`expression`](Self::expression) and
+    /// of the [`function`](Self::function).
+    pub fn pins(&self) -> &[CellPin] {
+        &self.pins
+    }
+
+    pub fn num_inputs(&self) -> usize {
+        self.pins.len()
+    }
+
+    /// Returns the function of the output in terms of the input pins.
+    pub fn function(&self) -> &TruthTable {
+        &self.function
+    }
+
+    /// Returns the patterns that match the structures implementing this cell in networks of the
+    /// given language. Variable `?x{i}` of the patterns corresponds to pin `i`.
+    ///
+    /// The first pattern is derived from the structure of the [`expression`](Self::expression)
+    /// with AND, OR and XOR operators implemented by the gates of the language. Additionally, if a
+    /// gate of the language computes the function of the cell (or its complement) with the pins as
+    /// inputs, a pattern consisting of only this gate is returned. Cells whose function cannot be
+    /// expressed by the gates of the language have no patterns.
+    pub fn patterns<L>(&self) -> Vec<Pattern<L>>
+    where
+        L: NetworkLanguage + FromOp,
+    {
+        let gates = <L::Node as Node>::Gates::VARIANTS;
+        let mut patterns = Vec::new();
+        match PatternTree::from_expression(&self.expression, gates) {
+            // buffers would match every e-class
+            None | Some(PatternTree::Var(_)) => {}
+            Some(tree) => patterns.push(tree.to_string()),
+        }
+        let num_inputs = self.num_inputs() as u32;
+        for gate in gates {
+            let Some(function) = gate.function() else {
+                continue;
+            };
+            if gate.fanin() as u32 != num_inputs || num_inputs == 0 {
+                continue;
+            }
+            let vars =
+                Vec::from_iter((0..num_inputs).map(|var| TruthTable::nth_var(num_inputs, var)));
+            let gate_function = function.evaluate(&vars);
+            let inputs = Vec::from_iter((0..num_inputs).map(|var| format!("?x{var}")));
+            let pattern = format!("({} {})", gate.name(), inputs.join(" "));
+            let pattern = if gate_function == self.function {
+                pattern
+            } else if !gate_function == self.function {
+                format!("(! {pattern})")
+            } else {
+                continue;
+            };
+            if !patterns.contains(&pattern) {
+                patterns.push(pattern);
+            }
+        }
+        patterns
+            .iter()
+            .map(|pattern| pattern.parse().expect("generated patterns should be valid"))
+            .collect()
+    }
+}
+
+/// An input pin of a [`Cell`] with its timing information from the *genlib* format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellPin {
+    pub name: String,
+    pub phase: PinPhase,
+    pub input_load: f64,
+    pub max_load: f64,
+    pub rise_block_delay: f64,
+    pub rise_fanout_delay: f64,
+    pub fall_block_delay: f64,
+    pub fall_fanout_delay: f64,
+}
+
+impl CellPin {
+    /// Returns the delay from this pin to the output of the cell if it drives the given load.
+    pub fn delay(&self, load: f64) -> f64 {
+        f64::max(
+            self.rise_block_delay + self.rise_fanout_delay * load,
+            self.fall_block_delay + self.fall_fanout_delay * load,
+        )
+    }
+}
+
+/// The logical relation between an input pin and the output of a cell.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PinPhase {
+    Inverting,
+    NonInverting,
+    Unknown,
+}
+
+/// A Boolean expression describing the output of a [`Cell`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CellExpression {
+    Const(bool),
+    /// The input pin with the given index.
+    Pin(usize),
+    Not(Box<CellExpression>),
+    And(Box<CellExpression>, Box<CellExpression>),
+    Or(Box<CellExpression>, Box<CellExpression>),
+    Xor(Box<CellExpression>, Box<CellExpression>),
+}
+
+impl CellExpression {
+    /// Evaluates this expression where `pins` contains the values of the input pins and `zero`
+    /// is the value of a constant false.
+    pub fn evaluate<V: crate::SimulationValue>(&self, zero: &V, pins: &[V]) -> V {
+        match self {
+            Self::Const(value) => {
+                if *value {
+                    !zero.clone()
+                } else {
+                    zero.clone()
+                }
+            }
+            Self::Pin(pin) => pins[*pin].clone(),
+            Self::Not(e) => !e.evaluate(zero, pins),
+            Self::And(a, b) => a.evaluate(zero, pins) & b.evaluate(zero, pins),
+            Self::Or(a, b) => a.evaluate(zero, pins) | b.evaluate(zero, pins),
+            Self::Xor(a, b) => a.evaluate(zero, pins) ^ b.evaluate(zero, pins),
+        }
+    }
+}
+
+/// A pattern in the syntax of *egg* before it is parsed for a specific language.
+enum PatternTree {
+    Var(usize),
+    False,
+    Not(Box<PatternTree>),
+    Gate(&'static str, Vec<PatternTree>),
+}
+
+impl PatternTree {
+    fn from_expression<G: GateType>(expression: &CellExpression, gates: &[G]) -> Option<Self> {
+        let find = |function: GateFunction, fanin: u8| {
+            gates
+                .iter()
+                .find(|gate| gate.function() == Some(function) && gate.fanin() == fanin)
+                .map(|gate| gate.name())
+        };
+        let binary = |a: &CellExpression, b: &CellExpression| {
+            Some((
+                Self::from_expression(a, gates)?,
+                Self::from_expression(b, gates)?,
+            ))
+        };
+        Some(match expression {
+            CellExpression::Const(false) => Self::False,
+            CellExpression::Const(true) => Self::False.not(),
+            CellExpression::Pin(pin) => Self::Var(*pin),
+            CellExpression::Not(e) => Self::from_expression(e, gates)?.not(),
+            CellExpression::And(a, b) => {
+                let (a, b) = binary(a, b)?;
+                if let Some(and) = find(GateFunction::And, 2) {
+                    Self::Gate(and, vec![a, b])
+                } else if let Some(or) = find(GateFunction::Or, 2) {
+                    Self::Gate(or, vec![a.not(), b.not()]).not()
+                } else {
+                    Self::Gate(find(GateFunction::Maj, 3)?, vec![a, b, Self::False])
+                }
+            }
+            CellExpression::Or(a, b) => {
+                let (a, b) = binary(a, b)?;
+                if let Some(or) = find(GateFunction::Or, 2) {
+                    Self::Gate(or, vec![a, b])
+                } else if let Some(and) = find(GateFunction::And, 2) {
+                    Self::Gate(and, vec![a.not(), b.not()]).not()
+                } else {
+                    Self::Gate(find(GateFunction::Maj, 3)?, vec![a, b, Self::False.not()])
+                }
+            }
+            CellExpression::Xor(a, b) => {
+                if let Some(xor) = find(GateFunction::Xor, 2) {
+                    let (a, b) = binary(a, b)?;
+                    Self::Gate(xor, vec![a, b])
+                } else {
+                    let not = |e: &CellExpression| CellExpression::Not(Box::new(e.clone()));
+                    let and = |a, b| CellExpression::And(Box::new(a), Box::new(b));
+                    let expanded = CellExpression::Or(
+                        Box::new(and((**a).clone(), not(b))),
+                        Box::new(and(not(a), (**b).clone())),
+                    );
+                    Self::from_expression(&expanded, gates)?
+                }
+            }
+        })
+    }
+
+    /// Complements this pattern, removing double negations.
+    fn not(self) -> Self {
+        match self {
+            Self::Not(inner) => *inner,
+            other => Self::Not(Box::new(other)),
+        }
+    }
+}
+
+impl Display for PatternTree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Var(var) => write!(f, "?x{var}"),
+            Self::False => f.write_str("f"),
+            Self::Not(inner) => write!(f, "(! {inner})"),
+            Self::Gate(name, inputs) => {
+                write!(f, "({name}")?;
+                for input in inputs {
+                    write!(f, " {input}")?;
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+/// Parses a Boolean expression as used in *genlib* and Liberty files. The names of the pins are
+/// looked up in `pins` and new names are appended to it, i.e. variables that do not appear in
+/// `pins` already are numbered in order of their first appearance.
+///
+/// Supports `!` and a trailing `'` for negation, `*`, `&` or juxtaposition for conjunction, `+`
+/// and `|` for disjunction, `^` for exclusive or as well as the constants `0`, `1`, `CONST0` and
+/// `CONST1`.
+pub(crate) fn parse_expression(
+    source: &str,
+    pins: &mut Vec<String>,
+) -> Result<CellExpression, String> {
+    let mut parser = ExpressionParser {
+        input: source.as_bytes(),
+        position: 0,
+        pins,
+    };
+    let expression = parser.or()?;
+    match parser.peek() {
+        None => Ok(expression),
+        Some(c) => Err(format!("unexpected `{}` in expression", c as char)),
+    }
+}
+
+const OPERATORS: &[u8] = b"!()+|*&^'";
+
+struct ExpressionParser<'s> {
+    input: &'s [u8],
+    position: usize,
+    pins: &'s mut Vec<String>,
+}
+
+impl ExpressionParser<'_> {
+    fn peek(&mut self) -> Option<u8> {
+        while self.input.get(self.position)?.is_ascii_whitespace() {
+            self.position += 1;
+        }
+        self.input.get(self.position).copied()
+    }
+
+    fn or(&mut self) -> Result<CellExpression, String> {
+        let mut expression = self.xor()?;
+        while let Some(b'+' | b'|') = self.peek() {
+            self.position += 1;
+            expression = CellExpression::Or(Box::new(expression), Box::new(self.xor()?));
+        }
+        Ok(expression)
+    }
+
+    fn xor(&mut self) -> Result<CellExpression, String> {
+        let mut expression = self.and()?;
+        while let Some(b'^') = self.peek() {
+            self.position += 1;
+            expression = CellExpression::Xor(Box::new(expression), Box::new(self.and()?));
+        }
+        Ok(expression)
+    }
+
+    fn and(&mut self) -> Result<CellExpression, String> {
+        let mut expression = self.unary()?;
+        loop {
+            match self.peek() {
+                Some(b'*' | b'&') => self.position += 1,
+                // juxtaposition
+                Some(b'!' | b'(') => {}
+                Some(c) if !OPERATORS.contains(&c) => {}
+                _ => break,
+            }
+            expression = CellExpression::And(Box::new(expression), Box::new(self.unary()?));
+        }
+        Ok(expression)
+    }
+
+    fn unary(&mut self) -> Result<CellExpression, String> {
+        let mut expression = match self.peek() {
+            Some(b'!') => {
+                self.position += 1;
+                return Ok(CellExpression::Not(Box::new(self.unary()?)));
+            }
+            Some(b'(') => {
+                self.position += 1;
+                let expression = self.or()?;
+                if self.peek() != Some(b')') {
+                    return Err("missing `)` in expression".to_string());
+                }
+                self.position += 1;
+                expression
+            }
+            Some(_) => self.identifier()?,
+            None => return Err("unexpected end of expression".to_string()),
+        };
+        while let Some(b'\'') = self.peek() {
+            self.position += 1;
+            expression = CellExpression::Not(Box::new(expression));
+        }
+        Ok(expression)
+    }
+
+    fn identifier(&mut self) -> Result<CellExpression, String> {
+        let start = self.position;
+        while let Some(c) = self.input.get(self.position) {
+            if c.is_ascii_whitespace() || OPERATORS.contains(c) {
+                break;
+            }
+            self.position += 1;
+        }
+        if start == self.position {
+            return Err("expected an input name in expression".to_string());
+        }
+        let name = String::from_utf8_lossy(&self.input[start..self.position]);
+        Ok(match name.as_ref() {
+            "0" | "CONST0" => CellExpression::Const(false),
+            "1" | "CONST1" => CellExpression::Const(true),
+            name => {
+                let pin = match self.pins.iter().position(|pin| pin == name) {
+                    Some(pin) => pin,
+                    None => {
+                        self.pins.push(name.to_string());
+                        self.pins.len() - 1
+                    }
+                };
+                CellExpression::Pin(pin)
+            }
+        })
+    }
+}
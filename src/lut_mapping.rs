@@ -0,0 +1,686 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use egg::{Analysis, EGraph};
+
+use crate::{
+    ChoiceNetwork, Cut, Id, Interface, InterfaceNames, Klut, Network, NetworkLanguage, Node,
+    Register, Signal, TruthTable,
+};
+
+/// Parameters for the LUT mapping functions ([`map_luts`], [`map_luts_egraph`] and
+/// [`map_luts_with_choices`]).
+#[derive(Debug, Clone)]
+pub struct LutMappingParams {
+    /// The maximum number of inputs of a LUT.
+    pub lut_size: usize,
+    /// The maximum number of cuts that are kept for each node (not including the trivial cut). At
+    /// least one cut is always kept.
+    pub cut_limit: usize,
+    /// The number of area recovery rounds, in which the cuts are selected by their exact area.
+    pub area_rounds: usize,
+}
+
+impl Default for LutMappingParams {
+    fn default() -> Self {
+        Self {
+            lut_size: 6,
+            cut_limit: 8,
+            area_rounds: 2,
+        }
+    }
+}
+
+/// Returned by the LUT mapping functions if a gate has more distinct fanins than
+/// [`lut_size`](LutMappingParams::lut_size), so it cannot be covered by any LUT. Gates of this
+/// width have to be decomposed first, e.g. with [`Receiver::convert`](crate::Receiver::convert).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LutSizeError {
+    /// The id of the gate in the mapped [`ChoiceNetwork`].
+    pub id: Id,
+    pub fanin: usize,
+    pub lut_size: usize,
+}
+
+impl Display for LutSizeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "gate {:?} has {} fanins, which do not fit into a LUT of size {}",
+            self.id, self.fanin, self.lut_size
+        )
+    }
+}
+
+impl Error for LutSizeError {}
+
+/// A k-LUT network that is the result of a LUT mapping.
+#[derive(Debug, Clone)]
+pub struct LutMapping {
+    nodes: Vec<Klut>,
+    outputs: Vec<Signal>,
+    interface: Interface,
+    num_luts: usize,
+    depth: u32,
+}
+
+impl LutMapping {
+    /// Returns the number of LUTs in the mapped network.
+    pub fn num_luts(&self) -> usize {
+        self.num_luts
+    }
+
+    /// Returns the depth of the mapped network in LUTs.
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+}
+
+impl Network for LutMapping {
+    type Node = Klut;
+
+    fn outputs(&self) -> impl Iterator<Item = Signal> {
+        self.outputs.iter().copied()
+    }
+    fn node(&self, id: Id) -> Klut {
+        self.nodes[u32::from(id) as usize].clone()
+    }
+    fn num_inputs(&self) -> u64 {
+        self.interface.num_inputs.unwrap_or(0)
+    }
+    fn num_registers(&self) -> u64 {
+        self.interface.registers.len() as u64
+    }
+    fn register(&self, index: u64) -> Register {
+        self.interface.registers[index as usize]
+    }
+    fn input_name(&self, input: u64) -> Option<&str> {
+        self.interface.names.input(input)
+    }
+    fn output_name(&self, output: usize) -> Option<&str> {
+        self.interface.names.output(output)
+    }
+}
+
+/// Maps the given network to a k-LUT network. See [`map_luts_with_choices`] for details. The
+/// number of primary inputs, the registers and the names of the network are kept.
+///
+/// # Example
+/// ```
+/// use eggmock::{convert_network, majority, map_luts, simulate_truth_tables, wallace_multiplier};
+/// use eggmock::{Aig, Klut, LutMappingParams, Mig, MutableNetwork, Network, TopologicalView, Xag};
+///
+/// let xag = wallace_multiplier(MutableNetwork::<Xag>::default(), 3);
+/// let expected = simulate_truth_tables(&xag);
+/// for lut_size in [2, 3, 4, 6] {
+///     let params = LutMappingParams { lut_size, ..LutMappingParams::default() };
+///     let mapping = map_luts(&xag, &params).unwrap();
+///     assert_eq!(simulate_truth_tables(&mapping), expected);
+///     assert_eq!(mapping.input_name(0), Some("a0"));
+///     let order = TopologicalView::new(&mapping);
+///     let luts = order.order().iter().filter_map(|id| match mapping.node(*id) {
+///         Klut::Lut(_, inputs) => Some(inputs.len()),
+///         _ => None,
+///     });
+///     assert!(luts.clone().all(|inputs| inputs <= lut_size));
+///     assert_eq!(luts.count(), mapping.num_luts());
+///
+///     // the LUTs can be decomposed into other network types again
+///     let aig: MutableNetwork<Aig> = convert_network(&mapping);
+///     assert_eq!(simulate_truth_tables(&aig), expected);
+/// }
+///
+/// // majority gates do not fit into LUTs with two inputs
+/// let mig = majority(MutableNetwork::<Mig>::default(), 3);
+/// let params = LutMappingParams { lut_size: 2, ..LutMappingParams::default() };
+/// assert!(map_luts(&mig, &params).is_err());
+/// ```
+pub fn map_luts<N>(network: &N, params: &LutMappingParams) -> Result<LutMapping, LutSizeError>
+where
+    N: Network + ?Sized,
+{
+    let mut mapping = map_luts_with_choices(&ChoiceNetwork::from_network(network), params)?;
+    let mut names = InterfaceNames::default();
+    for input in 0..network.num_inputs() + network.num_registers() {
+        if let Some(name) = network.input_name(input) {
+            names.set_input(input, name);
+        }
+    }
+    for output in 0..mapping.outputs.len() {
+        if let Some(name) = network.output_name(output) {
+            names.set_output(output, name);
+        }
+    }
+    mapping.interface.registers = (0..network.num_registers())
+        .map(|index| network.register(index))
+        .collect();
+    mapping.interface.names = names;
+    Ok(mapping)
+}
+
+/// Maps the outputs of the given e-graph to a k-LUT network. As the mapping is performed on a
+/// [`ChoiceNetwork`] built from the e-graph, the LUTs may cover any of the equivalent structures
+/// in the e-graph. See [`map_luts_with_choices`] for details.
+///
+/// # Example
+/// ```
+/// use eggmock::egg::{EGraph, Runner};
+/// use eggmock::{map_luts_egraph, mixed_rules, ripple_carry_adder, simulate_truth_tables};
+/// use eggmock::{LutMappingParams, MixedLanguage, MutableNetwork, Network, Receiver, Xag};
+///
+/// let adder = ripple_carry_adder(MutableNetwork::<Xag>::default(), 3);
+/// let receiver = EGraph::<MixedLanguage, ()>::default().convert::<Xag>();
+/// let (egraph, roots) = adder.send(receiver);
+/// let runner = Runner::default()
+///     .with_iter_limit(3)
+///     .with_egraph(egraph)
+///     .run(&mixed_rules());
+/// for lut_size in [2, 3, 4] {
+///     let params = LutMappingParams { lut_size, ..LutMappingParams::default() };
+///     let mapping = map_luts_egraph(&runner.egraph, &roots, &params).unwrap();
+///     assert_eq!(simulate_truth_tables(&mapping), simulate_truth_tables(&adder));
+/// }
+/// ```
+pub fn map_luts_egraph<L, A>(
+    egraph: &EGraph<L, A>,
+    roots: &[egg::Id],
+    params: &LutMappingParams,
+) -> Result<LutMapping, LutSizeError>
+where
+    L: NetworkLanguage,
+    A: Analysis<L>,
+{
+    map_luts_with_choices(&ChoiceNetwork::from_egraph(egraph, roots), params)
+}
+
+/// Maps the given choice network to a k-LUT network with LUTs of at most
+/// [`lut_size`](LutMappingParams::lut_size) inputs.
+///
+/// The mapper enumerates the cuts of each node, including the cuts of its choices, and primarily
+/// minimises the number of LUTs and secondarily the depth. Cuts are first selected by their area
+/// flow, which is then refined in [`area_rounds`](LutMappingParams::area_rounds) rounds of exact
+/// area recovery. Inverted fanins are absorbed into the LUT functions and LUTs that are only used
+/// as inverted outputs are complemented, so the mapped network only contains inverted signals for
+/// outputs that point to primary inputs or constants. A LUT that is used both inverted by an
+/// output and non-inverted is duplicated with the complemented function for the inverted outputs,
+/// and the duplicate is included in [`num_luts`](LutMapping::num_luts).
+///
+/// Returns an error if a gate that is not a choice has more distinct non-constant fanins than fit
+/// into a LUT.
+pub fn map_luts_with_choices<N: Node>(
+    network: &ChoiceNetwork<N>,
+    params: &LutMappingParams,
+) -> Result<LutMapping, LutSizeError> {
+    for id in network.ids().filter(|id| !network.is_choice(*id)) {
+        let node = network.node(id);
+        // constants do not occupy a LUT input
+        let mut fanins =
+            Vec::from_iter(node.inputs().iter().map(Signal::node_id).filter(|fanin| {
+                let fanin = network.node(*fanin);
+                !fanin.is_leaf() || fanin.input_index().is_some()
+            }));
+        fanins.sort_unstable();
+        fanins.dedup();
+        if fanins.len() > params.lut_size {
+            return Err(LutSizeError {
+                id,
+                fanin: fanins.len(),
+                lut_size: params.lut_size,
+            });
+        }
+    }
+    let mut mapper = Mapper::new(network, params);
+    mapper.enumerate();
+    for _ in 0..params.area_rounds {
+        mapper.recover_area();
+    }
+    Ok(mapper.extract())
+}
+
+/// A cut together with the information required for selecting it.
+struct MappingCut {
+    cut: Cut,
+    area_flow: f64,
+    depth: u32,
+}
+
+impl MappingCut {
+    fn function(&self) -> &TruthTable {
+        self.cut.truth_table().expect("mapping cuts have functions")
+    }
+
+    /// Returns true iff implementing this cut does not require a LUT, i.e. it is a constant or
+    /// equivalent to (the complement of) its single leaf.
+    fn is_free(&self) -> bool {
+        // the support of the cuts is minimal, so cuts with a single leaf depend on it
+        self.cut.size() <= 1
+    }
+
+    fn area(&self) -> u32 {
+        !self.is_free() as u32
+    }
+}
+
+struct Mapper<'a, N> {
+    network: &'a ChoiceNetwork<N>,
+    params: &'a LutMappingParams,
+    /// The cuts of each node that is not a choice, sorted by their cost. For gates, the last cut
+    /// is the trivial cut.
+    cuts: Vec<Vec<MappingCut>>,
+    /// The index of the selected cut of each gate.
+    best: Vec<usize>,
+    depth: Vec<u32>,
+    area_flow: Vec<f64>,
+    /// The estimated number of fanouts of each node in the mapping.
+    fanouts: Vec<u32>,
+    /// The number of references to each node in the current mapping.
+    refs: Vec<u32>,
+    is_gate: Vec<bool>,
+}
+
+impl<'a, N: Node> Mapper<'a, N> {
+    fn new(network: &'a ChoiceNetwork<N>, params: &'a LutMappingParams) -> Self {
+        let size = network.size();
+        let mut fanouts = vec![0; size];
+        for id in network.ids().filter(|id| !network.is_choice(*id)) {
+            for fanin in network.node(id).inputs() {
+                fanouts[Self::index(fanin.node_id())] += 1;
+            }
+        }
+        for output in network.outputs() {
+            fanouts[Self::index(output.node_id())] += 1;
+        }
+        Self {
+            network,
+            params,
+            cuts: Vec::from_iter((0..size).map(|_| Vec::new())),
+            best: vec![0; size],
+            depth: vec![0; size],
+            area_flow: vec![0.0; size],
+            fanouts,
+            refs: vec![0; size],
+            is_gate: Vec::from_iter(network.ids().map(|id| !network.node(id).is_leaf())),
+        }
+    }
+
+    fn index(id: Id) -> usize {
+        u32::from(id) as usize
+    }
+
+    fn is_gate(&self, id: Id) -> bool {
+        self.is_gate[Self::index(id)]
+    }
+
+    fn best_cut(&self, id: Id) -> &MappingCut {
+        &self.cuts[Self::index(id)][self.best[Self::index(id)]]
+    }
+
+    /// Enumerates the cuts of all nodes and selects the cut with the smallest area flow for each.
+    fn enumerate(&mut self) {
+        for id in self.network.ids() {
+            if self.network.is_choice(id) {
+                continue;
+            }
+            let node = self.network.node(id);
+            if node.is_leaf() {
+                let leaves = if node.input_index().is_some() {
+                    vec![id]
+                } else {
+                    Vec::new()
+                };
+                let num_vars = leaves.len() as u32;
+                let mut cut = Cut::new(leaves);
+                cut.truth_table = Some(match num_vars {
+                    0 => TruthTable::new(0),
+                    _ => TruthTable::nth_var(1, 0),
+                });
+                self.cuts[Self::index(id)] = vec![MappingCut {
+                    cut,
+                    area_flow: 0.0,
+                    depth: 0,
+                }];
+                continue;
+            }
+
+            let mut cuts = self.node_cuts(&node, false);
+            for choice in self.network.choices(id) {
+                let choice_node = self.network.node(choice.node_id());
+                for cut in self.node_cuts(&choice_node, choice.is_inverted()) {
+                    if !cuts.iter().any(|other| other.dominates(&cut)) {
+                        cuts.retain(|other| !cut.dominates(other));
+                        cuts.push(cut);
+                    }
+                }
+            }
+            let fanouts = self.fanouts[Self::index(id)].max(1) as f64;
+            let mut cuts = Vec::from_iter(cuts.into_iter().map(|cut| {
+                let mut cut = MappingCut {
+                    cut,
+                    area_flow: 0.0,
+                    depth: 0,
+                };
+                cut.area_flow = (cut.area() as f64
+                    + cut
+                        .cut
+                        .leaves()
+                        .iter()
+                        .map(|leaf| self.area_flow[Self::index(*leaf)])
+                        .sum::<f64>())
+                    / fanouts;
+                cut.depth = self.cut_depth(&cut);
+                cut
+            }));
+            cuts.sort_by(|a, b| {
+                (a.area_flow, a.depth, a.cut.size())
+                    .partial_cmp(&(b.area_flow, b.depth, b.cut.size()))
+                    .unwrap()
+            });
+            // the cut made of the fanins always fits, see `map_luts_with_choices`
+            cuts.truncate(self.params.cut_limit.max(1));
+
+            let best = &cuts[0];
+            self.area_flow[Self::index(id)] = best.area_flow;
+            self.depth[Self::index(id)] = best.depth;
+            let mut trivial = Cut::new(vec![id]);
+            trivial.truth_table = Some(TruthTable::nth_var(1, 0));
+            cuts.push(MappingCut {
+                cut: trivial,
+                area_flow: f64::INFINITY,
+                depth: u32::MAX,
+            });
+            self.best[Self::index(id)] = 0;
+            self.cuts[Self::index(id)] = cuts;
+        }
+    }
+
+    /// Computes the cuts of the given gate by merging the cuts of its fanins. The functions of the
+    /// cuts are complemented if `invert` is set.
+    fn node_cuts(&self, node: &N, invert: bool) -> Vec<Cut> {
+        let mut fanins = Vec::from_iter(node.inputs().iter().map(Signal::node_id));
+        fanins.sort_unstable();
+        fanins.dedup();
+
+        // each candidate contains the index of the used cut of each fanin
+        let mut candidates: Vec<(Cut, Vec<usize>)> = vec![(Cut::new(Vec::new()), Vec::new())];
+        for fanin in &fanins {
+            let mut merged: Vec<(Cut, Vec<usize>)> = Vec::new();
+            for (partial, used) in &candidates {
+                for (index, cut) in self.cuts[Self::index(*fanin)].iter().enumerate() {
+                    let Some(cut) = partial.merge(&cut.cut, self.params.lut_size) else {
+                        continue;
+                    };
+                    if merged.iter().any(|(other, _)| other.dominates(&cut)) {
+                        continue;
+                    }
+                    merged.retain(|(other, _)| !cut.dominates(other));
+                    let mut used = used.clone();
+                    used.push(index);
+                    merged.push((cut, used));
+                }
+            }
+            candidates = merged;
+        }
+
+        let mut cuts: Vec<Cut> = Vec::new();
+        for (cut, used) in candidates {
+            let num_vars = cut.size() as u32;
+            let inputs = Vec::from_iter(node.inputs().iter().map(|input| {
+                let position = fanins.binary_search(&input.node_id()).unwrap();
+                let fanin_cut = &self.cuts[Self::index(fanins[position])][used[position]].cut;
+                let mapping = Vec::from_iter(
+                    fanin_cut
+                        .leaves()
+                        .iter()
+                        .map(|leaf| cut.leaves().binary_search(leaf).unwrap() as u32),
+                );
+                let function = fanin_cut
+                    .truth_table()
+                    .expect("mapping cuts have functions")
+                    .remap(num_vars, &mapping);
+                if input.is_inverted() {
+                    !function
+                } else {
+                    function
+                }
+            }));
+            let function = node
+                .evaluate(&inputs)
+                .expect("gate function should be known");
+            let function = if invert { !function } else { function };
+            let cut = Self::minimize_support(cut, function);
+            if !cuts.iter().any(|other| other.dominates(&cut)) {
+                cuts.retain(|other| !cut.dominates(other));
+                cuts.push(cut);
+            }
+        }
+        cuts
+    }
+
+    /// Removes the leaves that the function of the cut does not depend on.
+    fn minimize_support(cut: Cut, function: TruthTable) -> Cut {
+        let support = Vec::from_iter((0..function.num_vars()).filter(|var| function.has_var(*var)));
+        if support.len() == cut.size() {
+            let mut cut = cut;
+            cut.truth_table = Some(function);
+            return cut;
+        }
+        let mut mapping = vec![0; cut.size()];
+        for (new_var, var) in support.iter().enumerate() {
+            mapping[*var as usize] = new_var as u32;
+        }
+        let leaves = Vec::from_iter(support.iter().map(|var| cut.leaves()[*var as usize]));
+        let mut cut = Cut::new(leaves);
+        cut.truth_table = Some(function.remap(support.len() as u32, &mapping));
+        cut
+    }
+
+    fn cut_depth(&self, cut: &MappingCut) -> u32 {
+        let leaves = cut
+            .cut
+            .leaves()
+            .iter()
+            .map(|leaf| self.depth[Self::index(*leaf)])
+            .max()
+            .unwrap_or(0);
+        leaves + cut.area()
+    }
+
+    /// Counts the references of the nodes in the current mapping.
+    fn compute_refs(&mut self) {
+        self.refs.fill(0);
+        for output in self.network.outputs() {
+            self.refs[Self::index(output.node_id())] += 1;
+        }
+        for id in (0..self.network.size() as u32).rev().map(Id::from) {
+            if self.refs[Self::index(id)] == 0 || !self.is_gate(id) {
+                continue;
+            }
+            for leaf in self.best_cut(id).cut.leaves().to_vec() {
+                self.refs[Self::index(leaf)] += 1;
+            }
+        }
+    }
+
+    /// Selects the cut of each gate that requires the fewest additional LUTs given the current
+    /// mapping of the other nodes.
+    fn recover_area(&mut self) {
+        self.compute_refs();
+        for id in self.network.ids() {
+            if self.network.is_choice(id) || !self.is_gate(id) {
+                continue;
+            }
+            let index = Self::index(id);
+            let mapped = self.refs[index] > 0;
+            if mapped {
+                self.deref_cut(id, self.best[index]);
+            }
+            let mut best = (u32::MAX, u32::MAX, usize::MAX);
+            for cut in 0..self.cuts[index].len() - 1 {
+                let area = self.ref_cut(id, cut);
+                self.deref_cut(id, cut);
+                let depth = self.cut_depth(&self.cuts[index][cut]);
+                if (area, depth) < (best.0, best.1) {
+                    best = (area, depth, cut);
+                }
+            }
+            self.best[index] = best.2;
+            self.depth[index] = best.1;
+            if mapped {
+                self.ref_cut(id, best.2);
+            }
+        }
+    }
+
+    /// References the leaves of the given cut of the node and returns the number of LUTs that are
+    /// added to the mapping.
+    fn ref_cut(&mut self, id: Id, cut: usize) -> u32 {
+        // an explicit stack, as the mapped cones may be arbitrarily deep
+        let mut stack = vec![(id, cut)];
+        let mut area = 0;
+        while let Some((id, cut)) = stack.pop() {
+            let cut = &self.cuts[Self::index(id)][cut];
+            area += cut.area();
+            for leaf in cut.cut.leaves() {
+                let index = Self::index(*leaf);
+                if self.refs[index] == 0 && self.is_gate[index] {
+                    stack.push((*leaf, self.best[index]));
+                }
+                self.refs[index] += 1;
+            }
+        }
+        area
+    }
+
+    /// Dereferences the leaves of the given cut of the node and returns the number of LUTs that
+    /// are removed from the mapping.
+    fn deref_cut(&mut self, id: Id, cut: usize) -> u32 {
+        let mut stack = vec![(id, cut)];
+        let mut area = 0;
+        while let Some((id, cut)) = stack.pop() {
+            let cut = &self.cuts[Self::index(id)][cut];
+            area += cut.area();
+            for leaf in cut.cut.leaves() {
+                let index = Self::index(*leaf);
+                self.refs[index] -= 1;
+                if self.refs[index] == 0 && self.is_gate[index] {
+                    stack.push((*leaf, self.best[index]));
+                }
+            }
+        }
+        area
+    }
+
+    /// Builds the k-LUT network from the selected cuts.
+    fn extract(mut self) -> LutMapping {
+        self.compute_refs();
+        let mut nodes: Vec<Klut> = Vec::new();
+        let mut constant = None;
+        let mut signals: Vec<Option<Signal>> = vec![None; self.network.size()];
+        for id in self.network.ids() {
+            if self.refs[Self::index(id)] == 0 {
+                continue;
+            }
+            let node = self.network.node(id);
+            let signal = if let Some(input) = node.input_index() {
+                nodes.push(Klut::Input(input));
+                Signal::new(Id::from(nodes.len() as u32 - 1), false)
+            } else {
+                let cut = self.best_cut(id);
+                let function = cut.function();
+                match cut.cut.size() {
+                    0 => {
+                        let id = *constant.get_or_insert_with(|| {
+                            nodes.push(Klut::False);
+                            Id::from(nodes.len() as u32 - 1)
+                        });
+                        Signal::new(id, function.is_const1())
+                    }
+                    1 => {
+                        let leaf = signals[Self::index(cut.cut.leaves()[0])].unwrap();
+                        leaf.maybe_invert(!function.bit(1))
+                    }
+                    _ => {
+                        let mut function = function.clone();
+                        let mut inputs = Vec::with_capacity(cut.cut.size());
+                        for (var, leaf) in cut.cut.leaves().iter().enumerate() {
+                            let signal = signals[Self::index(*leaf)].unwrap();
+                            if signal.is_inverted() {
+                                function = function.flip_var(var as u32);
+                            }
+                            inputs.push(Signal::new(signal.node_id(), false));
+                        }
+                        nodes.push(Klut::lut(function, inputs));
+                        Signal::new(Id::from(nodes.len() as u32 - 1), false)
+                    }
+                }
+            };
+            signals[Self::index(id)] = Some(signal);
+        }
+        let mut outputs = Vec::from_iter(
+            self.network
+                .outputs()
+                .map(|output| output.map_id(|id| signals[Self::index(id)].unwrap())),
+        );
+        complement_outputs(&mut nodes, &mut outputs);
+
+        let mut depth = vec![0u32; nodes.len()];
+        let mut num_luts = 0;
+        for (index, node) in nodes.iter().enumerate() {
+            if let Klut::Lut(_, inputs) = node {
+                num_luts += 1;
+                depth[index] = 1 + inputs
+                    .iter()
+                    .map(|input| depth[Self::index(input.node_id())])
+                    .max()
+                    .unwrap_or(0);
+            }
+        }
+        let depth = outputs
+            .iter()
+            .map(|output| depth[Self::index(output.node_id())])
+            .max()
+            .unwrap_or(0);
+        LutMapping {
+            nodes,
+            outputs,
+            interface: Interface {
+                num_inputs: Some(self.network.num_inputs()),
+                ..Interface::default()
+            },
+            num_luts,
+            depth,
+        }
+    }
+}
+
+/// Removes the inversion of outputs that point to LUTs by complementing the function of LUTs
+/// that are only used inverted and by duplicating LUTs that are also used non-inverted.
+fn complement_outputs(nodes: &mut Vec<Klut>, outputs: &mut [Signal]) {
+    let mut non_inverted_uses = vec![false; nodes.len()];
+    for node in nodes.iter() {
+        for input in node.inputs() {
+            non_inverted_uses[u32::from(input.node_id()) as usize] = true;
+        }
+    }
+    for output in outputs.iter().filter(|output| !output.is_inverted()) {
+        non_inverted_uses[u32::from(output.node_id()) as usize] = true;
+    }
+    let mut complements = vec![None; nodes.len()];
+    for output in outputs.iter_mut().filter(|output| output.is_inverted()) {
+        let index = u32::from(output.node_id()) as usize;
+        let Klut::Lut(function, inputs) = &nodes[index] else {
+            continue;
+        };
+        if complements[index].is_none() {
+            let complement = Klut::Lut(!function.clone(), inputs.clone());
+            if non_inverted_uses[index] {
+                nodes.push(complement);
+                complements[index] = Some(Id::from(nodes.len() as u32 - 1));
+            } else {
+                nodes[index] = complement;
+                complements[index] = Some(output.node_id());
+            }
+        }
+        *output = Signal::new(complements[index].unwrap(), false);
+    }
+}
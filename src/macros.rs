@@ -1,11 +1,18 @@
 /// # Example
 /// ```no_run
+/// use eggmock::{egg, NetworkLanguage, Signal};
+///
 /// eggmock::define_network! {
 ///     pub enum "xag" = Xag {
-///         // 2: fanin
-///         "*" = And(2),
-///         "xor" = Xor(2)
-///         "xor4" =
+///         gates {
+///             // 2: fanin
+///             "and" = And(2),
+///             "xor" = Xor(2)
+///         }
+///         // optional, mapped to mockturtle's `create_nary_*`
+///         nary_gates {
+///             "and4" = And4(4)
+///         }
 ///     }
 /// }
 /// ```
@@ -21,19 +28,37 @@
 /// - implements [`GateType`] for `[<$name GateType]`
 /// - implements [`ReceiverFFI`] for `[<$name ReceiverFFI>]<R>`
 /// - implements [`Receiver` for [<$name ReceiverFFI>]<R>`
+/// - implements `Serialize` and `Deserialize` for `$name` if the `serde` feature is enabled
 ///
 /// NOTE: see [paste](https://docs.rs/paste/latest/paste/) for understanding `[<...>]` notation
 #[macro_export]
-macro_rules! define_network {(
+macro_rules! define_network {
+    (
+        $(#[$meta:meta])* $vis:vis enum $mockturtle_ntk:literal = $name:ident {
+            gates {
+                $($gate_str:literal = $gate:ident($fanin:literal)),+ $(,)?
+            }
+        }
+    ) => {
+        $crate::define_network! {
+            $(#[$meta])* $vis enum $mockturtle_ntk = $name {
+                gates {
+                    $($gate_str = $gate($fanin)),+
+                }
+                nary_gates {}
+            }
+        }
+    };
+    (
         $(#[$meta:meta])* $vis:vis enum $mockturtle_ntk:literal = $name:ident {
             // Binary gates
             gates {
-                $($gate_str:literal = $gate:ident($fanin:literal)),* $(,)?
+                $($gate_str:literal = $gate:ident($fanin:literal)),+ $(,)?
             }
             // N-ary gates
-            $(nary_gates {
+            nary_gates {
                 $($gate_nary_str:literal = $gate_nary:ident($fanin_nary:literal)),* $(,)?
-            })*
+            }
         }
     ) => {
         $crate::paste::paste! {
@@ -45,8 +70,8 @@ macro_rules! define_network {(
                     Input(u64), // TODO: change `u64` to `Signal`??
                     "f" = False,
                     "!" = Not($crate::egg::Id),
-                    $($gate_str = $gate([$crate::egg::Id;$fanin])),+,
-                    $($gate_nary_str = $gate_nary([$crate::egg::Id;$fanin_nary])),*
+                    $($gate_str = $gate([$crate::egg::Id;$fanin]),)+
+                    $($gate_nary_str = $gate_nary([$crate::egg::Id;$fanin_nary]),)*
                 }
             }
 
@@ -55,10 +80,12 @@ macro_rules! define_network {(
             $vis enum $name {
                 Input(u64),
                 False,
-                $($gate([$crate::Signal;$fanin])),+
-                $($gate_nary([$crate::Signal;$fanin_nary])),*
+                $($gate([$crate::Signal;$fanin]),)+
+                $($gate_nary([$crate::Signal;$fanin_nary]),)*
             }
 
+            $crate::__impl_node_serde!($name);
+
             impl $crate::Node for $name {
                 type Gates = $crate::paste::paste!([<$name GateType>]);
                 type Language = [<$name Language>];
@@ -78,7 +105,12 @@ macro_rules! define_network {(
                             $crate::seq_macro::seq!(N in 0..$fanin {
                                 Self::$gate([#(map(signals[N]),)*])
                             })
-                        }),+
+                        })+
+                        $(Self::$gate_nary(signals) => {
+                            $crate::seq_macro::seq!(N in 0..$fanin_nary {
+                                Self::$gate_nary([#(map(signals[N]),)*])
+                            })
+                        })*
                     }
                 }
 
@@ -86,7 +118,41 @@ macro_rules! define_network {(
                     match self {
                         Self::Input(_) => &[],
                         Self::False => &[],
-                        $(Self::$gate(ids) => ids),+
+                        $(Self::$gate(ids) => ids,)+
+                        $(Self::$gate_nary(ids) => ids,)*
+                    }
+                }
+
+                fn input_index(&self) -> Option<u64> {
+                    match self {
+                        Self::Input(index) => Some(*index),
+                        _ => None,
+                    }
+                }
+
+                fn input(index: u64) -> Self {
+                    Self::Input(index)
+                }
+
+                fn constant_false() -> Self {
+                    Self::False
+                }
+
+                fn from_gate(gate: [<$name GateType>], inputs: &[Signal]) -> Option<Self> {
+                    match gate {
+                        $([<$name GateType>]::$gate => Some(Self::$gate(inputs.try_into().ok()?)),)+
+                        $([<$name GateType>]::$gate_nary => {
+                            Some(Self::$gate_nary(inputs.try_into().ok()?))
+                        })*
+                    }
+                }
+
+                fn gate_type(&self) -> Option<[<$name GateType>]> {
+                    match self {
+                        Self::Input(_) => None,
+                        Self::False => None,
+                        $(Self::$gate(_) => Some([<$name GateType>]::$gate),)+
+                        $(Self::$gate_nary(_) => Some([<$name GateType>]::$gate_nary),)*
                     }
                 }
             }
@@ -107,8 +173,15 @@ macro_rules! define_network {(
                             $crate::seq_macro::seq!(N in 0..$fanin {
                                 [#(signal_mapper(ids[N]),)*]
                             })
-                        )
-                        ),+
+                        ),
+                        )+
+                        $(
+                        $name::$gate_nary(ids) => Self::$gate_nary(
+                            $crate::seq_macro::seq!(N in 0..$fanin_nary {
+                                [#(signal_mapper(ids[N]),)*]
+                            })
+                        ),
+                        )*
                     }
                 }
 
@@ -125,8 +198,15 @@ macro_rules! define_network {(
                             $crate::seq_macro::seq!(N in 0..$fanin {
                                 [#(id_mapper(ids[N]),)*]
                             })
-                        ))
-                        ),+
+                        )),
+                        )+
+                        $(
+                        Self::$gate_nary(ids) => Some($name::$gate_nary(
+                            $crate::seq_macro::seq!(N in 0..$fanin_nary {
+                                [#(id_mapper(ids[N]),)*]
+                            })
+                        )),
+                        )*
                     }
                 }
 
@@ -142,44 +222,56 @@ macro_rules! define_network {(
             }
 
             /// Network-specific gates
-            #[derive(Copy, Clone, Eq, PartialEq, Hash)]
+            #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
             $vis enum [<$name GateType>] {
-                $($gate),+
+                $($gate,)+
+                $($gate_nary,)*
             }
 
             /// Each gate in `mockturtle` has a name, fanin
             impl $crate::GateType for [<$name GateType>] {
                 type Node = $name;
                 const VARIANTS: &'static [Self] = &[
-                    $(Self::$gate),+
-                    $(Self::$gate_nary),*
+                    $(Self::$gate,)+
+                    $(Self::$gate_nary,)*
                 ];
 
                 fn name(&self) -> &'static str {
                     match self {
-                        $(Self::$gate => stringify!([<$gate:snake:lower>])),+
-                        $(Self::$gate_nary => stringify!([<$gate_nary:snake:lower>])),*
+                        $(Self::$gate => stringify!([<$gate:snake:lower>]),)+
+                        $(Self::$gate_nary => stringify!([<$gate_nary:snake:lower>]),)*
                     }
                 }
 
                 fn fanin(&self) -> u8 {
                     match self {
-                        $(Self::$gate => $fanin),+
-                        $(Self::$gate_nary => $fanin_nary),*
+                        $(Self::$gate => $fanin,)+
+                        $(Self::$gate_nary => $fanin_nary,)*
                     }
                 }
 
                 fn mockturtle_create(&self) -> &'static str {
                     match self {
-                        $(Self::$gate => concat!("create_", $gate_str)),+
-                        $(Self::$gate_nary => concat!("create_nary_", $gate_nary_str)),*
+                        $(Self::$gate => concat!("create_", $gate_str),)+
+                        $(Self::$gate_nary => concat!("create_nary_", $gate_nary_str),)*
                     }
                 }
 
                 fn mockturtle_is(&self) -> &'static str {
                     match self {
-                        $(Self::$gate => concat!("is_", $gate_str)),+
-                        $(Self::$gate_nary => concat!("is_nary", $gate_nary_str)),*
+                        $(Self::$gate => concat!("is_", $gate_str),)+
+                        $(Self::$gate_nary => concat!("is_nary_", $gate_nary_str),)*
+                    }
+                }
+
+                fn function(&self) -> Option<$crate::GateFunction> {
+                    match self {
+                        $(Self::$gate => const {
+                            $crate::GateFunction::from_mockturtle_name($gate_str)
+                        },)+
+                        $(Self::$gate_nary => const {
+                            $crate::GateFunction::from_mockturtle_name(concat!("nary_", $gate_nary_str))
+                        },)*
                     }
                 }
             }
@@ -192,10 +284,33 @@ macro_rules! define_network {(
                 create_constant: extern "C" fn (*mut $crate::libc::c_void, value: bool) -> $crate::Signal,
                 $([<create_ $gate:snake:lower>]: $crate::seq_macro::seq!(N in 1..=$fanin {
                      extern "C" fn(*mut $crate::libc::c_void, #(input~N: $crate::Signal,)*) -> $crate::Signal
-                })),+,
+                }),)+
                 $([<create_nary_ $gate_nary:snake:lower>]: $crate::seq_macro::seq!(N in 1..=$fanin_nary {
                      extern "C" fn(*mut $crate::libc::c_void, #(input~N: $crate::Signal,)*) -> $crate::Signal
-                })),*
+                }),)*
+                set_num_inputs: extern "C" fn(*mut $crate::libc::c_void, num_inputs: u64),
+                set_registers: extern "C" fn(
+                    *mut $crate::libc::c_void,
+                    inits: *const u8,
+                    num_registers: usize
+                ),
+                set_input_name: extern "C" fn(
+                    *mut $crate::libc::c_void,
+                    input: u64,
+                    name: *const $crate::libc::c_char,
+                    name_size: usize
+                ),
+                set_output_name: extern "C" fn(
+                    *mut $crate::libc::c_void,
+                    output: usize,
+                    name: *const $crate::libc::c_char,
+                    name_size: usize
+                ),
+                add_choice: extern "C" fn(
+                    *mut $crate::libc::c_void,
+                    node: $crate::Signal,
+                    choice: $crate::Signal
+                ),
                 done: extern "C" fn (*mut $crate::libc::c_void, outputs: *const $crate::Signal, outputs_size: usize) -> R,
             }
 
@@ -209,8 +324,13 @@ macro_rules! define_network {(
                         data: data as *mut $crate::libc::c_void,
                         create_input: Self::create_input::<Recv>,
                         create_constant: Self::create_constant::<Recv>,
-                        $([<create_ $gate:snake:lower>]: Self::[<create_ $gate:snake:lower>]::<Recv>),+,
-                        $([<create_nary_ $gate_nary:snake:lower>]: Self::[<create_nary_ $gate_nary:snake:lower>]::<Recv>),*
+                        $([<create_ $gate:snake:lower>]: Self::[<create_ $gate:snake:lower>]::<Recv>,)+
+                        $([<create_nary_ $gate_nary:snake:lower>]: Self::[<create_nary_ $gate_nary:snake:lower>]::<Recv>,)*
+                        set_num_inputs: Self::set_num_inputs::<Recv>,
+                        set_registers: Self::set_registers::<Recv>,
+                        set_input_name: Self::set_input_name::<Recv>,
+                        set_output_name: Self::set_output_name::<Recv>,
+                        add_choice: Self::add_choice::<Recv>,
                         done: Self::done::<Recv>,
                     }
                 }
@@ -230,18 +350,39 @@ macro_rules! define_network {(
                             $crate::seq_macro::seq!(N in 0..$fanin {
                                 (self.[<create_ $gate:snake:lower>])(self.data, #(ids[N],)*)
                             })
-                        }),+
+                        })+
                         $($name::$gate_nary(ids) => {
                             $crate::seq_macro::seq!(N in 0..$fanin_nary {
-                                (self.[<create_ $gate_nary:snake:lower>])(self.data, #(ids[N],)*)
+                                (self.[<create_nary_ $gate_nary:snake:lower>])(self.data, #(ids[N],)*)
                             })
-                        }),*
+                        })*
                     }
                 }
 
                 fn done(self, outputs: &[$crate::Signal]) -> R {
                     (self.done)(self.data, outputs.as_ptr(), outputs.len())
                 }
+
+                fn set_num_inputs(&mut self, num_inputs: u64) {
+                    (self.set_num_inputs)(self.data, num_inputs)
+                }
+
+                fn set_registers(&mut self, registers: &[$crate::Register]) {
+                    let inits = Vec::from_iter(registers.iter().map($crate::Register::mockturtle_init));
+                    (self.set_registers)(self.data, inits.as_ptr(), inits.len())
+                }
+
+                fn set_input_name(&mut self, input: u64, name: &str) {
+                    (self.set_input_name)(self.data, input, name.as_ptr() as *const _, name.len())
+                }
+
+                fn set_output_name(&mut self, output: usize, name: &str) {
+                    (self.set_output_name)(self.data, output, name.as_ptr() as *const _, name.len())
+                }
+
+                fn add_choice(&mut self, node: $crate::Signal, choice: $crate::Signal) {
+                    (self.add_choice)(self.data, node, choice)
+                }
             }
 
             /// FFI for network-specific functions provided by `mockturtle`
@@ -282,7 +423,7 @@ macro_rules! define_network {(
                 });)+
 
                 $($crate::seq_macro::seq!(N in 1..=$fanin_nary {
-                    pub extern "C" fn [<create_ $gate_nary:snake:lower>]<Recv>(
+                    pub extern "C" fn [<create_nary_ $gate_nary:snake:lower>]<Recv>(
                         data: *mut $crate::libc::c_void
                         #(, input~N: $crate::Signal)*
                     ) -> $crate::Signal
@@ -293,6 +434,72 @@ macro_rules! define_network {(
                     }
                 });)*
 
+                extern "C" fn set_num_inputs<Recv>(
+                    data: *mut $crate::libc::c_void,
+                    num_inputs: u64
+                )
+                where
+                    Recv: $crate::Receiver<Node = $name, Result = R> + 'static
+                {
+                    unsafe { &mut *(data as *mut Recv) }.set_num_inputs(num_inputs)
+                }
+
+                extern "C" fn set_registers<Recv>(
+                    data: *mut $crate::libc::c_void,
+                    inits: *const u8,
+                    num_registers: usize
+                )
+                where
+                    Recv: $crate::Receiver<Node = $name, Result = R> + 'static
+                {
+                    let inits = if num_registers == 0 {
+                        &[]
+                    } else {
+                        unsafe { std::slice::from_raw_parts(inits, num_registers) }
+                    };
+                    let registers = Vec::from_iter(
+                        inits.iter().map(|init| $crate::Register::from_mockturtle_init(*init))
+                    );
+                    unsafe { &mut *(data as *mut Recv) }.set_registers(&registers)
+                }
+
+                extern "C" fn set_input_name<Recv>(
+                    data: *mut $crate::libc::c_void,
+                    input: u64,
+                    name: *const $crate::libc::c_char,
+                    name_size: usize
+                )
+                where
+                    Recv: $crate::Receiver<Node = $name, Result = R> + 'static
+                {
+                    let name = unsafe { $crate::ffi_str(name, name_size) };
+                    unsafe { &mut *(data as *mut Recv) }.set_input_name(input, &name)
+                }
+
+                extern "C" fn set_output_name<Recv>(
+                    data: *mut $crate::libc::c_void,
+                    output: usize,
+                    name: *const $crate::libc::c_char,
+                    name_size: usize
+                )
+                where
+                    Recv: $crate::Receiver<Node = $name, Result = R> + 'static
+                {
+                    let name = unsafe { $crate::ffi_str(name, name_size) };
+                    unsafe { &mut *(data as *mut Recv) }.set_output_name(output, &name)
+                }
+
+                extern "C" fn add_choice<Recv>(
+                    data: *mut $crate::libc::c_void,
+                    node: $crate::Signal,
+                    choice: $crate::Signal
+                )
+                where
+                    Recv: $crate::Receiver<Node = $name, Result = R> + 'static
+                {
+                    unsafe { &mut *(data as *mut Recv) }.add_choice(node, choice)
+                }
+
                 extern "C" fn done<Recv>(
                     data: *mut $crate::libc::c_void,
                     outputs: *const $crate::Signal,
@@ -312,3 +519,37 @@ macro_rules! define_network {(
         }
     };
 }
+
+/// Implements `Serialize` and `Deserialize` for a node type generated by [`define_network`] if
+/// the `serde` feature of eggmock is enabled. The feature has to be checked here, as a `cfg` in
+/// the expansion of [`define_network`] would check the features of the calling crate instead.
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_node_serde {
+    ($name:ident) => {
+        impl $crate::serde::Serialize for $name {
+            fn serialize<S: $crate::serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> ::std::result::Result<S::Ok, S::Error> {
+                $crate::serialize_node(self, serializer)
+            }
+        }
+
+        impl<'de> $crate::serde::Deserialize<'de> for $name {
+            fn deserialize<D: $crate::serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> ::std::result::Result<Self, D::Error> {
+                $crate::deserialize_node(deserializer)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_node_serde {
+    ($name:ident) => {};
+}
@@ -0,0 +1,144 @@
+use std::marker::PhantomData;
+
+use egg::{rewrite, Analysis, CostFunction, EGraph, Extractor, Id as EggId, Rewrite};
+use rustc_hash::FxHashMap;
+
+use crate::{GateType, Id, MixedLanguage, Network, NetworkLanguage, Node, Receiver, Signal};
+
+/// Returns rules that relate the gates of [`Mixed`](crate::Mixed) networks to each other, i.e.
+/// ANDs and ORs as majorities with a constant input, ORs as ANDs with inverted inputs and outputs,
+/// XORs and majorities expanded into ANDs and ORs, as well as double negation and commutativity.
+///
+/// Saturating an e-graph with these rules makes every gate available in the gate types of all
+/// built-in networks, so that [`extract_restricted`] can extract a network of any of them.
+pub fn mixed_rules<A: Analysis<MixedLanguage>>() -> Vec<Rewrite<MixedLanguage, A>> {
+    let mut rules = vec![
+        rewrite!("double-not"; "(! (! ?a))" => "?a"),
+        rewrite!("and-commute"; "(and ?a ?b)" => "(and ?b ?a)"),
+        rewrite!("or-commute"; "(or ?a ?b)" => "(or ?b ?a)"),
+        rewrite!("xor-commute"; "(xor ?a ?b)" => "(xor ?b ?a)"),
+    ];
+    rules.extend(rewrite!("and-maj"; "(and ?a ?b)" <=> "(maj ?a ?b f)"));
+    rules.extend(rewrite!("or-maj"; "(or ?a ?b)" <=> "(maj ?a ?b (! f))"));
+    rules.extend(rewrite!("or-and"; "(or ?a ?b)" <=> "(! (and (! ?a) (! ?b)))"));
+    rules.extend(rewrite!("xor-and";
+        "(xor ?a ?b)" <=> "(! (and (! (and ?a (! ?b))) (! (and (! ?a) ?b))))"
+    ));
+    rules.extend(rewrite!("maj-and-or";
+        "(maj ?a ?b ?c)" <=> "(or (and ?a ?b) (and ?c (or ?a ?b)))"
+    ));
+    rules
+}
+
+/// The cost of an e-node under a [`RestrictedCost`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Restricted<C> {
+    /// The e-node and all e-nodes below it are valid in the target network.
+    Valid(C),
+    /// The e-node or an e-node below it has no corresponding gate in the target network.
+    Invalid,
+}
+
+/// A cost function that only allows e-nodes whose gate exists in the network type `N`, i.e. that
+/// has a gate type with the same [`GateFunction`](crate::GateFunction) and fanin. Inputs,
+/// constants and nots are always allowed. Other e-nodes and e-nodes that depend on them have
+/// cost [`Restricted::Invalid`], valid e-nodes are rated by the wrapped cost function.
+pub struct RestrictedCost<N, CF> {
+    cost_function: CF,
+    _target: PhantomData<fn() -> N>,
+}
+
+impl<N: Node, CF> RestrictedCost<N, CF> {
+    pub fn new(cost_function: CF) -> Self {
+        Self {
+            cost_function,
+            _target: PhantomData,
+        }
+    }
+
+    /// Returns whether the given e-node may be extracted into a network of type `N`.
+    pub fn is_valid<L: NetworkLanguage>(enode: &L) -> bool {
+        let placeholder = Signal::new(Id::from(0), false);
+        let Some(gate) = enode
+            .to_node(|_| placeholder)
+            .and_then(|node| node.gate_type())
+        else {
+            return true;
+        };
+        let Some(function) = gate.function() else {
+            return false;
+        };
+        N::Gates::VARIANTS
+            .iter()
+            .any(|target| target.function() == Some(function) && target.fanin() == gate.fanin())
+    }
+}
+
+impl<N, L, CF> CostFunction<L> for RestrictedCost<N, CF>
+where
+    N: Node,
+    L: NetworkLanguage,
+    CF: CostFunction<L>,
+{
+    type Cost = Restricted<CF::Cost>;
+
+    fn cost<C>(&mut self, enode: &L, mut costs: C) -> Self::Cost
+    where
+        C: FnMut(EggId) -> Self::Cost,
+    {
+        if !Self::is_valid(enode) {
+            return Restricted::Invalid;
+        }
+        let mut child_costs = FxHashMap::default();
+        for child in enode.children() {
+            match costs(*child) {
+                Restricted::Valid(cost) => child_costs.insert(*child, cost),
+                Restricted::Invalid => return Restricted::Invalid,
+            };
+        }
+        Restricted::Valid(
+            self.cost_function
+                .cost(enode, |child| child_costs[&child].clone()),
+        )
+    }
+}
+
+/// Extracts the network given by the roots from the e-graph, using only e-nodes that are valid in
+/// the network type of the receiver (see [`RestrictedCost`]), and sends it to the receiver.
+///
+/// If a root cannot be extracted with valid e-nodes only, the invalid e-nodes are decomposed with
+/// [`decompose_gate`](crate::decompose_gate), so the result is always equivalent to the roots.
+///
+/// # Example
+/// ```
+/// use eggmock::egg::{AstSize, EGraph, Runner};
+/// use eggmock::{extract_restricted, mixed_rules, ripple_carry_adder, simulate_truth_tables};
+/// use eggmock::{Aig, MixedLanguage, MutableNetwork, Network, Receiver, Xmg};
+///
+/// let aig = ripple_carry_adder(MutableNetwork::<Aig>::default(), 2);
+/// let (egraph, roots) = aig.send(EGraph::<MixedLanguage, ()>::default().convert::<Aig>());
+/// let runner = Runner::default().with_egraph(egraph).run(&mixed_rules());
+/// let xmg = extract_restricted(
+///     &runner.egraph,
+///     &roots,
+///     AstSize,
+///     MutableNetwork::<Xmg>::default(),
+/// );
+/// assert_eq!(simulate_truth_tables(&xmg), simulate_truth_tables(&aig));
+/// assert!(xmg.num_gates() < aig.num_gates());
+/// ```
+pub fn extract_restricted<L, A, CF, R>(
+    egraph: &EGraph<L, A>,
+    roots: &[EggId],
+    cost_function: CF,
+    receiver: R,
+) -> R::Result
+where
+    L: NetworkLanguage,
+    A: Analysis<L>,
+    CF: CostFunction<L>,
+    R: Receiver,
+{
+    let cost_function = RestrictedCost::<R::Node, _>::new(cost_function);
+    (Extractor::new(egraph, cost_function), roots.to_vec()).send(receiver.convert())
+}
@@ -1,37 +1,59 @@
 use rustc_hash::FxHashMap;
-use crate::{Id, Node, Network, Receiver, Signal};
+use crate::{Id, Node, Network, Receiver, Register, Signal};
 
 pub trait NetworkWithBackwardEdges: Network {
     fn node_outputs(&self, id: Id) -> impl Iterator<Item = Id> + '_;
     fn leafs(&self) -> impl Iterator<Item = Id> + '_;
+    /// Returns the number of references to the given node, i.e. the number of gate inputs and
+    /// primary outputs that point to it. A gate that uses the node as several of its inputs
+    /// references it multiple times.
+    fn fanout_size(&self, id: Id) -> usize {
+        let gate_references: usize = self
+            .node_outputs(id)
+            .map(|fanout| {
+                self.node(fanout)
+                    .inputs()
+                    .iter()
+                    .filter(|input| input.node_id() == id)
+                    .count()
+            })
+            .sum();
+        gate_references + self.outputs().filter(|output| output.node_id() == id).count()
+    }
 }
 
 pub struct ComputedNetworkWithBackwardEdges<'a, P: ?Sized> {
     network: &'a P,
     backward: FxHashMap<Id, Vec<Id>>,
     leafs: Vec<Id>,
+    references: FxHashMap<Id, usize>,
 }
 
 impl<'a, P: Network + ?Sized> ComputedNetworkWithBackwardEdges<'a, P> {
     pub fn new(network: &'a P) -> Self {
         let mut backward = FxHashMap::default();
         let mut leafs = Vec::new();
+        let mut references = FxHashMap::default();
+        for output in network.outputs() {
+            *references.entry(output.node_id()).or_insert(0) += 1;
+        }
         for (output_id, output) in network.iter() {
             let inputs = output.inputs();
             for i in 0..inputs.len() {
                 let input_signal = inputs[i];
                 let input_id = input_signal.node_id();
+                *references.entry(input_id).or_insert(0) += 1;
                 // prevent duplicate entries in the Vecs
                 if inputs[0..i].iter().map(Signal::node_id).any(|id| id == input_id) {
                     continue;
                 }
                 backward.entry(input_id).or_insert_with(Vec::new).push(output_id);
             }
-            if inputs.len() == 0 {
+            if inputs.is_empty() {
                 leafs.push(output_id);
             }
         }
-        Self { network, backward, leafs }
+        Self { network, backward, leafs, references }
     }
 }
 
@@ -44,6 +66,21 @@ impl<P: Network + ?Sized> Network for ComputedNetworkWithBackwardEdges<'_, P> {
     fn node(&self, id: Id) -> Self::Node {
         self.network.node(id)
     }
+    fn num_inputs(&self) -> u64 {
+        self.network.num_inputs()
+    }
+    fn num_registers(&self) -> u64 {
+        self.network.num_registers()
+    }
+    fn register(&self, index: u64) -> Register {
+        self.network.register(index)
+    }
+    fn input_name(&self, input: u64) -> Option<&str> {
+        self.network.input_name(input)
+    }
+    fn output_name(&self, output: usize) -> Option<&str> {
+        self.network.output_name(output)
+    }
     fn iter(&self) -> impl Iterator<Item=(Id, Self::Node)> + '_ {
         self.network.iter()
     }
@@ -59,4 +96,7 @@ impl<P: Network + ?Sized> NetworkWithBackwardEdges for ComputedNetworkWithBackwa
     fn leafs(&self) -> impl Iterator<Item=Id> + '_ {
         self.leafs.iter().cloned()
     }
+    fn fanout_size(&self, id: Id) -> usize {
+        self.references.get(&id).copied().unwrap_or(0)
+    }
 }
@@ -0,0 +1,296 @@
+use egg::{Analysis, EGraph};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{Id, Network, NetworkLanguage, Node, Receiver, Signal};
+
+/// A network in which nodes may have *choices*, i.e. alternative, functionally equivalent
+/// implementations.
+///
+/// The nodes are stored in topological order and every node (including the choices) only has
+/// fanins with smaller ids than the node it is a choice of. Hence, each node may be replaced by any
+/// of its choices without introducing a cycle. Choice nodes are never used as fanins or outputs.
+#[derive(Debug, Clone)]
+pub struct ChoiceNetwork<N> {
+    nodes: Vec<N>,
+    choices: Vec<Vec<Signal>>,
+    is_choice: Vec<bool>,
+    outputs: Vec<Signal>,
+    num_inputs: u64,
+}
+
+impl<N: Node> ChoiceNetwork<N> {
+    /// Builds a choice network from all e-nodes of the e-classes reachable from the given roots.
+    ///
+    /// Each e-class is represented by the first of its e-nodes whose children are all represented
+    /// already. The remaining e-nodes become choices of the representative if all of their children
+    /// are represented by nodes that were created before it. E-nodes that do not fulfill this
+    /// condition are dropped, which guarantees that the network stays acyclic.
+    ///
+    /// # Example
+    /// ```
+    /// use eggmock::egg::EGraph;
+    /// use eggmock::{ChoiceNetwork, Mig, MigLanguage, Network};
+    ///
+    /// // maj(a, b, c) is equivalent to the inverted maj(!a, !b, !c)
+    /// let mut egraph = EGraph::<MigLanguage, ()>::default();
+    /// let [a, b, c] = [0, 1, 2].map(|input| egraph.add(MigLanguage::Input(input)));
+    /// let maj = egraph.add(MigLanguage::Maj([a, b, c]));
+    /// let not_maj = egraph.add(MigLanguage::Not(maj));
+    /// let [na, nb, nc] = [a, b, c].map(|input| egraph.add(MigLanguage::Not(input)));
+    /// let inverted_maj = egraph.add(MigLanguage::Maj([na, nb, nc]));
+    /// egraph.union(not_maj, inverted_maj);
+    /// egraph.rebuild();
+    ///
+    /// // the class is represented by the inverted maj(a, b, c), so the choice is inverted as well
+    /// let network = ChoiceNetwork::from_egraph(&egraph, &[inverted_maj]);
+    /// let output = network.outputs().next().unwrap();
+    /// assert!(output.is_inverted());
+    /// let choices = network.choices(output.node_id());
+    /// assert_eq!(choices.len(), 1);
+    /// assert!(choices[0].is_inverted());
+    /// assert!(matches!(network.node(choices[0].node_id()), Mig::Maj(_)));
+    ///
+    /// // the phase survives sending the choices to another receiver
+    /// let sent = network.send_with_choices(ChoiceNetwork::default());
+    /// let output = sent.outputs().next().unwrap();
+    /// assert!(sent.choices(output.node_id())[0].is_inverted());
+    /// ```
+    pub fn from_egraph<L, A>(egraph: &EGraph<L, A>, roots: &[egg::Id]) -> Self
+    where
+        L: NetworkLanguage<Node = N>,
+        A: Analysis<L>,
+    {
+        let mut classes = Vec::new();
+        let mut visited = FxHashSet::default();
+        let mut remaining = Vec::from_iter(roots.iter().map(|id| egraph.find(*id)));
+        while let Some(class) = remaining.pop() {
+            if !visited.insert(class) {
+                continue;
+            }
+            classes.push(class);
+            for node in &egraph[class].nodes {
+                remaining.extend(node.children().iter().map(|id| egraph.find(*id)));
+            }
+        }
+        classes.sort_unstable();
+
+        let mut network = Self::default();
+        let mut class_signals: FxHashMap<egg::Id, Signal> = FxHashMap::default();
+        let mut strash: FxHashMap<N, Id> = FxHashMap::default();
+        let mut processed: Vec<Vec<bool>> = classes
+            .iter()
+            .map(|class| vec![false; egraph[*class].len()])
+            .collect();
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (class_idx, class) in classes.iter().enumerate() {
+                for (node_idx, enode) in egraph[*class].nodes.iter().enumerate() {
+                    if processed[class_idx][node_idx]
+                        || !enode
+                            .children()
+                            .iter()
+                            .all(|child| class_signals.contains_key(&egraph.find(*child)))
+                    {
+                        continue;
+                    }
+                    processed[class_idx][node_idx] = true;
+                    let class_signal = class_signals.get(class).copied();
+                    let node = enode.to_node(|child| class_signals[&egraph.find(child)]);
+                    let Some(node) = node else {
+                        // nots do not add an implementation but may represent the class
+                        if class_signal.is_none() {
+                            let child = class_signals[&egraph.find(enode.children()[0])];
+                            class_signals.insert(*class, child.invert());
+                            changed = true;
+                        }
+                        continue;
+                    };
+                    match class_signal {
+                        None => {
+                            let id = match strash.get(&node) {
+                                Some(id) => {
+                                    // choice nodes must not be used as fanins or outputs
+                                    network.detach_choice(*id);
+                                    *id
+                                }
+                                None => network.add_node(node, &mut strash),
+                            };
+                            class_signals.insert(*class, Signal::new(id, false));
+                            changed = true;
+                        }
+                        Some(signal) => {
+                            let representative = signal.node_id();
+                            if strash.contains_key(&node)
+                                || node.inputs().iter().any(|s| s.node_id() >= representative)
+                            {
+                                continue;
+                            }
+                            let id = network.add_node(node, &mut strash);
+                            network.is_choice[u32::from(id) as usize] = true;
+                            network.choices[u32::from(representative) as usize]
+                                .push(Signal::new(id, signal.is_inverted()));
+                        }
+                    }
+                }
+            }
+        }
+
+        network.outputs = roots
+            .iter()
+            .map(|root| {
+                *class_signals
+                    .get(&egraph.find(*root))
+                    .expect("every root should be representable by an acyclic network")
+            })
+            .collect();
+        network.num_inputs = network
+            .nodes
+            .iter()
+            .filter_map(Node::input_index)
+            .map(|index| index + 1)
+            .max()
+            .unwrap_or(0);
+        network
+    }
+
+    /// Builds a choice network without any choices from the nodes that are reachable from the
+    /// outputs of the given network.
+    pub fn from_network(network: &(impl Network<Node = N> + ?Sized)) -> Self {
+        network.send(Self::default())
+    }
+
+    fn add_node(&mut self, node: N, strash: &mut FxHashMap<N, Id>) -> Id {
+        let id = self.push_node(node.clone());
+        strash.insert(node, id);
+        id
+    }
+
+    /// Turns the node with the given id into a regular node if it is a choice.
+    fn detach_choice(&mut self, id: Id) {
+        if !std::mem::take(&mut self.is_choice[u32::from(id) as usize]) {
+            return;
+        }
+        for choices in &mut self.choices {
+            choices.retain(|choice| choice.node_id() != id);
+        }
+    }
+
+    fn push_node(&mut self, node: N) -> Id {
+        let id = Id::from(self.nodes.len() as u32);
+        self.nodes.push(node);
+        self.choices.push(Vec::new());
+        self.is_choice.push(false);
+        id
+    }
+
+    /// Returns the number of nodes in this network, including choice nodes and nodes that are not
+    /// reachable from an output.
+    pub fn size(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns an iterator over all node ids in topological order.
+    pub fn ids(&self) -> impl Iterator<Item = Id> {
+        (0..self.nodes.len() as u32).map(Id::from)
+    }
+
+    /// Returns the choices of the node with the given id. Each returned signal is functionally
+    /// equivalent to the (non-inverted) node.
+    pub fn choices(&self, id: Id) -> &[Signal] {
+        &self.choices[u32::from(id) as usize]
+    }
+
+    /// Returns true iff the node with the given id is a choice of another node.
+    pub fn is_choice(&self, id: Id) -> bool {
+        self.is_choice[u32::from(id) as usize]
+    }
+
+    /// Sends all nodes of this network to the given receiver in topological order and then passes
+    /// the choices to [`Receiver::add_choice`].
+    ///
+    /// In contrast to [`Network::send`], which only sends the nodes reachable from the outputs,
+    /// this also sends the choice nodes and their fanin cones.
+    pub fn send_with_choices<R: Receiver<Node = N>>(&self, mut receiver: R) -> R::Result {
+        receiver.set_num_inputs(self.num_inputs);
+        let mut signals: Vec<Signal> = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let node = node.map_input_ids(|id| signals[u32::from(id) as usize]);
+            signals.push(receiver.create_node(node));
+        }
+        let map = |signal: Signal| signal.map_id(|id| signals[u32::from(id) as usize]);
+        for (id, choices) in self.choices.iter().enumerate() {
+            for choice in choices {
+                receiver.add_choice(signals[id], map(*choice));
+            }
+        }
+        let outputs = Vec::from_iter(self.outputs.iter().map(|output| map(*output)));
+        receiver.done(&outputs)
+    }
+}
+
+impl<N> Default for ChoiceNetwork<N> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            choices: Vec::new(),
+            is_choice: Vec::new(),
+            outputs: Vec::new(),
+            num_inputs: 0,
+        }
+    }
+}
+
+/// Receives a network with choices. Choices that may introduce a cycle, i.e. where the choice
+/// has a fanin that was not created before the node, are dropped. Choice nodes must not be used
+/// as fanins or outputs.
+impl<N: Node> Receiver for ChoiceNetwork<N> {
+    type Node = N;
+    type Result = Self;
+
+    fn create_node(&mut self, node: N) -> Signal {
+        Signal::new(self.push_node(node), false)
+    }
+
+    fn done(mut self, outputs: &[Signal]) -> Self {
+        self.outputs = outputs.to_vec();
+        self
+    }
+
+    fn set_num_inputs(&mut self, num_inputs: u64) {
+        self.num_inputs = num_inputs;
+    }
+
+    fn add_choice(&mut self, node: Signal, choice: Signal) {
+        let representative = node.node_id();
+        let choice_id = choice.node_id();
+        if choice_id == representative
+            || self.nodes[u32::from(choice_id) as usize]
+                .inputs()
+                .iter()
+                .any(|s| s.node_id() >= representative)
+        {
+            return;
+        }
+        self.is_choice[u32::from(choice_id) as usize] = true;
+        self.choices[u32::from(representative) as usize].push(Signal::new(
+            choice_id,
+            node.is_inverted() ^ choice.is_inverted(),
+        ));
+    }
+}
+
+impl<N: Node> Network for ChoiceNetwork<N> {
+    type Node = N;
+
+    fn outputs(&self) -> impl Iterator<Item = Signal> {
+        self.outputs.iter().copied()
+    }
+    fn node(&self, id: Id) -> Self::Node {
+        self.nodes[u32::from(id) as usize].clone()
+    }
+    fn num_inputs(&self) -> u64 {
+        self.num_inputs
+    }
+}
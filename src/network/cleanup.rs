@@ -0,0 +1,126 @@
+use rustc_hash::FxHashMap;
+
+use crate::{Id, MutableNetwork, Network, Node, Receiver, Signal, TopologicalView};
+
+/// The result of [`cleanup`].
+#[derive(Debug, Clone)]
+pub struct Cleanup<N: Node> {
+    /// The network without unreachable nodes. Its ids are dense, i.e. they range from zero to the
+    /// number of nodes, and in topological order.
+    pub network: MutableNetwork<N>,
+    /// Maps the ids of the nodes of the original network that are reachable from an output to
+    /// their ids in the cleaned-up network.
+    pub mapping: FxHashMap<Id, Id>,
+}
+
+/// Copies the nodes of the given network that are reachable from its outputs into a new network,
+/// which assigns ids in topological order without gaps. Structurally equal nodes are merged. The
+/// number of inputs, the registers and the names of the inputs and outputs are kept, even if an
+/// input is no longer used.
+///
+/// # Example
+/// ```
+/// use eggmock::{cleanup, simulate_truth_tables, Aig, Cleanup, MutableNetwork, Network, Node};
+/// use eggmock::{Id, Receiver, Register, Signal};
+///
+/// let mut aig = MutableNetwork::<Aig>::default();
+/// aig.set_num_inputs(3);
+/// aig.set_registers(&[Register { init: Some(true) }]);
+/// aig.set_input_name(2, "c");
+/// aig.set_input_name(3, "r");
+/// let [a, b, r] = [0, 1, 3].map(|input| aig.add_node(Aig::Input(input)));
+/// let ab = aig.add_node(Aig::And([a, b]));
+/// let y = aig.add_node(Aig::And([ab, r]));
+/// let unused = aig.add_node(Aig::And([a, r]));
+/// aig.add_output(y);
+/// // the input of the register
+/// aig.add_output(ab.invert());
+/// aig.set_output_name(0, "y");
+/// // afterwards, `ab` uses a node with a larger id
+/// let br = aig.add_node(Aig::And([b, r.invert()]));
+/// aig.substitute_node(a.node_id(), br);
+///
+/// let Cleanup { network, mapping } = cleanup(&aig);
+/// // the ids are dense and in topological order
+/// assert!(network.ids().eq((0..5).map(Id::from)));
+/// for id in network.ids() {
+///     for input in network.node(id).inputs() {
+///         assert!(input.node_id() < id);
+///     }
+/// }
+/// assert_eq!(mapping.len(), 5);
+/// assert!(!mapping.contains_key(&a.node_id()));
+/// assert!(!mapping.contains_key(&unused.node_id()));
+/// let outputs = Vec::from_iter(
+///     aig.outputs()
+///         .map(|output| output.map_id(|id| Signal::new(mapping[&id], false))),
+/// );
+/// assert_eq!(Vec::from_iter(network.outputs()), outputs);
+/// assert_eq!(simulate_truth_tables(&network), simulate_truth_tables(&aig));
+///
+/// // the interface is kept, including the unused inputs
+/// assert_eq!(network.num_inputs(), 3);
+/// assert_eq!(network.register(0), Register { init: Some(true) });
+/// assert_eq!(network.input_name(2), Some("c"));
+/// assert_eq!(network.input_name(3), Some("r"));
+/// assert_eq!(network.output_name(0), Some("y"));
+///
+/// // compacting a network in place is equivalent
+/// let expected = simulate_truth_tables(&aig);
+/// assert_eq!(aig.compact(), mapping);
+/// assert_eq!(aig.size(), 5);
+/// assert_eq!(simulate_truth_tables(&aig), expected);
+/// assert_eq!(aig.input_name(3), Some("r"));
+/// ```
+pub fn cleanup<N: Network + ?Sized>(network: &N) -> Cleanup<N::Node> {
+    let mut cleaned = MutableNetwork::default();
+    cleaned.set_num_inputs(network.num_inputs());
+    if network.num_registers() != 0 {
+        let registers =
+            Vec::from_iter((0..network.num_registers()).map(|index| network.register(index)));
+        cleaned.set_registers(&registers);
+    }
+    for input in 0..network.num_inputs() + network.num_registers() {
+        if let Some(name) = network.input_name(input) {
+            cleaned.set_input_name(input, name);
+        }
+    }
+
+    let mut mapping: FxHashMap<Id, Id> = FxHashMap::default();
+    for id in TopologicalView::new(network).order() {
+        let node = network
+            .node(*id)
+            .map_input_ids(|input| Signal::new(mapping[&input], false));
+        mapping.insert(*id, cleaned.add_node(node).node_id());
+    }
+    let outputs = Vec::from_iter(
+        network
+            .outputs()
+            .map(|output| output.map_id(|id| Signal::new(mapping[&id], false))),
+    );
+    for output in 0..outputs.len() {
+        if let Some(name) = network.output_name(output) {
+            cleaned.set_output_name(output, name);
+        }
+    }
+    Cleanup {
+        network: cleaned.done(&outputs),
+        mapping,
+    }
+}
+
+impl<N: Node> MutableNetwork<N> {
+    /// Removes all nodes that are deleted or not reachable from an output and compacts the ids.
+    /// Returns the mapping from the previous to the new ids. See [`cleanup`].
+    ///
+    /// The registered callbacks are kept but not invoked.
+    pub fn compact(&mut self) -> FxHashMap<Id, Id> {
+        let Cleanup {
+            mut network,
+            mapping,
+        } = cleanup(self);
+        std::mem::swap(network.events_mut(), self.events_mut());
+        *self = network;
+        mapping
+    }
+}
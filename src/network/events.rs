@@ -0,0 +1,166 @@
+use std::fmt::{Debug, Formatter};
+
+use crate::Id;
+
+type NodeCallback<N> = Box<dyn FnMut(Id, &N)>;
+type ModifiedCallback<N> = Box<dyn FnMut(Id, &N, &N)>;
+
+/// Identifies a callback that was registered with [`NetworkEvents`]. Can be used to release the
+/// callback again.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct EventHandle {
+    kind: EventKind,
+    index: usize,
+}
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+enum EventKind {
+    Add,
+    Modified,
+    Delete,
+}
+
+/// Callbacks that are invoked when a network is modified, corresponding to *mockturtle*'s
+/// `network_events`. They allow keeping data that is associated with the nodes of a network, e.g.
+/// levels or simulation values, up to date while the network is changed in place.
+///
+/// Callbacks that need to access data outside of the closure, e.g. the data that is kept in sync,
+/// can share it via [`Rc<RefCell<_>>`](std::rc::Rc).
+///
+/// # Example
+/// ```
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+///
+/// use eggmock::{Aig, MutableNetwork, Network};
+///
+/// let log = Rc::new(RefCell::new(Vec::new()));
+/// let mut aig = MutableNetwork::<Aig>::default();
+/// let added = aig.events_mut().on_add({
+///     let log = log.clone();
+///     move |id, _| log.borrow_mut().push(format!("add {}", u32::from(id)))
+/// });
+/// aig.events_mut().on_modified({
+///     let log = log.clone();
+///     move |id, _, _| log.borrow_mut().push(format!("modify {}", u32::from(id)))
+/// });
+/// aig.events_mut().on_delete({
+///     let log = log.clone();
+///     move |id, _| log.borrow_mut().push(format!("delete {}", u32::from(id)))
+/// });
+///
+/// let [a, b, c] = [0, 1, 2].map(|input| aig.add_node(Aig::Input(input)));
+/// let ab = aig.add_node(Aig::And([a, b]));
+/// let abc = aig.add_node(Aig::And([ab, c]));
+/// aig.add_output(abc);
+/// // adding an existing node does not add anything
+/// aig.add_node(Aig::And([a, b]));
+/// assert_eq!(*log.borrow(), ["add 0", "add 1", "add 2", "add 3", "add 4"]);
+///
+/// log.borrow_mut().clear();
+/// aig.events_mut().release(added);
+/// let bc = aig.add_node(Aig::And([b, c]));
+/// // `abc` is modified to `b & c`, so it is merged into `bc`
+/// aig.substitute_node(ab.node_id(), b);
+/// assert_eq!(*log.borrow(), ["modify 4", "delete 3", "delete 4"]);
+/// assert_eq!(Vec::from_iter(aig.outputs()), [bc]);
+/// ```
+pub struct NetworkEvents<N> {
+    on_add: Vec<Option<NodeCallback<N>>>,
+    on_modified: Vec<Option<ModifiedCallback<N>>>,
+    on_delete: Vec<Option<NodeCallback<N>>>,
+}
+
+impl<N> NetworkEvents<N> {
+    /// Registers a callback that is invoked with the id and the node whenever a node is added.
+    pub fn on_add(&mut self, callback: impl FnMut(Id, &N) + 'static) -> EventHandle {
+        Self::register(&mut self.on_add, Box::new(callback), EventKind::Add)
+    }
+
+    /// Registers a callback that is invoked whenever the inputs of a node are changed in place. It
+    /// receives the id of the node, the node before and the node after the modification.
+    pub fn on_modified(&mut self, callback: impl FnMut(Id, &N, &N) + 'static) -> EventHandle {
+        Self::register(
+            &mut self.on_modified,
+            Box::new(callback),
+            EventKind::Modified,
+        )
+    }
+
+    /// Registers a callback that is invoked with the id and the node whenever a node is deleted.
+    pub fn on_delete(&mut self, callback: impl FnMut(Id, &N) + 'static) -> EventHandle {
+        Self::register(&mut self.on_delete, Box::new(callback), EventKind::Delete)
+    }
+
+    /// Removes the callback with the given handle. Does nothing if it was released before.
+    pub fn release(&mut self, handle: EventHandle) {
+        match handle.kind {
+            EventKind::Add => Self::unregister(&mut self.on_add, handle.index),
+            EventKind::Modified => Self::unregister(&mut self.on_modified, handle.index),
+            EventKind::Delete => Self::unregister(&mut self.on_delete, handle.index),
+        }
+    }
+
+    /// Removes all callbacks.
+    pub fn clear(&mut self) {
+        self.on_add.iter_mut().for_each(|callback| *callback = None);
+        self.on_modified
+            .iter_mut()
+            .for_each(|callback| *callback = None);
+        self.on_delete
+            .iter_mut()
+            .for_each(|callback| *callback = None);
+    }
+
+    pub(crate) fn added(&mut self, id: Id, node: &N) {
+        for callback in self.on_add.iter_mut().flatten() {
+            callback(id, node);
+        }
+    }
+
+    pub(crate) fn modified(&mut self, id: Id, previous: &N, node: &N) {
+        for callback in self.on_modified.iter_mut().flatten() {
+            callback(id, previous, node);
+        }
+    }
+
+    pub(crate) fn deleted(&mut self, id: Id, node: &N) {
+        for callback in self.on_delete.iter_mut().flatten() {
+            callback(id, node);
+        }
+    }
+
+    fn register<C>(callbacks: &mut Vec<Option<C>>, callback: C, kind: EventKind) -> EventHandle {
+        callbacks.push(Some(callback));
+        EventHandle {
+            kind,
+            index: callbacks.len() - 1,
+        }
+    }
+
+    fn unregister<C>(callbacks: &mut [Option<C>], index: usize) {
+        if let Some(callback) = callbacks.get_mut(index) {
+            *callback = None;
+        }
+    }
+}
+
+impl<N> Default for NetworkEvents<N> {
+    fn default() -> Self {
+        Self {
+            on_add: Vec::new(),
+            on_modified: Vec::new(),
+            on_delete: Vec::new(),
+        }
+    }
+}
+
+impl<N> Debug for NetworkEvents<N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NetworkEvents")
+            .field("on_add", &self.on_add.iter().flatten().count())
+            .field("on_modified", &self.on_modified.iter().flatten().count())
+            .field("on_delete", &self.on_delete.iter().flatten().count())
+            .finish()
+    }
+}
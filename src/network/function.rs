@@ -0,0 +1,99 @@
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+
+/// A type whose values can be used to simulate logic networks, such as [`bool`], [`u64`] (64
+/// patterns in parallel) or [`TruthTable`](crate::TruthTable).
+pub trait SimulationValue:
+    Clone + BitAnd<Output = Self> + BitOr<Output = Self> + BitXor<Output = Self> + Not<Output = Self>
+{
+}
+
+impl<T> SimulationValue for T where
+    T: Clone
+        + BitAnd<Output = Self>
+        + BitOr<Output = Self>
+        + BitXor<Output = Self>
+        + Not<Output = Self>
+{
+}
+
+/// The Boolean function computed by a gate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GateFunction {
+    /// Conjunction of all inputs.
+    And,
+    /// Disjunction of all inputs.
+    Or,
+    /// Parity of all inputs.
+    Xor,
+    /// Majority of three inputs.
+    Maj,
+    /// If-then-else, i.e. `(a & b) | (!a & c)`.
+    Ite,
+    /// Negated conjunction of all inputs.
+    Nand,
+    /// Negated disjunction of all inputs.
+    Nor,
+    /// Negated parity of all inputs.
+    Xnor,
+}
+
+impl GateFunction {
+    /// Derives the function of a gate from the name of the *mockturtle* method that creates it
+    /// without the `create_` prefix (e.g. `"and"` or `"nary_or"`). The fanin of n-ary gates may be
+    /// appended to their name (e.g. `"nary_and4"`). Returns [`None`] if the function is unknown.
+    pub const fn from_mockturtle_name(name: &str) -> Option<Self> {
+        let mut name = name.as_bytes();
+        if let [b'n', b'a', b'r', b'y', b'_', rest @ ..] = name {
+            name = rest;
+            while let [rest @ .., b'0'..=b'9'] = name {
+                name = rest;
+            }
+        }
+        match name {
+            b"and" => Some(Self::And),
+            b"or" => Some(Self::Or),
+            b"xor" | b"xor3" => Some(Self::Xor),
+            b"maj" => Some(Self::Maj),
+            b"ite" => Some(Self::Ite),
+            b"nand" => Some(Self::Nand),
+            b"nor" => Some(Self::Nor),
+            b"xnor" => Some(Self::Xnor),
+            _ => None,
+        }
+    }
+
+    /// Computes the output of a gate of this function from the values of its inputs.
+    ///
+    /// # Panics
+    /// Panics if the number of inputs does not fit the function (e.g. not three for
+    /// [`Maj`](Self::Maj) or [`Ite`](Self::Ite)) or if there are no inputs.
+    pub fn evaluate<V: SimulationValue>(&self, inputs: &[V]) -> V {
+        let fold = |op: fn(V, V) -> V| {
+            inputs
+                .iter()
+                .cloned()
+                .reduce(op)
+                .expect("gates should have at least one input")
+        };
+        match self {
+            Self::And => fold(|a, b| a & b),
+            Self::Or => fold(|a, b| a | b),
+            Self::Xor => fold(|a, b| a ^ b),
+            Self::Nand => !fold(|a, b| a & b),
+            Self::Nor => !fold(|a, b| a | b),
+            Self::Xnor => !fold(|a, b| a ^ b),
+            Self::Maj => {
+                let [a, b, c] = inputs else {
+                    panic!("majority gates should have three inputs")
+                };
+                (a.clone() & b.clone()) | (a.clone() & c.clone()) | (b.clone() & c.clone())
+            }
+            Self::Ite => {
+                let [i, t, e] = inputs else {
+                    panic!("if-then-else gates should have three inputs")
+                };
+                (i.clone() & t.clone()) | (!i.clone() & e.clone())
+            }
+        }
+    }
+}
@@ -7,20 +7,51 @@ use std::fmt::Debug;
 use std::hash::Hash;
 
 mod backwards;
+mod choices;
+mod cleanup;
+mod events;
+mod function;
+mod mutable;
+mod references;
+mod simulation;
+mod topological;
 
 pub use backwards::*;
+pub use choices::*;
+pub use cleanup::*;
+pub use events::*;
+pub use function::*;
+pub use mutable::*;
+pub use references::*;
+pub use simulation::*;
+pub use topological::*;
 
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 /// References a node in a network.
 pub struct Id(u32);
 
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 /// References a node by its id with a flag that indicates whether the signal from this node is
 /// inverted.
 pub struct Signal(u32);
 
+/// Describes a register (latch) of a sequential network.
+///
+/// Registers are transferred as part of the combinational network: the output of the `i`-th
+/// register is the primary input with index `num_inputs + i` (a pseudo-PI) and its input is the
+/// `i`-th of the last `num_registers` outputs (a pseudo-PO). This matches the combinational
+/// input/output order of *mockturtle*.
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Register {
+    /// The initial value of the register or [`None`] if it is unknown or irrelevant.
+    pub init: Option<bool>,
+}
+
 /// Describes a node of a logic network. This includes PIs, constant nodes and gates.
 pub trait Node: 'static + Debug + Sized + Clone + Hash + Eq {
     /// The type that contains descriptions of the gate types in this network.
@@ -53,6 +84,36 @@ pub trait Node: 'static + Debug + Sized + Clone + Hash + Eq {
     fn is_leaf(&self) -> bool {
         self.inputs().is_empty()
     }
+    /// Returns the index of the primary input represented by this node or [`None`] if this node is
+    /// not a PI.
+    fn input_index(&self) -> Option<u64>;
+    /// Returns the node that represents the primary input with the given index.
+    fn input(index: u64) -> Self;
+    /// Returns the constant-false node.
+    fn constant_false() -> Self;
+    /// Creates a gate of the given type with the given inputs. Returns [`None`] if the number of
+    /// inputs does not match the fanin of the gate type.
+    fn from_gate(gate: Self::Gates, inputs: &[Signal]) -> Option<Self>;
+    /// Creates a gate that computes the given function of the given inputs. Returns [`None`] if
+    /// the network has no such gate. By default, this uses the first gate type whose
+    /// [`GateType::function`] and fanin match.
+    fn from_function(function: GateFunction, inputs: &[Signal]) -> Option<Self> {
+        Self::Gates::VARIANTS
+            .iter()
+            .filter(|gate| {
+                gate.function() == Some(function) && gate.fanin() as usize == inputs.len()
+            })
+            .find_map(|gate| Self::from_gate(*gate, inputs))
+    }
+    /// Returns the gate type of this node or [`None`] if this node is not a gate (i.e. a PI or a
+    /// constant).
+    fn gate_type(&self) -> Option<Self::Gates>;
+    /// Computes the output of this node from the values of its inputs. Returns [`None`] if this
+    /// node is not a gate or its function is unknown. By default, this uses
+    /// [`GateType::function`].
+    fn evaluate<V: SimulationValue>(&self, inputs: &[V]) -> Option<V> {
+        Some(self.gate_type()?.function()?.evaluate(inputs))
+    }
 }
 
 /// Contains the [`Language`] type that can represent a Network.
@@ -74,6 +135,27 @@ pub trait NetworkLanguage: Language {
     fn not(id: egg::Id) -> Self;
 }
 
+impl Register {
+    /// Creates a register from *mockturtle*'s encoding of initial values (`0` and `1` for the
+    /// respective value, any other value if the initial value is unknown).
+    pub fn from_mockturtle_init(init: u8) -> Self {
+        let init = match init {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        };
+        Self { init }
+    }
+    /// Returns the initial value in *mockturtle*'s encoding. See
+    /// [`from_mockturtle_init`](Self::from_mockturtle_init).
+    pub fn mockturtle_init(&self) -> u8 {
+        match self.init {
+            Some(init) => init as u8,
+            None => 3,
+        }
+    }
+}
+
 impl Signal {
     const NOT_MASK: u32 = 1 << 31;
 
@@ -106,8 +188,17 @@ impl Signal {
     }
 }
 
+impl std::ops::Not for Signal {
+    type Output = Signal;
+
+    /// Returns the inverted signal, see [`invert`](Signal::invert).
+    fn not(self) -> Signal {
+        self.invert()
+    }
+}
+
 /// Contains description of the gates in a network, which is used for code generation.
-pub trait GateType: 'static + Sized {
+pub trait GateType: 'static + Sized + Debug + Copy + Eq + Hash {
     type Node: Node<Gates = Self>;
 
     /// Contains all gate types of the associated Network type.
@@ -125,6 +216,9 @@ pub trait GateType: 'static + Sized {
     /// Returns the name of the method on the *mockturtle* network implementation that checks
     /// whether a given node ID belongs to a gate of this type (e.g. `"is_and"`).
     fn mockturtle_is(&self) -> &'static str;
+    /// Returns the Boolean function of gates of this type or [`None`] if it is unknown. The
+    /// function is derived from the name of the *mockturtle* method that creates the gate.
+    fn function(&self) -> Option<GateFunction>;
 }
 
 impl Debug for Signal {
@@ -168,6 +262,34 @@ pub trait Network {
     fn outputs(&self) -> impl Iterator<Item = Signal>;
     /// Returns the node with the given id.
     fn node(&self, id: Id) -> Self::Node;
+    /// Returns the number of primary inputs of this network, not including register outputs. By
+    /// default, this is one more than the largest index of a PI that is reachable from an output,
+    /// so sequential networks have to override this.
+    fn num_inputs(&self) -> u64 {
+        self.iter()
+            .filter_map(|(_, node)| node.input_index())
+            .map(|index| index + 1)
+            .max()
+            .unwrap_or(0)
+    }
+    /// Returns the number of registers of this network. The last `num_registers` outputs are the
+    /// register inputs. See [`Register`] for details.
+    fn num_registers(&self) -> u64 {
+        0
+    }
+    /// Returns the register with the given index.
+    fn register(&self, _index: u64) -> Register {
+        Register::default()
+    }
+    /// Returns the name of the primary input with the given index, if it has one.
+    fn input_name(&self, _input: u64) -> Option<&str> {
+        None
+    }
+    /// Returns the name of the primary output at the given position in
+    /// [`outputs`](Self::outputs), if it has one.
+    fn output_name(&self, _output: usize) -> Option<&str> {
+        None
+    }
     /// Returns an iterator over all nodes that are reachable from an output and their ids.
     fn iter(&self) -> impl Iterator<Item = (Id, Self::Node)> + '_ {
         NetworkNodeIterator {
@@ -180,6 +302,16 @@ pub trait Network {
     fn send<R: Receiver<Node = Self::Node>>(&self, mut receiver: R) -> R::Result {
         let mut src_to_dest: FxHashMap<Id, Signal> = FxHashMap::default();
         let mut path = Vec::new();
+        receiver.set_num_inputs(self.num_inputs());
+        if self.num_registers() != 0 {
+            let registers = Vec::from_iter((0..self.num_registers()).map(|i| self.register(i)));
+            receiver.set_registers(&registers);
+        }
+        for input in 0..self.num_inputs() + self.num_registers() {
+            if let Some(name) = self.input_name(input) {
+                receiver.set_input_name(input, name);
+            }
+        }
         for signal in self.outputs() {
             let mut node_id = signal.node_id();
             let mut node = self.node(node_id);
@@ -209,6 +341,11 @@ pub trait Network {
             self.outputs()
                 .map(|signal| signal.map_id(|id| src_to_dest[&id])),
         );
+        for output in 0..outputs.len() {
+            if let Some(name) = self.output_name(output) {
+                receiver.set_output_name(output, name);
+            }
+        }
         receiver.done(outputs.as_slice())
     }
 
@@ -0,0 +1,439 @@
+use rustc_hash::FxHashMap;
+
+use crate::{
+    Id, Interface, Network, NetworkEvents, NetworkWithBackwardEdges, Node, Receiver, Register,
+    Signal,
+};
+
+/// An owned network that can be modified in place.
+///
+/// Nodes are structurally hashed, i.e. adding a node that already exists returns the existing
+/// node. The network maintains the fanouts, the reference counts (see
+/// [`fanout_size`](NetworkWithBackwardEdges::fanout_size)) and the level of each node while it is
+/// modified. Deleted nodes keep their ids but are no longer part of the network.
+///
+/// Callbacks that are invoked when nodes are added, modified or deleted can be registered with
+/// [`events_mut`](Self::events_mut). They are not transferred to clones of the network.
+///
+/// A network can be built with the [`Receiver`] implementation, e.g. with
+/// `network.send(MutableNetwork::default())`.
+#[derive(Debug)]
+pub struct MutableNetwork<N: Node> {
+    nodes: Vec<N>,
+    dead: Vec<bool>,
+    fanouts: Vec<Vec<Id>>,
+    references: Vec<usize>,
+    levels: Vec<u32>,
+    outputs: Vec<Signal>,
+    strash: FxHashMap<N, Id>,
+    interface: Interface,
+    events: NetworkEvents<N>,
+}
+
+impl<N: Node> Default for MutableNetwork<N> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            dead: Vec::new(),
+            fanouts: Vec::new(),
+            references: Vec::new(),
+            levels: Vec::new(),
+            outputs: Vec::new(),
+            strash: FxHashMap::default(),
+            interface: Interface::default(),
+            events: NetworkEvents::default(),
+        }
+    }
+}
+
+impl<N: Node> Clone for MutableNetwork<N> {
+    fn clone(&self) -> Self {
+        Self {
+            nodes: self.nodes.clone(),
+            dead: self.dead.clone(),
+            fanouts: self.fanouts.clone(),
+            references: self.references.clone(),
+            levels: self.levels.clone(),
+            outputs: self.outputs.clone(),
+            strash: self.strash.clone(),
+            interface: self.interface.clone(),
+            events: NetworkEvents::default(),
+        }
+    }
+}
+
+impl<N: Node> MutableNetwork<N> {
+    fn index(id: Id) -> usize {
+        u32::from(id) as usize
+    }
+
+    /// Returns the number of nodes that were ever added, including deleted nodes. All ids are
+    /// smaller than this number.
+    pub fn size(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the ids of all nodes that are not deleted in the order of their creation.
+    pub fn ids(&self) -> impl Iterator<Item = Id> + '_ {
+        (0..self.nodes.len() as u32)
+            .map(Id::from)
+            .filter(|id| !self.is_dead(*id))
+    }
+
+    /// Returns the number of gates that are not deleted.
+    pub fn num_gates(&self) -> usize {
+        self.ids()
+            .filter(|id| !self.node_ref(*id).is_leaf())
+            .count()
+    }
+
+    /// Returns a reference to the node with the given id.
+    pub fn node_ref(&self, id: Id) -> &N {
+        &self.nodes[Self::index(id)]
+    }
+
+    pub fn is_dead(&self, id: Id) -> bool {
+        self.dead[Self::index(id)]
+    }
+
+    /// Returns the gates that use the given node as an input. Each gate is contained once, even if
+    /// it uses the node as several of its inputs.
+    pub fn fanouts(&self, id: Id) -> &[Id] {
+        &self.fanouts[Self::index(id)]
+    }
+
+    /// Returns the level of the given node, i.e. the number of gates on the longest path from a
+    /// leaf to it.
+    pub fn level(&self, id: Id) -> u32 {
+        self.levels[Self::index(id)]
+    }
+
+    /// Returns the largest level of an output.
+    pub fn depth(&self) -> u32 {
+        self.outputs
+            .iter()
+            .map(|output| self.level(output.node_id()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the interface of the network, which contains the names of its inputs and outputs.
+    pub fn interface(&self) -> &Interface {
+        &self.interface
+    }
+
+    pub fn interface_mut(&mut self) -> &mut Interface {
+        &mut self.interface
+    }
+
+    pub fn events(&self) -> &NetworkEvents<N> {
+        &self.events
+    }
+
+    /// Returns the callbacks of the network, which can be used to register new callbacks.
+    pub fn events_mut(&mut self) -> &mut NetworkEvents<N> {
+        &mut self.events
+    }
+
+    /// Adds the given node to the network and returns a signal pointing to it. If the network
+    /// contains an equal node already, no node is added and the existing node is returned instead.
+    ///
+    /// # Panics
+    /// Panics if an input of the node is deleted.
+    pub fn add_node(&mut self, node: N) -> Signal {
+        if let Some(id) = self.strash.get(&node) {
+            return Signal::new(*id, false);
+        }
+        let id = Id::from(self.nodes.len() as u32);
+        let mut level = 0;
+        for input in node.inputs() {
+            assert!(
+                !self.is_dead(input.node_id()),
+                "inputs of added nodes should not be deleted"
+            );
+            self.references[Self::index(input.node_id())] += 1;
+            level = level.max(self.level(input.node_id()) + 1);
+        }
+        for fanin in Self::unique_fanins(&node) {
+            self.fanouts[Self::index(fanin)].push(id);
+        }
+        if let Some(input) = node.input_index() {
+            self.include_input(input);
+        }
+        self.strash.insert(node.clone(), id);
+        self.nodes.push(node);
+        self.dead.push(false);
+        self.fanouts.push(Vec::new());
+        self.references.push(0);
+        self.levels.push(level);
+        self.events.added(id, &self.nodes[Self::index(id)]);
+        Signal::new(id, false)
+    }
+
+    /// Grows the number of primary inputs such that the input with the given index exists. The
+    /// indices directly after the primary inputs belong to the register outputs (see [`Register`]),
+    /// so they do not count as primary inputs.
+    fn include_input(&mut self, input: u64) {
+        let num_registers = self.interface.registers.len() as u64;
+        let num_inputs = self.interface.num_inputs.get_or_insert(0);
+        if input >= *num_inputs + num_registers {
+            *num_inputs = input + 1 - num_registers;
+        }
+    }
+
+    /// Adds a primary output and returns its position.
+    pub fn add_output(&mut self, signal: Signal) -> usize {
+        self.references[Self::index(signal.node_id())] += 1;
+        self.outputs.push(signal);
+        self.outputs.len() - 1
+    }
+
+    /// Replaces all uses of the node `old` by `new`, i.e. its uses as a gate input as well as
+    /// its uses as a primary output, and deletes `old` if it is a gate.
+    ///
+    /// Gates whose inputs change are updated in place, so their ids are kept. If an updated gate
+    /// becomes structurally equal to another gate, it is substituted by the other gate as well.
+    /// The levels of all affected nodes are updated. The `on_modified` callbacks are invoked for
+    /// each updated gate and the `on_delete` callbacks for each deleted gate.
+    ///
+    /// `new` must not depend on `old`, as this would introduce a cycle.
+    ///
+    /// # Example
+    /// ```
+    /// use eggmock::{simulate_truth_tables, Aig, MutableNetwork, Network, TruthTable};
+    ///
+    /// let mut aig = MutableNetwork::<Aig>::default();
+    /// let [a, b, c, d] = [0, 1, 2, 3].map(|input| aig.add_node(Aig::Input(input)));
+    /// let ac = aig.add_node(Aig::And([a, c]));
+    /// let bc = aig.add_node(Aig::And([b, c]));
+    /// let acd = aig.add_node(Aig::And([ac, d]));
+    /// let bcd = aig.add_node(Aig::And([bc, d]));
+    /// aig.add_output(acd);
+    /// aig.add_output(bcd.invert());
+    ///
+    /// // replacing `a` by `b` makes `ac` equal to `bc` and then `acd` equal to `bcd`
+    /// aig.substitute_node(a.node_id(), b);
+    /// assert!(aig.is_dead(ac.node_id()) && aig.is_dead(acd.node_id()));
+    /// assert!(!aig.is_dead(a.node_id()));
+    /// assert_eq!(aig.fanouts(bc.node_id()), [bcd.node_id()]);
+    /// assert_eq!(Vec::from_iter(aig.outputs()), [bcd, bcd.invert()]);
+    /// assert_eq!(aig.num_gates(), 2);
+    /// let bcd_function = [1, 2, 3]
+    ///     .map(|var| TruthTable::nth_var(4, var))
+    ///     .into_iter()
+    ///     .reduce(|a, b| a & b)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     simulate_truth_tables(&aig),
+    ///     [bcd_function.clone(), !bcd_function]
+    /// );
+    ///
+    /// // replacing a gate by a leaf deletes it and updates the levels of its fanouts
+    /// assert_eq!(aig.level(bcd.node_id()), 2);
+    /// aig.substitute_node(bc.node_id(), c);
+    /// assert!(aig.is_dead(bc.node_id()));
+    /// assert_eq!(aig.node_ref(bcd.node_id()), &Aig::And([c, d]));
+    /// assert_eq!(aig.level(bcd.node_id()), 1);
+    /// assert_eq!(aig.depth(), 1);
+    ///
+    /// // deleting an unused gate also deletes its unused fanins, but keeps the leaves
+    /// let ab = aig.add_node(Aig::And([a, b]));
+    /// let abd = aig.add_node(Aig::And([ab, d]));
+    /// aig.delete_node(abd.node_id());
+    /// assert!(aig.is_dead(abd.node_id()) && aig.is_dead(ab.node_id()));
+    /// assert!(!aig.is_dead(a.node_id()));
+    /// assert_eq!(aig.fanouts(d.node_id()), [bcd.node_id()]);
+    /// assert_eq!(aig.num_gates(), 1);
+    /// ```
+    pub fn substitute_node(&mut self, old: Id, new: Signal) {
+        let mut pending = vec![(old, new)];
+        while let Some((old, new)) = pending.pop() {
+            if old == new.node_id() || self.is_dead(old) {
+                continue;
+            }
+            for fanout in std::mem::take(&mut self.fanouts[Self::index(old)]) {
+                let node = self.nodes[Self::index(fanout)].clone();
+                let updated = node.map_input_signals(|input| {
+                    if input.node_id() == old {
+                        input.replace_id(new)
+                    } else {
+                        input
+                    }
+                });
+                let uses = node
+                    .inputs()
+                    .iter()
+                    .filter(|input| input.node_id() == old)
+                    .count();
+                self.references[Self::index(old)] -= uses;
+                self.references[Self::index(new.node_id())] += uses;
+                let new_fanouts = &mut self.fanouts[Self::index(new.node_id())];
+                if !new_fanouts.contains(&fanout) {
+                    new_fanouts.push(fanout);
+                }
+
+                if self.strash.get(&node) == Some(&fanout) {
+                    self.strash.remove(&node);
+                }
+                match self.strash.get(&updated) {
+                    Some(existing) if *existing != fanout => {
+                        pending.push((fanout, Signal::new(*existing, false)));
+                    }
+                    _ => {
+                        self.strash.insert(updated.clone(), fanout);
+                    }
+                }
+                self.nodes[Self::index(fanout)] = updated;
+                self.events
+                    .modified(fanout, &node, &self.nodes[Self::index(fanout)]);
+                self.update_levels(fanout);
+            }
+            self.replace_in_outputs(old, new);
+            if self.references[Self::index(old)] == 0 && !self.node_ref(old).is_leaf() {
+                self.delete_node(old);
+            }
+        }
+    }
+
+    /// Replaces all primary outputs that point to `old` by `new`, keeping their inversion.
+    pub fn replace_in_outputs(&mut self, old: Id, new: Signal) {
+        for output in &mut self.outputs {
+            if output.node_id() == old {
+                *output = output.replace_id(new);
+                self.references[Self::index(old)] -= 1;
+                self.references[Self::index(new.node_id())] += 1;
+            }
+        }
+    }
+
+    /// Deletes the given node and, recursively, all gates that are no longer referenced
+    /// afterwards. Leaves that are no longer referenced are kept.
+    ///
+    /// # Panics
+    /// Panics if the node is still referenced.
+    pub fn delete_node(&mut self, id: Id) {
+        assert_eq!(
+            self.references[Self::index(id)],
+            0,
+            "deleted nodes should not be referenced"
+        );
+        let mut remaining = vec![id];
+        while let Some(id) = remaining.pop() {
+            if self.is_dead(id) {
+                continue;
+            }
+            self.dead[Self::index(id)] = true;
+            let node = self.nodes[Self::index(id)].clone();
+            if self.strash.get(&node) == Some(&id) {
+                self.strash.remove(&node);
+            }
+            self.events.deleted(id, &node);
+            for input in node.inputs() {
+                self.references[Self::index(input.node_id())] -= 1;
+            }
+            for fanin in Self::unique_fanins(&node) {
+                self.fanouts[Self::index(fanin)].retain(|fanout| *fanout != id);
+                if self.references[Self::index(fanin)] == 0 && !self.node_ref(fanin).is_leaf() {
+                    remaining.push(fanin);
+                }
+            }
+        }
+    }
+
+    /// Recomputes the level of the given gate and propagates changes to its transitive fanout.
+    fn update_levels(&mut self, id: Id) {
+        let mut remaining = vec![id];
+        while let Some(id) = remaining.pop() {
+            let level = self
+                .node_ref(id)
+                .inputs()
+                .iter()
+                .map(|input| self.level(input.node_id()) + 1)
+                .max()
+                .unwrap_or(0);
+            if level != self.level(id) {
+                self.levels[Self::index(id)] = level;
+                remaining.extend(self.fanouts(id).iter().copied());
+            }
+        }
+    }
+
+    fn unique_fanins(node: &N) -> Vec<Id> {
+        let mut fanins = Vec::from_iter(node.inputs().iter().map(Signal::node_id));
+        fanins.sort_unstable();
+        fanins.dedup();
+        fanins
+    }
+}
+
+impl<N: Node> Receiver for MutableNetwork<N> {
+    type Node = N;
+    type Result = Self;
+
+    fn create_node(&mut self, node: N) -> Signal {
+        self.add_node(node)
+    }
+
+    fn done(mut self, outputs: &[Signal]) -> Self {
+        for output in outputs {
+            self.add_output(*output);
+        }
+        self
+    }
+
+    fn set_num_inputs(&mut self, num_inputs: u64) {
+        self.interface.num_inputs = Some(num_inputs);
+    }
+
+    fn set_registers(&mut self, registers: &[Register]) {
+        self.interface.registers = registers.to_vec();
+    }
+
+    fn set_input_name(&mut self, input: u64, name: &str) {
+        self.interface.names.set_input(input, name);
+    }
+
+    fn set_output_name(&mut self, output: usize, name: &str) {
+        self.interface.names.set_output(output, name);
+    }
+}
+
+impl<N: Node> Network for MutableNetwork<N> {
+    type Node = N;
+
+    fn outputs(&self) -> impl Iterator<Item = Signal> {
+        self.outputs.iter().copied()
+    }
+    fn node(&self, id: Id) -> N {
+        self.nodes[Self::index(id)].clone()
+    }
+    fn num_inputs(&self) -> u64 {
+        self.interface.num_inputs.unwrap_or(0)
+    }
+    fn num_registers(&self) -> u64 {
+        self.interface.registers.len() as u64
+    }
+    fn register(&self, index: u64) -> Register {
+        self.interface.registers[index as usize]
+    }
+    fn input_name(&self, input: u64) -> Option<&str> {
+        self.interface.names.input(input)
+    }
+    fn output_name(&self, output: usize) -> Option<&str> {
+        self.interface.names.output(output)
+    }
+}
+
+impl<N: Node> NetworkWithBackwardEdges for MutableNetwork<N> {
+    fn node_outputs(&self, id: Id) -> impl Iterator<Item = Id> + '_ {
+        self.fanouts(id).iter().copied()
+    }
+    /// Returns the leaves that are referenced by a gate or an output.
+    fn leafs(&self) -> impl Iterator<Item = Id> + '_ {
+        self.ids()
+            .filter(|id| self.node_ref(*id).is_leaf() && self.references[Self::index(*id)] > 0)
+    }
+    fn fanout_size(&self, id: Id) -> usize {
+        self.references[Self::index(id)]
+    }
+}
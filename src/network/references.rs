@@ -0,0 +1,251 @@
+use rustc_hash::FxHashMap;
+
+use crate::{Id, NetworkWithBackwardEdges, Node, TopologicalView};
+
+/// Reference counts of the nodes of a network that can be modified to simulate the removal or
+/// addition of logic, e.g. to compute maximum fanout-free cones (MFFCs). Initially, the count of a
+/// node is its [`fanout_size`](NetworkWithBackwardEdges::fanout_size), i.e. it includes the
+/// references of primary outputs and duplicate fanins.
+///
+/// # Example
+/// ```
+/// use eggmock::{Aig, FanoutFreeRegions, MutableNetwork, ReferenceCounts};
+///
+/// let mut aig = MutableNetwork::<Aig>::default();
+/// let [a, b, c, d] = [0, 1, 2, 3].map(|input| aig.add_node(Aig::Input(input)));
+/// let ab = aig.add_node(Aig::And([a, b]));
+/// // a duplicate fanin counts as two references
+/// let twice = aig.add_node(Aig::And([ab, ab]));
+/// let abc = aig.add_node(Aig::And([twice, c]));
+/// let abcd = aig.add_node(Aig::And([abc, d]));
+/// let bc = aig.add_node(Aig::And([b, c]));
+/// let root = aig.add_node(Aig::And([abcd, bc.invert()]));
+/// aig.add_output(root);
+/// aig.add_output(twice);
+///
+/// let mut refs = ReferenceCounts::new(&aig);
+/// assert_eq!(refs.references(ab.node_id()), 2);
+/// // referenced by a gate and by an output
+/// assert_eq!(refs.references(twice.node_id()), 2);
+///
+/// // the cone of `root` stops at `twice`, which is also used by an output
+/// let mut mffc = refs.mffc(root.node_id());
+/// mffc.sort();
+/// let mut expected = [abc, abcd, bc, root].map(|signal| signal.node_id());
+/// expected.sort();
+/// assert_eq!(mffc, expected);
+/// assert!(!refs.is_in_mffc(root.node_id(), twice.node_id()));
+/// assert!(refs.is_in_mffc(twice.node_id(), ab.node_id()));
+/// assert_eq!(refs.mffc_size(twice.node_id()), 2);
+/// assert_eq!(refs.mffc_size(a.node_id()), 0);
+///
+/// // removing `root` releases one reference of `twice`, adding it again restores the counts
+/// assert_eq!(refs.deref_node(root.node_id()), 4);
+/// assert_eq!(refs.references(twice.node_id()), 1);
+/// assert_eq!(refs.ref_node(root.node_id()), 4);
+/// assert_eq!(refs.references(twice.node_id()), 2);
+///
+/// // `ab` has two references and `twice` is used by an output, so both are roots of a region
+/// let regions = FanoutFreeRegions::new(&aig);
+/// assert_eq!(regions.roots(), [ab, twice, root].map(|signal| signal.node_id()));
+/// assert_eq!(regions.root(abc.node_id()), Some(root.node_id()));
+/// assert_eq!(regions.root(a.node_id()), None);
+/// assert_eq!(regions.members(root.node_id()).count(), 4);
+/// let mut leaves = regions.leaves(&aig, root.node_id());
+/// leaves.sort();
+/// assert_eq!(leaves, [b, c, d, twice].map(|signal| signal.node_id()));
+/// ```
+pub struct ReferenceCounts<'a, N: ?Sized> {
+    network: &'a N,
+    references: FxHashMap<Id, usize>,
+}
+
+impl<'a, N: NetworkWithBackwardEdges + ?Sized> ReferenceCounts<'a, N> {
+    pub fn new(network: &'a N) -> Self {
+        let references = network
+            .iter()
+            .map(|(id, _)| (id, network.fanout_size(id)))
+            .collect();
+        Self {
+            network,
+            references,
+        }
+    }
+
+    /// Returns the current reference count of the given node.
+    pub fn references(&self, id: Id) -> usize {
+        self.references.get(&id).copied().unwrap_or(0)
+    }
+
+    /// Dereferences the fanins of the given gate and, recursively, the fanins of all gates whose
+    /// reference count drops to zero. Returns the number of gates that are no longer referenced,
+    /// including the given gate. Returns zero for leaves.
+    pub fn deref_node(&mut self, id: Id) -> usize {
+        self.deref_collect(id, |_| {})
+    }
+
+    /// Reverts [`deref_node`](Self::deref_node) by referencing the fanins of the given gate and,
+    /// recursively, the fanins of all gates that become referenced again. Returns the number of
+    /// gates that are referenced again, including the given gate.
+    pub fn ref_node(&mut self, id: Id) -> usize {
+        let node = self.network.node(id);
+        if node.is_leaf() {
+            return 0;
+        }
+        let mut count = 1;
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            for input in node.inputs() {
+                let references = self.references.entry(input.node_id()).or_insert(0);
+                *references += 1;
+                if *references == 1 {
+                    let input = self.network.node(input.node_id());
+                    if !input.is_leaf() {
+                        count += 1;
+                        stack.push(input);
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    /// Returns the gates of the maximum fanout-free cone of the given gate, i.e. the gates that
+    /// are only used (transitively) by the given gate, starting with the gate itself. Returns an
+    /// empty list for leaves. The reference counts are unchanged afterwards.
+    pub fn mffc(&mut self, id: Id) -> Vec<Id> {
+        let mut members = Vec::new();
+        self.deref_collect(id, |member| members.push(member));
+        if !members.is_empty() {
+            self.ref_node(id);
+        }
+        members
+    }
+
+    /// Returns the number of gates in the maximum fanout-free cone of the given gate. See
+    /// [`mffc`](Self::mffc).
+    pub fn mffc_size(&mut self, id: Id) -> usize {
+        let size = self.deref_node(id);
+        if size != 0 {
+            self.ref_node(id);
+        }
+        size
+    }
+
+    /// Returns true iff `member` is part of the maximum fanout-free cone of `root`.
+    pub fn is_in_mffc(&mut self, root: Id, member: Id) -> bool {
+        self.mffc(root).contains(&member)
+    }
+
+    fn deref_collect(&mut self, id: Id, mut collect: impl FnMut(Id)) -> usize {
+        let node = self.network.node(id);
+        if node.is_leaf() {
+            return 0;
+        }
+        collect(id);
+        let mut count = 1;
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            for input in node.inputs() {
+                let references = self
+                    .references
+                    .get_mut(&input.node_id())
+                    .filter(|references| **references > 0)
+                    .expect("dereferenced node should be referenced");
+                *references -= 1;
+                if *references == 0 {
+                    let node = self.network.node(input.node_id());
+                    if !node.is_leaf() {
+                        collect(input.node_id());
+                        count += 1;
+                        stack.push(node);
+                    }
+                }
+            }
+        }
+        count
+    }
+}
+
+/// A partitioning of the gates of a network into fanout-free regions (FFRs). Each region has a
+/// root, which is a gate that is referenced by a primary output or by more or less than one gate
+/// input, and contains all gates whose references lead only to the root.
+pub struct FanoutFreeRegions {
+    roots: Vec<Id>,
+    /// The gates in topological order.
+    gates: Vec<Id>,
+    root_of: FxHashMap<Id, Id>,
+}
+
+impl FanoutFreeRegions {
+    pub fn new<N>(network: &N) -> Self
+    where
+        N: NetworkWithBackwardEdges + ?Sized,
+    {
+        let topological = TopologicalView::new(network);
+        let gates = Vec::from_iter(
+            topological
+                .order()
+                .iter()
+                .copied()
+                .filter(|id| !network.node(*id).is_leaf()),
+        );
+        let mut root_of = FxHashMap::default();
+        let mut roots = Vec::new();
+        for id in gates.iter().rev() {
+            let mut fanouts = network.node_outputs(*id);
+            let root = match (network.fanout_size(*id), fanouts.next(), fanouts.next()) {
+                // the single reference is a gate input and not a primary output
+                (1, Some(fanout), None) => root_of[&fanout],
+                _ => {
+                    roots.push(*id);
+                    *id
+                }
+            };
+            root_of.insert(*id, root);
+        }
+        roots.reverse();
+        Self {
+            roots,
+            gates,
+            root_of,
+        }
+    }
+
+    /// Returns the roots of the regions in topological order.
+    pub fn roots(&self) -> &[Id] {
+        &self.roots
+    }
+
+    /// Returns the root of the region that contains the given gate or [`None`] if the node is not
+    /// a gate that is reachable from an output.
+    pub fn root(&self, id: Id) -> Option<Id> {
+        self.root_of.get(&id).copied()
+    }
+
+    /// Returns the gates of the region with the given root in topological order.
+    pub fn members(&self, root: Id) -> impl Iterator<Item = Id> + '_ {
+        self.gates
+            .iter()
+            .copied()
+            .filter(move |id| self.root_of[id] == root)
+    }
+
+    /// Returns the leaves of the region with the given root, i.e. the nodes outside of the region
+    /// that are inputs of its gates.
+    pub fn leaves<N>(&self, network: &N, root: Id) -> Vec<Id>
+    where
+        N: NetworkWithBackwardEdges + ?Sized,
+    {
+        let mut leaves = Vec::new();
+        for member in self.members(root) {
+            for input in network.node(member).inputs() {
+                let input = input.node_id();
+                if self.root(input) != Some(root) && !leaves.contains(&input) {
+                    leaves.push(input);
+                }
+            }
+        }
+        leaves
+    }
+}
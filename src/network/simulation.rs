@@ -0,0 +1,61 @@
+use rustc_hash::FxHashMap;
+
+use crate::{Id, Network, Node, SimulationValue, TopologicalView, TruthTable};
+
+/// Simulates the given network and returns the values of its outputs.
+///
+/// `inputs` contains the values of the primary inputs, followed by the values of the register
+/// outputs for sequential networks (see [`Register`](crate::Register)), and `constant_false` is
+/// the value of the constant-false node.
+///
+/// # Panics
+/// Panics if there are too few input values or if a gate has an unknown function (see
+/// [`Node::evaluate`]).
+pub fn simulate<N, V>(network: &N, constant_false: V, inputs: &[V]) -> Vec<V>
+where
+    N: Network + ?Sized,
+    V: SimulationValue,
+{
+    let mut values: FxHashMap<Id, V> = FxHashMap::default();
+    for id in TopologicalView::new(network).order() {
+        let node = network.node(*id);
+        let value = if let Some(input) = node.input_index() {
+            inputs[input as usize].clone()
+        } else if node.is_leaf() {
+            constant_false.clone()
+        } else {
+            let fanins = Vec::from_iter(node.inputs().iter().map(|input| {
+                let value = values[&input.node_id()].clone();
+                if input.is_inverted() {
+                    !value
+                } else {
+                    value
+                }
+            }));
+            node.evaluate(&fanins)
+                .expect("simulated gates should have a known function")
+        };
+        values.insert(*id, value);
+    }
+    network
+        .outputs()
+        .map(|output| {
+            let value = values[&output.node_id()].clone();
+            if output.is_inverted() {
+                !value
+            } else {
+                value
+            }
+        })
+        .collect()
+}
+
+/// Computes the functions of the outputs of the given network in terms of its inputs, including
+/// the register outputs of sequential networks. Variable `i` of the truth tables is input `i`.
+///
+/// Useful to check small networks for equivalence.
+pub fn simulate_truth_tables<N: Network + ?Sized>(network: &N) -> Vec<TruthTable> {
+    let num_vars = (network.num_inputs() + network.num_registers()) as u32;
+    let inputs = Vec::from_iter((0..num_vars).map(|var| TruthTable::nth_var(num_vars, var)));
+    simulate(network, TruthTable::new(num_vars), &inputs)
+}
@@ -0,0 +1,269 @@
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{Id, Network, Node, Receiver, Register, Signal};
+
+/// A view on a network whose [`iter`](Network::iter) returns the nodes that are reachable from the
+/// outputs in topological order, i.e. every node comes after all of its inputs. The order is
+/// computed once when the view is created.
+///
+/// # Example
+/// ```
+/// use eggmock::{ripple_carry_adder, MutableNetwork, Network, Node, TopologicalView, Xag};
+///
+/// let mut adder = ripple_carry_adder(MutableNetwork::<Xag>::default(), 4);
+/// let inputs = [0, 1].map(|input| adder.add_node(Xag::Input(input)));
+/// let unused = adder.add_node(Xag::And(inputs));
+///
+/// let view = TopologicalView::new(&adder);
+/// for (position, id) in view.order().iter().enumerate() {
+///     assert_eq!(view.position(*id), Some(position));
+///     for input in adder.node(*id).inputs() {
+///         assert!(view.position(input.node_id()).unwrap() < position);
+///     }
+/// }
+/// // nodes that are not reachable from an output are not contained
+/// assert_eq!(view.position(unused.node_id()), None);
+/// assert_eq!(view.size(), adder.num_gates() - 1 + adder.num_inputs() as usize);
+/// ```
+pub struct TopologicalView<'a, P: ?Sized> {
+    network: &'a P,
+    order: Vec<Id>,
+    positions: FxHashMap<Id, usize>,
+}
+
+impl<'a, P: Network + ?Sized> TopologicalView<'a, P> {
+    pub fn new(network: &'a P) -> Self {
+        let mut order = Vec::new();
+        let mut visited = FxHashSet::default();
+        // post-order traversal from the outputs
+        let mut stack = Vec::new();
+        for output in network.outputs() {
+            stack.push((output.node_id(), false));
+            while let Some((id, expanded)) = stack.pop() {
+                if expanded {
+                    order.push(id);
+                    continue;
+                }
+                if !visited.insert(id) {
+                    continue;
+                }
+                stack.push((id, true));
+                let node = network.node(id);
+                stack.extend(
+                    node.inputs()
+                        .iter()
+                        .rev()
+                        .filter(|input| !visited.contains(&input.node_id()))
+                        .map(|input| (input.node_id(), false)),
+                );
+            }
+        }
+        let positions = order
+            .iter()
+            .enumerate()
+            .map(|(position, id)| (*id, position))
+            .collect();
+        Self {
+            network,
+            order,
+            positions,
+        }
+    }
+
+    /// Returns the ids of the nodes in topological order.
+    pub fn order(&self) -> &[Id] {
+        &self.order
+    }
+
+    /// Returns the position of the given node in the topological order or [`None`] if it is not
+    /// reachable from an output.
+    pub fn position(&self, id: Id) -> Option<usize> {
+        self.positions.get(&id).copied()
+    }
+
+    /// Returns the number of nodes that are reachable from an output.
+    pub fn size(&self) -> usize {
+        self.order.len()
+    }
+}
+
+/// A view on a network that provides the level of each node, i.e. the number of gates on the
+/// longest path from a leaf to the node, as well as the depth of the network and of each output.
+/// Corresponds to *mockturtle*'s `depth_view`. The levels are computed once when the view is
+/// created, so all queries are cheap.
+///
+/// # Example
+/// ```
+/// use eggmock::{DepthView, MutableNetwork, Network, Node, Xag};
+///
+/// // a & b & c & d as a chain and a ^ b
+/// let mut xag = MutableNetwork::<Xag>::default();
+/// let [a, b, c, d] = [0, 1, 2, 3].map(|input| xag.add_node(Xag::Input(input)));
+/// let ab = xag.add_node(Xag::And([a, b]));
+/// let abc = xag.add_node(Xag::And([ab, c]));
+/// let abcd = xag.add_node(Xag::And([abc, d.invert()]));
+/// let xor = xag.add_node(Xag::Xor([a, b]));
+/// xag.add_output(abcd);
+/// xag.add_output(xor);
+///
+/// let view = DepthView::new(&xag);
+/// assert_eq!(view.level(a.node_id()), 0);
+/// assert_eq!(view.level(abc.node_id()), 2);
+/// assert_eq!(view.level(abcd.node_id()), 3);
+/// assert_eq!(view.output_depths(), [3, 1]);
+/// assert_eq!(view.depth(), 3);
+/// let level_one = Vec::from_iter(view.nodes_at_level(1));
+/// assert_eq!(level_one.len(), 2);
+/// assert!(level_one.contains(&ab.node_id()) && level_one.contains(&xor.node_id()));
+///
+/// // xors count as two levels
+/// let view = DepthView::with_costs(&xag, |node| match node {
+///     Xag::Xor(_) => 2,
+///     node => !node.is_leaf() as u32,
+/// });
+/// assert_eq!(view.output_depths(), [3, 2]);
+/// ```
+pub struct DepthView<'a, P: ?Sized> {
+    topological: TopologicalView<'a, P>,
+    levels: FxHashMap<Id, u32>,
+    output_depths: Vec<u32>,
+}
+
+impl<'a, P: Network + ?Sized> DepthView<'a, P> {
+    /// Creates a depth view in which every gate has a level one above the largest level of its
+    /// inputs and leaves have level zero.
+    pub fn new(network: &'a P) -> Self {
+        Self::with_costs(network, |node| !node.is_leaf() as u32)
+    }
+
+    /// Creates a depth view in which the level of a gate is the largest level of its inputs plus
+    /// the cost of the gate. Leaves have level zero.
+    pub fn with_costs(network: &'a P, mut cost: impl FnMut(&P::Node) -> u32) -> Self {
+        let topological = TopologicalView::new(network);
+        let mut levels: FxHashMap<Id, u32> = FxHashMap::default();
+        for id in topological.order() {
+            let node = network.node(*id);
+            let level = match node
+                .inputs()
+                .iter()
+                .map(|input| levels[&input.node_id()])
+                .max()
+            {
+                Some(inputs) => inputs + cost(&node),
+                None => 0,
+            };
+            levels.insert(*id, level);
+        }
+        let output_depths =
+            Vec::from_iter(network.outputs().map(|output| levels[&output.node_id()]));
+        Self {
+            topological,
+            levels,
+            output_depths,
+        }
+    }
+
+    /// Returns the level of the given node.
+    ///
+    /// # Panics
+    /// Panics if the node is not reachable from an output.
+    pub fn level(&self, id: Id) -> u32 {
+        self.levels[&id]
+    }
+
+    /// Returns the largest level of an output.
+    pub fn depth(&self) -> u32 {
+        self.output_depths.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Returns the level of the output at the given position in [`outputs`](Network::outputs).
+    pub fn output_depth(&self, output: usize) -> u32 {
+        self.output_depths[output]
+    }
+
+    /// Returns the levels of all outputs in the order of [`outputs`](Network::outputs).
+    pub fn output_depths(&self) -> &[u32] {
+        &self.output_depths
+    }
+
+    /// Returns the ids of the nodes in topological order.
+    pub fn order(&self) -> &[Id] {
+        self.topological.order()
+    }
+
+    /// Returns the ids of the nodes with the given level in topological order.
+    pub fn nodes_at_level(&self, level: u32) -> impl Iterator<Item = Id> + '_ {
+        self.order()
+            .iter()
+            .copied()
+            .filter(move |id| self.levels[id] == level)
+    }
+}
+
+impl<P: Network + ?Sized> Network for TopologicalView<'_, P> {
+    type Node = P::Node;
+
+    fn outputs(&self) -> impl Iterator<Item = Signal> {
+        self.network.outputs()
+    }
+    fn node(&self, id: Id) -> Self::Node {
+        self.network.node(id)
+    }
+    fn num_inputs(&self) -> u64 {
+        self.network.num_inputs()
+    }
+    fn num_registers(&self) -> u64 {
+        self.network.num_registers()
+    }
+    fn register(&self, index: u64) -> Register {
+        self.network.register(index)
+    }
+    fn input_name(&self, input: u64) -> Option<&str> {
+        self.network.input_name(input)
+    }
+    fn output_name(&self, output: usize) -> Option<&str> {
+        self.network.output_name(output)
+    }
+    /// Returns the nodes that are reachable from the outputs in topological order.
+    fn iter(&self) -> impl Iterator<Item = (Id, Self::Node)> + '_ {
+        self.order().iter().map(|id| (*id, self.network.node(*id)))
+    }
+    fn send<R: Receiver<Node = Self::Node>>(&self, receiver: R) -> R::Result {
+        self.network.send(receiver)
+    }
+}
+
+impl<P: Network + ?Sized> Network for DepthView<'_, P> {
+    type Node = P::Node;
+
+    fn outputs(&self) -> impl Iterator<Item = Signal> {
+        self.topological.network.outputs()
+    }
+    fn node(&self, id: Id) -> Self::Node {
+        self.topological.network.node(id)
+    }
+    fn num_inputs(&self) -> u64 {
+        self.topological.network.num_inputs()
+    }
+    fn num_registers(&self) -> u64 {
+        self.topological.network.num_registers()
+    }
+    fn register(&self, index: u64) -> Register {
+        self.topological.network.register(index)
+    }
+    fn input_name(&self, input: u64) -> Option<&str> {
+        self.topological.network.input_name(input)
+    }
+    fn output_name(&self, output: usize) -> Option<&str> {
+        self.topological.network.output_name(output)
+    }
+    /// Returns the nodes that are reachable from the outputs in topological order.
+    fn iter(&self) -> impl Iterator<Item = (Id, Self::Node)> + '_ {
+        self.order()
+            .iter()
+            .map(|id| (*id, self.topological.network.node(*id)))
+    }
+    fn send<R: Receiver<Node = Self::Node>>(&self, receiver: R) -> R::Result {
+        self.topological.network.send(receiver)
+    }
+}
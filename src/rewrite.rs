@@ -1,4 +1,6 @@
-use crate::{ Node, Receiver, ReceiverFFI};
+use std::marker::PhantomData;
+
+use crate::{Interface, Node, Receiver, ReceiverFFI};
 
 /// Allows rewriting of logic networks.
 ///
@@ -21,36 +23,81 @@ pub trait Rewriter {
     );
 }
 
+/// Like [`Rewriter`], but the rewritten network may be of a different type than the received one,
+/// e.g. to rewrite an [`Aig`](crate::Aig) into an [`Xmg`](crate::Xmg). Every [`Rewriter`] is a
+/// converting rewriter whose input and output types are the same.
+pub trait ConvertingRewriter {
+    type Input: Node;
+    type Output: Node;
+    type Intermediate;
+
+    fn create_receiver(
+        &mut self,
+    ) -> impl Receiver<Node = Self::Input, Result = Self::Intermediate> + 'static;
+    fn rewrite(
+        self,
+        input: Self::Intermediate,
+        output: impl Receiver<Node = Self::Output, Result = ()>,
+    );
+}
+
+impl<R: Rewriter> ConvertingRewriter for R {
+    type Input = R::Node;
+    type Output = R::Node;
+    type Intermediate = R::Intermediate;
+
+    fn create_receiver(
+        &mut self,
+    ) -> impl Receiver<Node = Self::Input, Result = Self::Intermediate> + 'static {
+        Rewriter::create_receiver(self)
+    }
+
+    fn rewrite(
+        self,
+        input: Self::Intermediate,
+        output: impl Receiver<Node = Self::Output, Result = ()>,
+    ) {
+        Rewriter::rewrite(self, input, output)
+    }
+}
+
 /// A struct that contains a data pointer and a function pointing to the function that performs the
-/// rewrite using the data.
+/// rewrite using the data. `N` is the type of the received network and `O` the type of the
+/// rewritten network.
 ///
-/// Allocated memory is released after a call to the rewrite function.
+/// Allocated memory is released after a call to the rewrite function. The names of the primary
+/// inputs and outputs as well as the number of primary inputs of the received network are restored
+/// on the rewritten network.
 #[repr(C)]
-pub struct RewriterFFI<N: Node> {
+pub struct RewriterFFI<N: Node, O: Node = N> {
     data: *mut libc::c_void,
-    rewrite: extern "C" fn(*mut libc::c_void, N::ReceiverFFI<()>),
+    rewrite: extern "C" fn(*mut libc::c_void, O::ReceiverFFI<()>),
+    _input: PhantomData<fn(N) -> ()>,
 }
 
-impl<N: Node> RewriterFFI<N> {
-    pub fn new<R>(mut rewriter: R) -> N::ReceiverFFI<RewriterFFI<N>>
+impl<N: Node, O: Node> RewriterFFI<N, O> {
+    pub fn new<R>(mut rewriter: R) -> N::ReceiverFFI<RewriterFFI<N, O>>
     where
-        R: Rewriter<Node = N> + 'static,
+        R: ConvertingRewriter<Input = N, Output = O> + 'static,
         R::Intermediate: 'static,
     {
-        N::ReceiverFFI::new(rewriter.create_receiver().map(|result| {
-            let data = Box::into_raw(Box::new((rewriter, result)));
-            RewriterFFI {
-                data: data as *mut libc::c_void,
-                rewrite: Self::rewrite::<R>,
-            }
-        }))
+        N::ReceiverFFI::new(rewriter.create_receiver().collect_interface().map(
+            |(result, interface)| {
+                let data = Box::into_raw(Box::new((rewriter, result, interface)));
+                RewriterFFI {
+                    data: data as *mut libc::c_void,
+                    rewrite: Self::rewrite::<R>,
+                    _input: PhantomData,
+                }
+            },
+        ))
     }
 
-    extern "C" fn rewrite<R: Rewriter<Node = N>>(
+    extern "C" fn rewrite<R: ConvertingRewriter<Input = N, Output = O>>(
         data: *mut libc::c_void,
-        callback: N::ReceiverFFI<()>,
+        callback: O::ReceiverFFI<()>,
     ) {
-        let data = unsafe { Box::from_raw(data as *mut (R, R::Intermediate)) };
-        data.0.rewrite(data.1, callback)
+        let data = unsafe { Box::from_raw(data as *mut (R, R::Intermediate, Interface)) };
+        data.0.rewrite(data.1, callback.with_interface(data.2))
     }
 }
@@ -0,0 +1,79 @@
+use std::borrow::Cow;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{GateType, Node, Signal, TruthTable};
+
+/// The serialized form of the nodes of networks defined with
+/// [`define_network`](crate::define_network). Gates are identified by their [`GateType::name`], so
+/// the form does not depend on the order of the gate types.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Node")]
+enum SerializedNode<'a> {
+    Input(u64),
+    False,
+    Gate(Cow<'a, str>, Cow<'a, [Signal]>),
+}
+
+/// Serializes a node as an input, the constant or a gate with the name of its gate type and its
+/// inputs. Used by [`define_network`](crate::define_network).
+#[doc(hidden)]
+pub fn serialize_node<N: Node, S: Serializer>(node: &N, serializer: S) -> Result<S::Ok, S::Error> {
+    let serialized = if let Some(input) = node.input_index() {
+        SerializedNode::Input(input)
+    } else if let Some(gate) = node.gate_type() {
+        SerializedNode::Gate(Cow::Borrowed(gate.name()), Cow::Borrowed(node.inputs()))
+    } else {
+        SerializedNode::False
+    };
+    serialized.serialize(serializer)
+}
+
+/// Deserializes a node serialized with [`serialize_node`]. Used by
+/// [`define_network`](crate::define_network).
+#[doc(hidden)]
+pub fn deserialize_node<'de, N: Node, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<N, D::Error> {
+    match SerializedNode::deserialize(deserializer)? {
+        SerializedNode::Input(input) => Ok(N::input(input)),
+        SerializedNode::False => Ok(N::constant_false()),
+        SerializedNode::Gate(name, inputs) => {
+            let gate = N::Gates::VARIANTS
+                .iter()
+                .find(|gate| gate.name() == name)
+                .ok_or_else(|| {
+                    D::Error::custom(format!("unknown {} gate `{name}`", N::NTK_TYPENAME))
+                })?;
+            N::from_gate(*gate, &inputs).ok_or_else(|| {
+                D::Error::custom(format!(
+                    "`{name}` gates have {} inputs but got {}",
+                    gate.fanin(),
+                    inputs.len()
+                ))
+            })
+        }
+    }
+}
+
+/// Truth tables are serialized as their number of variables and [`TruthTable::to_hex`].
+impl From<TruthTable> for (u32, String) {
+    fn from(tt: TruthTable) -> Self {
+        (tt.num_vars(), tt.to_hex())
+    }
+}
+
+impl TryFrom<(u32, String)> for TruthTable {
+    type Error = String;
+
+    fn try_from((num_vars, hex): (u32, String)) -> Result<Self, String> {
+        let digits = 1usize
+            .checked_shl(num_vars)
+            .map(|bits| (bits / 4).max(1))
+            .filter(|digits| *digits == hex.len());
+        digits
+            .and_then(|_| TruthTable::from_hex(num_vars, &hex))
+            .ok_or_else(|| format!("`{hex}` is not a truth table with {num_vars} variables"))
+    }
+}
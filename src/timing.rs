@@ -0,0 +1,385 @@
+use std::fmt::Write;
+
+use rustc_hash::FxHashMap;
+
+use crate::{
+    CellMapping, GateType, Id, Klut, LibertyCostModel, Network, NetworkWithBackwardEdges, Node,
+    TopologicalView,
+};
+
+/// Assigns delays to the gates of a network for [`analyze_timing`].
+pub trait DelayModel<N: Node> {
+    /// Returns the delay from the input at the given position to the output of the node with the
+    /// given id. Only called for nodes that are not leaves.
+    fn delay(&self, id: Id, node: &N, input: usize) -> f64;
+}
+
+/// A delay model in which every gate has a delay of one, i.e. the arrival time of a node is its
+/// level.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct UnitDelay;
+
+impl<N: Node> DelayModel<N> for UnitDelay {
+    fn delay(&self, _id: Id, _node: &N, _input: usize) -> f64 {
+        1.0
+    }
+}
+
+/// A delay model that assigns a delay to each gate type.
+#[derive(Debug, Clone)]
+pub struct GateDelays<G> {
+    delays: FxHashMap<G, f64>,
+    default: f64,
+}
+
+impl<G: GateType> GateDelays<G> {
+    /// Creates a delay model in which all gate types have the given delay.
+    pub fn new(default: f64) -> Self {
+        Self {
+            delays: FxHashMap::default(),
+            default,
+        }
+    }
+
+    /// Creates a delay model with the delays of the given cost model. Gate types that are not
+    /// implemented by the library have an infinite delay.
+    pub fn from_cost_model(model: &LibertyCostModel<G>) -> Self {
+        let mut delays = Self::new(f64::INFINITY);
+        for gate in G::VARIANTS {
+            if let Some(cost) = model.gate(*gate) {
+                delays.set(*gate, cost.delay);
+            }
+        }
+        delays
+    }
+
+    pub fn set(&mut self, gate: G, delay: f64) {
+        self.delays.insert(gate, delay);
+    }
+
+    pub fn with(mut self, gate: G, delay: f64) -> Self {
+        self.set(gate, delay);
+        self
+    }
+
+    pub fn get(&self, gate: G) -> f64 {
+        self.delays.get(&gate).copied().unwrap_or(self.default)
+    }
+}
+
+impl<N: Node> DelayModel<N> for GateDelays<N::Gates> {
+    fn delay(&self, _id: Id, node: &N, _input: usize) -> f64 {
+        match node.gate_type() {
+            Some(gate) => self.get(gate),
+            None => self.default,
+        }
+    }
+}
+
+/// A delay model for a [`CellMapping`] that uses the delays of the cell pins. The load of each
+/// cell is the sum of the input loads of the pins it drives.
+#[derive(Debug, Clone)]
+pub struct LibraryDelays<'m, 'l> {
+    mapping: &'m CellMapping<'l>,
+    loads: Vec<f64>,
+}
+
+impl<'m, 'l> LibraryDelays<'m, 'l> {
+    pub fn new(mapping: &'m CellMapping<'l>) -> Self {
+        let mut loads = vec![0.0; mapping.ids().count()];
+        for id in mapping.ids() {
+            let Some(cell) = mapping.cell(id) else {
+                continue;
+            };
+            for (input, pin) in mapping.node(id).inputs().iter().zip(cell.pins()) {
+                loads[u32::from(input.node_id()) as usize] += pin.input_load;
+            }
+        }
+        Self { mapping, loads }
+    }
+
+    /// Returns the load driven by the given node.
+    pub fn load(&self, id: Id) -> f64 {
+        self.loads[u32::from(id) as usize]
+    }
+}
+
+impl DelayModel<Klut> for LibraryDelays<'_, '_> {
+    fn delay(&self, id: Id, _node: &Klut, input: usize) -> f64 {
+        match self.mapping.cell(id) {
+            Some(cell) => cell.pins()[input].delay(self.load(id)),
+            None => 0.0,
+        }
+    }
+}
+
+/// A node on a [`critical_path`](TimingAnalysis::critical_path).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathNode<G> {
+    pub id: Id,
+    /// The gate type of the node or [`None`] if it is a leaf.
+    pub gate_type: Option<G>,
+    pub arrival: f64,
+}
+
+/// The result of [`analyze_timing`].
+#[derive(Debug, Clone)]
+pub struct TimingAnalysis<G> {
+    /// The nodes in topological order.
+    order: Vec<Id>,
+    gate_types: FxHashMap<Id, Option<G>>,
+    arrival: FxHashMap<Id, f64>,
+    required: FxHashMap<Id, f64>,
+    /// The fanin that determines the arrival time of each gate.
+    critical_fanin: FxHashMap<Id, Id>,
+    outputs: Vec<(Id, Option<String>)>,
+    required_time: f64,
+}
+
+/// Performs a static timing analysis of the given network with the given delay model.
+///
+/// The arrival times are propagated from the leaves, which arrive at time zero, towards the
+/// outputs. The required times are propagated backwards along the fanouts from the outputs, which
+/// are required at `required_time` or, if it is [`None`], at the largest arrival time of an output.
+///
+/// # Example
+/// ```
+/// use eggmock::{analyze_timing, GateDelays, MutableNetwork, UnitDelay, Xag, XagGateType};
+///
+/// // y = (a ^ b) & c and !(a ^ b)
+/// let mut xag = MutableNetwork::<Xag>::default();
+/// let [a, b, c] = [0, 1, 2].map(|input| xag.add_node(Xag::Input(input)));
+/// let xor = xag.add_node(Xag::Xor([a, b]));
+/// let and = xag.add_node(Xag::And([xor, c]));
+/// xag.add_output(and);
+/// xag.add_output(xor.invert());
+/// xag.interface_mut().names.set_output(0, "y");
+///
+/// let timing = analyze_timing(&xag, &UnitDelay, None);
+/// assert_eq!(timing.delay(), 2.0);
+/// assert_eq!(timing.required_time(), 2.0);
+/// // the xor has to be available one gate before the outputs are required
+/// assert_eq!(timing.slack(xor.node_id()), 0.0);
+/// assert_eq!(timing.slack(c.node_id()), 1.0);
+/// let path = Vec::from_iter(timing.critical_path().iter().map(|node| node.gate_type));
+/// assert_eq!(path, [None, Some(XagGateType::Xor), Some(XagGateType::And)]);
+///
+/// let delays = GateDelays::new(1.0).with(XagGateType::Xor, 3.0);
+/// let timing = analyze_timing(&xag, &delays, Some(5.0));
+/// assert_eq!(timing.delay(), 4.0);
+/// assert_eq!(timing.worst_slack(), 1.0);
+/// assert_eq!(timing.required(c.node_id()), 4.0);
+/// assert_eq!(
+///     timing.report(),
+///     "\
+/// delay:         4.000
+/// required time: 5.000
+/// worst slack:   1.000
+///
+/// output               node    arrival   required      slack
+/// y                       4      4.000      5.000      1.000
+/// #1                      3      3.000      5.000      2.000
+///
+/// critical path:
+///     node gate        arrival   required      slack
+///        0 leaf          0.000      1.000      1.000
+///        3 xor           3.000      4.000      1.000
+///        4 and           4.000      5.000      1.000
+/// "
+/// );
+/// ```
+pub fn analyze_timing<N, M>(
+    network: &N,
+    model: &M,
+    required_time: Option<f64>,
+) -> TimingAnalysis<<N::Node as Node>::Gates>
+where
+    N: NetworkWithBackwardEdges + ?Sized,
+    M: DelayModel<N::Node> + ?Sized,
+{
+    let order = TopologicalView::new(network).order().to_vec();
+    let mut nodes = FxHashMap::default();
+    let mut arrival: FxHashMap<Id, f64> = FxHashMap::default();
+    let mut critical_fanin = FxHashMap::default();
+    for id in &order {
+        let node = network.node(*id);
+        let mut latest: Option<(f64, Id)> = None;
+        for (position, input) in node.inputs().iter().enumerate() {
+            let time = arrival[&input.node_id()] + model.delay(*id, &node, position);
+            if latest.is_none_or(|(latest, _)| time > latest) {
+                latest = Some((time, input.node_id()));
+            }
+        }
+        if let Some((time, fanin)) = latest {
+            critical_fanin.insert(*id, fanin);
+            arrival.insert(*id, time);
+        } else {
+            arrival.insert(*id, 0.0);
+        }
+        nodes.insert(*id, node);
+    }
+
+    let outputs = Vec::from_iter(network.outputs().enumerate().map(|(index, output)| {
+        (
+            output.node_id(),
+            network.output_name(index).map(str::to_string),
+        )
+    }));
+    let required_time = required_time.unwrap_or_else(|| {
+        outputs
+            .iter()
+            .map(|(id, _)| arrival[id])
+            .fold(0.0, f64::max)
+    });
+    let mut required: FxHashMap<Id, f64> = FxHashMap::default();
+    for (id, _) in &outputs {
+        required.insert(*id, required_time);
+    }
+    for id in order.iter().rev() {
+        let mut time = required.get(id).copied().unwrap_or(f64::INFINITY);
+        for fanout in network.node_outputs(*id) {
+            let node = &nodes[&fanout];
+            for (position, input) in node.inputs().iter().enumerate() {
+                if input.node_id() == *id {
+                    time = time.min(required[&fanout] - model.delay(fanout, node, position));
+                }
+            }
+        }
+        required.insert(*id, time);
+    }
+
+    TimingAnalysis {
+        order,
+        gate_types: nodes
+            .into_iter()
+            .map(|(id, node)| (id, node.gate_type()))
+            .collect(),
+        arrival,
+        required,
+        critical_fanin,
+        outputs,
+        required_time,
+    }
+}
+
+impl<G: GateType> TimingAnalysis<G> {
+    /// Returns the ids of the analysed nodes in topological order.
+    pub fn ids(&self) -> &[Id] {
+        &self.order
+    }
+
+    /// Returns the largest arrival time of an output.
+    pub fn delay(&self) -> f64 {
+        self.outputs
+            .iter()
+            .map(|(id, _)| self.arrival[id])
+            .fold(0.0, f64::max)
+    }
+
+    /// Returns the time at which the outputs are required.
+    pub fn required_time(&self) -> f64 {
+        self.required_time
+    }
+
+    /// Returns the time at which the output of the given node is available.
+    ///
+    /// # Panics
+    /// Panics if the node is not reachable from an output.
+    pub fn arrival(&self, id: Id) -> f64 {
+        self.arrival[&id]
+    }
+
+    /// Returns the latest time at which the output of the given node has to be available for the
+    /// outputs to meet the required time. Nodes that do not lead to an output have an infinite
+    /// required time.
+    ///
+    /// # Panics
+    /// Panics if the node is not reachable from an output.
+    pub fn required(&self, id: Id) -> f64 {
+        self.required[&id]
+    }
+
+    /// Returns the difference between the required and the arrival time of the given node. A
+    /// negative slack means that the node is on a path that violates the required time.
+    pub fn slack(&self, id: Id) -> f64 {
+        self.required(id) - self.arrival(id)
+    }
+
+    /// Returns the nodes on the path to the output with the largest arrival time, starting at a
+    /// leaf.
+    pub fn critical_path(&self) -> Vec<PathNode<G>> {
+        let Some((output, _)) = self
+            .outputs
+            .iter()
+            .max_by(|(a, _), (b, _)| self.arrival[a].total_cmp(&self.arrival[b]))
+        else {
+            return Vec::new();
+        };
+        let mut path = vec![*output];
+        while let Some(fanin) = self.critical_fanin.get(path.last().unwrap()) {
+            path.push(*fanin);
+        }
+        path.reverse();
+        path.into_iter()
+            .map(|id| PathNode {
+                id,
+                gate_type: self.gate_types[&id],
+                arrival: self.arrival[&id],
+            })
+            .collect()
+    }
+
+    /// Returns a textual report containing the arrival and required times and the slack of the
+    /// outputs and of the nodes on the critical path.
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+        let _ = writeln!(report, "delay:         {:.3}", self.delay());
+        let _ = writeln!(report, "required time: {:.3}", self.required_time);
+        let _ = writeln!(report, "worst slack:   {:.3}", self.worst_slack());
+        let _ = writeln!(report);
+        let _ = writeln!(
+            report,
+            "{:<16} {:>8} {:>10} {:>10} {:>10}",
+            "output", "node", "arrival", "required", "slack"
+        );
+        for (index, (id, name)) in self.outputs.iter().enumerate() {
+            let name = name.clone().unwrap_or_else(|| format!("#{index}"));
+            let _ = writeln!(
+                report,
+                "{:<16} {:>8} {:>10.3} {:>10.3} {:>10.3}",
+                name,
+                u32::from(*id),
+                self.arrival(*id),
+                self.required_time,
+                self.required_time - self.arrival(*id)
+            );
+        }
+        let _ = writeln!(report);
+        let _ = writeln!(report, "critical path:");
+        let _ = writeln!(
+            report,
+            "{:>8} {:<8} {:>10} {:>10} {:>10}",
+            "node", "gate", "arrival", "required", "slack"
+        );
+        for node in self.critical_path() {
+            let _ = writeln!(
+                report,
+                "{:>8} {:<8} {:>10.3} {:>10.3} {:>10.3}",
+                u32::from(node.id),
+                node.gate_type.map(|gate| gate.name()).unwrap_or("leaf"),
+                node.arrival,
+                self.required(node.id),
+                self.slack(node.id)
+            );
+        }
+        report
+    }
+
+    /// Returns the smallest slack of an output.
+    pub fn worst_slack(&self) -> f64 {
+        self.outputs
+            .iter()
+            .map(|(id, _)| self.required_time - self.arrival[id])
+            .fold(f64::INFINITY, f64::min)
+    }
+}
@@ -1,6 +1,10 @@
+use std::borrow::Cow;
 use std::marker::PhantomData;
 
-use super::{Node, Signal};
+use rustc_hash::FxHashMap;
+
+use super::{Node, Register, Signal};
+use crate::{decompose_gate, DecomposedReceiver, NetworkBuilder};
 
 /// A type that can receive nodes of a logic network and produce some result from it.
 pub trait Receiver: Sized {
@@ -12,6 +16,28 @@ pub trait Receiver: Sized {
     /// Creates the result from the previously transferred nodes where `outputs` contains the output
     /// signals.
     fn done(self, outputs: &[Signal]) -> Self::Result;
+    /// Declares that the network has exactly `num_inputs` primary inputs, including the ones that
+    /// are never created via [`create_node`](Self::create_node) because no output depends on them.
+    /// Does nothing by default.
+    fn set_num_inputs(&mut self, _num_inputs: u64) {}
+    /// Declares the registers of a sequential network. Called after
+    /// [`set_num_inputs`](Self::set_num_inputs) and before any node is created. See [`Register`]
+    /// for how registers are represented. Does nothing by default.
+    fn set_registers(&mut self, _registers: &[Register]) {}
+    /// Assigns a name to the primary input with the given index, where the indices following the
+    /// primary inputs refer to the register outputs (see [`Register`]). May be called at any point
+    /// before [`done`](Self::done). Does nothing by default.
+    fn set_input_name(&mut self, _input: u64, _name: &str) {}
+    /// Assigns a name to the primary output at the given position of the `outputs` that will be
+    /// passed to [`done`](Self::done). Does nothing by default.
+    fn set_output_name(&mut self, _output: usize, _name: &str) {}
+    /// Declares that the previously created `choice` is functionally equivalent to `node` and may
+    /// be used as an alternative implementation of it. Does nothing by default.
+    ///
+    /// The receivers generated for *mockturtle* record the choice if the target network provides
+    /// `add_choice` for two nodes (e.g. a `choice_view`) and ignore it otherwise. As these choices
+    /// cannot be inverted, choices whose phase differs from the one of `node` are dropped there.
+    fn add_choice(&mut self, _node: Signal, _choice: Signal) {}
     /// Maps the result of this Receiver using the given function.
     fn map<Res2, F>(self, map: F) -> impl Receiver<Node = Self::Node, Result = Res2>
     where
@@ -22,6 +48,55 @@ pub trait Receiver: Sized {
             map,
         }
     }
+    /// Records the [`Interface`] passed to this receiver and returns it alongside the result. The
+    /// interface is forwarded to this receiver as well.
+    ///
+    /// # Example
+    /// ```
+    /// use eggmock::egg::{AstSize, EGraph, Extractor};
+    /// use eggmock::{read_aiger, Aig, AigLanguage, MutableNetwork, Network, Receiver};
+    ///
+    /// let source = "aag 4 3 0 1 1\n2\n4\n6\n8\n8 2 4\ni0 a\ni1 b\ni2 c\no0 y\n";
+    /// let aig = read_aiger(MutableNetwork::<Aig>::default(), source.as_bytes()).unwrap();
+    ///
+    /// // an e-graph forgets the interface, so it is collected on the way in ...
+    /// let receiver = EGraph::<AigLanguage, ()>::default().collect_interface();
+    /// let ((egraph, roots), interface) = aig.send(receiver);
+    /// assert_eq!(interface.num_inputs, Some(3));
+    /// assert_eq!(interface.names.input(2), Some("c"));
+    ///
+    /// // ... and restored on the way out
+    /// let receiver = MutableNetwork::<Aig>::default().with_interface(interface);
+    /// let restored = (Extractor::new(&egraph, AstSize), roots).send(receiver);
+    /// assert_eq!(restored.num_inputs(), 3);
+    /// assert_eq!(restored.input_name(0), Some("a"));
+    /// assert_eq!(restored.input_name(2), Some("c"));
+    /// assert_eq!(restored.output_name(0), Some("y"));
+    /// ```
+    fn collect_interface(
+        self,
+    ) -> impl Receiver<Node = Self::Node, Result = (Self::Result, Interface)> {
+        InterfaceCollectingReceiver {
+            original: self,
+            interface: Interface::default(),
+        }
+    }
+    /// Passes the given [`Interface`] to this receiver, regardless of what the sender declares.
+    fn with_interface(
+        mut self,
+        interface: Interface,
+    ) -> impl Receiver<Node = Self::Node, Result = Self::Result> {
+        if let Some(num_inputs) = interface.num_inputs {
+            self.set_num_inputs(num_inputs);
+        }
+        if !interface.registers.is_empty() {
+            self.set_registers(&interface.registers);
+        }
+        InterfaceReceiver {
+            original: self,
+            names: interface.names,
+        }
+    }
     fn adapt<From: Node, F>(self, adapter: F) -> AdaptedReceiver<From, Self, F>
     where
         F: FnMut(From) -> Self::Node,
@@ -32,6 +107,69 @@ pub trait Receiver: Sized {
             adapter,
         }
     }
+    /// Like [`adapt`](Self::adapt) but the decomposer may create any number of nodes for each
+    /// gate it receives, using the given builder, and returns the signal that represents the gate.
+    /// The inputs of the received gates are signals of this receiver. Primary inputs and constants
+    /// are created by the returned receiver itself.
+    fn decompose<From: Node, F>(self, decomposer: F) -> DecomposedReceiver<From, Self, F>
+    where
+        F: FnMut(&From, &mut NetworkBuilder<Self>) -> Signal,
+    {
+        DecomposedReceiver::new(self, decomposer)
+    }
+    /// Converts networks of any type into this receiver's network type by decomposing each gate
+    /// with [`decompose_gate`](crate::decompose_gate).
+    fn convert<From: Node>(self) -> impl Receiver<Node = From, Result = Self::Result> {
+        self.decompose(decompose_gate::<From, Self>)
+    }
+}
+
+/// Describes the interface of a network apart from its output signals.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Interface {
+    /// The number of primary inputs or [`None`] if it is unknown.
+    pub num_inputs: Option<u64>,
+    pub registers: Vec<Register>,
+    pub names: InterfaceNames,
+}
+
+/// Names of the primary inputs and outputs of a network.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InterfaceNames {
+    inputs: FxHashMap<u64, String>,
+    outputs: FxHashMap<usize, String>,
+}
+
+impl InterfaceNames {
+    /// Returns the name of the primary input with the given index, if it has one.
+    pub fn input(&self, input: u64) -> Option<&str> {
+        self.inputs.get(&input).map(String::as_str)
+    }
+    /// Returns the name of the primary output at the given position, if it has one.
+    pub fn output(&self, output: usize) -> Option<&str> {
+        self.outputs.get(&output).map(String::as_str)
+    }
+    pub fn set_input(&mut self, input: u64, name: impl Into<String>) {
+        self.inputs.insert(input, name.into());
+    }
+    pub fn set_output(&mut self, output: usize, name: impl Into<String>) {
+        self.outputs.insert(output, name.into());
+    }
+    /// Returns an iterator over all named primary inputs and their names.
+    pub fn inputs(&self) -> impl Iterator<Item = (u64, &str)> {
+        self.inputs
+            .iter()
+            .map(|(input, name)| (*input, name.as_str()))
+    }
+    /// Returns an iterator over all named primary outputs and their names.
+    pub fn outputs(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.outputs
+            .iter()
+            .map(|(output, name)| (*output, name.as_str()))
+    }
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty() && self.outputs.is_empty()
+    }
 }
 
 pub trait ReceiverFFI: Receiver {
@@ -58,6 +196,91 @@ where
     fn done(self, outputs: &[Signal]) -> Self::Result {
         (self.map)(self.original.done(outputs))
     }
+    fn set_num_inputs(&mut self, num_inputs: u64) {
+        self.original.set_num_inputs(num_inputs)
+    }
+    fn set_registers(&mut self, registers: &[Register]) {
+        self.original.set_registers(registers)
+    }
+    fn set_input_name(&mut self, input: u64, name: &str) {
+        self.original.set_input_name(input, name)
+    }
+    fn set_output_name(&mut self, output: usize, name: &str) {
+        self.original.set_output_name(output, name)
+    }
+    fn add_choice(&mut self, node: Signal, choice: Signal) {
+        self.original.add_choice(node, choice)
+    }
+}
+
+struct InterfaceCollectingReceiver<Original> {
+    original: Original,
+    interface: Interface,
+}
+
+impl<O: Receiver> Receiver for InterfaceCollectingReceiver<O> {
+    type Node = O::Node;
+    type Result = (O::Result, Interface);
+
+    fn create_node(&mut self, node: Self::Node) -> Signal {
+        self.original.create_node(node)
+    }
+    fn done(self, outputs: &[Signal]) -> Self::Result {
+        (self.original.done(outputs), self.interface)
+    }
+    fn set_num_inputs(&mut self, num_inputs: u64) {
+        self.interface.num_inputs = Some(num_inputs);
+        self.original.set_num_inputs(num_inputs)
+    }
+    fn set_registers(&mut self, registers: &[Register]) {
+        self.interface.registers = registers.to_vec();
+        self.original.set_registers(registers)
+    }
+    fn set_input_name(&mut self, input: u64, name: &str) {
+        self.interface.names.set_input(input, name);
+        self.original.set_input_name(input, name)
+    }
+    fn set_output_name(&mut self, output: usize, name: &str) {
+        self.interface.names.set_output(output, name);
+        self.original.set_output_name(output, name)
+    }
+    fn add_choice(&mut self, node: Signal, choice: Signal) {
+        self.original.add_choice(node, choice)
+    }
+}
+
+struct InterfaceReceiver<Original> {
+    original: Original,
+    names: InterfaceNames,
+}
+
+impl<O: Receiver> Receiver for InterfaceReceiver<O> {
+    type Node = O::Node;
+    type Result = O::Result;
+
+    fn create_node(&mut self, node: Self::Node) -> Signal {
+        self.original.create_node(node)
+    }
+    fn done(mut self, outputs: &[Signal]) -> Self::Result {
+        for (input, name) in self.names.inputs() {
+            self.original.set_input_name(input, name);
+        }
+        for (output, name) in self.names.outputs() {
+            if output < outputs.len() {
+                self.original.set_output_name(output, name);
+            }
+        }
+        self.original.done(outputs)
+    }
+    fn set_input_name(&mut self, input: u64, name: &str) {
+        self.names.set_input(input, name);
+    }
+    fn set_output_name(&mut self, output: usize, name: &str) {
+        self.names.set_output(output, name);
+    }
+    fn add_choice(&mut self, node: Signal, choice: Signal) {
+        self.original.add_choice(node, choice)
+    }
 }
 
 pub struct AdaptedReceiver<From, To, F> {
@@ -82,4 +305,37 @@ where
     fn done(self, outputs: &[Signal]) -> Self::Result {
         self.to.done(outputs)
     }
+
+    fn set_num_inputs(&mut self, num_inputs: u64) {
+        self.to.set_num_inputs(num_inputs)
+    }
+
+    fn set_registers(&mut self, registers: &[Register]) {
+        self.to.set_registers(registers)
+    }
+
+    fn set_input_name(&mut self, input: u64, name: &str) {
+        self.to.set_input_name(input, name)
+    }
+
+    fn set_output_name(&mut self, output: usize, name: &str) {
+        self.to.set_output_name(output, name)
+    }
+
+    fn add_choice(&mut self, node: Signal, choice: Signal) {
+        self.to.add_choice(node, choice)
+    }
+}
+
+/// Converts a string that was passed through the FFI as a pointer and a size in bytes. Invalid
+/// UTF-8 sequences are replaced.
+///
+/// # Safety
+/// `name` has to point to `size` readable bytes if `size` is not zero.
+#[doc(hidden)]
+pub unsafe fn ffi_str<'a>(name: *const libc::c_char, size: usize) -> Cow<'a, str> {
+    if size == 0 {
+        return Cow::Borrowed("");
+    }
+    String::from_utf8_lossy(std::slice::from_raw_parts(name as *const u8, size))
 }
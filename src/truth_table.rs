@@ -0,0 +1,305 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+
+use crate::SimulationValue;
+
+/// A truth table of a Boolean function with a fixed number of variables, stored as a bit vector
+/// where bit `i` contains the function value under the assignment `i` (variable `0` being the least
+/// significant bit).
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "(u32, String)", try_from = "(u32, String)")
+)]
+pub struct TruthTable {
+    num_vars: u32,
+    words: Vec<u64>,
+}
+
+impl TruthTable {
+    const VAR_MASKS: [u64; 6] = [
+        0xaaaa_aaaa_aaaa_aaaa,
+        0xcccc_cccc_cccc_cccc,
+        0xf0f0_f0f0_f0f0_f0f0,
+        0xff00_ff00_ff00_ff00,
+        0xffff_0000_ffff_0000,
+        0xffff_ffff_0000_0000,
+    ];
+
+    /// Creates the constant false function of `num_vars` variables.
+    pub fn new(num_vars: u32) -> Self {
+        Self {
+            num_vars,
+            words: vec![0; Self::num_words_for(num_vars)],
+        }
+    }
+
+    /// Creates a constant function of `num_vars` variables.
+    pub fn constant(num_vars: u32, value: bool) -> Self {
+        let tt = Self::new(num_vars);
+        if value {
+            !tt
+        } else {
+            tt
+        }
+    }
+
+    /// Creates the projection onto the variable `var`.
+    pub fn nth_var(num_vars: u32, var: u32) -> Self {
+        assert!(var < num_vars, "variable {var} out of range");
+        let mut tt = Self::new(num_vars);
+        if var < 6 {
+            tt.words.fill(Self::VAR_MASKS[var as usize]);
+        } else {
+            let period = 1 << (var - 6);
+            for (i, word) in tt.words.iter_mut().enumerate() {
+                if (i / period) % 2 == 1 {
+                    *word = u64::MAX;
+                }
+            }
+        }
+        tt.mask();
+        tt
+    }
+
+    /// Creates a truth table from its bits, least significant first. Excess bits are ignored.
+    pub fn from_words(num_vars: u32, words: &[u64]) -> Self {
+        let mut tt = Self::new(num_vars);
+        for (dst, src) in tt.words.iter_mut().zip(words) {
+            *dst = *src;
+        }
+        tt.mask();
+        tt
+    }
+
+    fn num_words_for(num_vars: u32) -> usize {
+        if num_vars <= 6 {
+            1
+        } else {
+            1 << (num_vars - 6)
+        }
+    }
+
+    fn mask(&mut self) {
+        if self.num_vars < 6 {
+            self.words[0] &= (1u64 << (1 << self.num_vars)) - 1;
+        }
+    }
+
+    pub fn num_vars(&self) -> u32 {
+        self.num_vars
+    }
+
+    /// Returns the number of bits (i.e. `2^num_vars`).
+    pub fn num_bits(&self) -> usize {
+        1 << self.num_vars
+    }
+
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Returns the function value under the given assignment.
+    pub fn bit(&self, index: usize) -> bool {
+        self.words[index / 64] >> (index % 64) & 1 == 1
+    }
+
+    pub fn set_bit(&mut self, index: usize, value: bool) {
+        let word = &mut self.words[index / 64];
+        *word = (*word & !(1 << (index % 64))) | ((value as u64) << (index % 64));
+    }
+
+    pub fn is_const0(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    pub fn is_const1(&self) -> bool {
+        (!self.clone()).is_const0()
+    }
+
+    /// Returns the number of assignments under which the function is true.
+    pub fn count_ones(&self) -> u32 {
+        self.words.iter().map(|w| w.count_ones()).sum()
+    }
+
+    /// Returns true iff the function depends on the given variable.
+    pub fn has_var(&self, var: u32) -> bool {
+        (0..self.num_bits())
+            .filter(|i| i >> var & 1 == 0)
+            .any(|i| self.bit(i) != self.bit(i | 1 << var))
+    }
+
+    /// Returns the function with the two given variables swapped.
+    pub fn swap_vars(&self, a: u32, b: u32) -> Self {
+        let mut tt = Self::new(self.num_vars);
+        for i in 0..self.num_bits() {
+            let bit_a = i >> a & 1;
+            let bit_b = i >> b & 1;
+            let j = (i & !(1 << a) & !(1 << b)) | (bit_a << b) | (bit_b << a);
+            tt.set_bit(j, self.bit(i));
+        }
+        tt
+    }
+
+    /// Returns the function with the given variable complemented.
+    pub fn flip_var(&self, var: u32) -> Self {
+        let mut tt = Self::new(self.num_vars);
+        for i in 0..self.num_bits() {
+            tt.set_bit(i ^ 1 << var, self.bit(i));
+        }
+        tt
+    }
+
+    /// Returns the cofactor of the given variable set to `value`. The variable remains in the
+    /// truth table but the function no longer depends on it.
+    pub fn cofactor(&self, var: u32, value: bool) -> Self {
+        let mut tt = Self::new(self.num_vars);
+        for i in 0..self.num_bits() {
+            let source = if value { i | 1 << var } else { i & !(1 << var) };
+            tt.set_bit(i, self.bit(source));
+        }
+        tt
+    }
+
+    /// Returns the same function over `num_vars` variables, where the additional variables are
+    /// appended and ignored by the function.
+    pub fn extend_to(&self, num_vars: u32) -> Self {
+        assert!(num_vars >= self.num_vars);
+        let mut tt = Self::new(num_vars);
+        let bits = self.num_bits();
+        for i in 0..tt.num_bits() {
+            tt.set_bit(i, self.bit(i % bits));
+        }
+        tt
+    }
+
+    /// Returns the function where variable `i` of this function is replaced by variable
+    /// `mapping[i]` of the returned function of `num_vars` variables.
+    pub fn remap(&self, num_vars: u32, mapping: &[u32]) -> Self {
+        assert_eq!(mapping.len(), self.num_vars as usize);
+        let mut tt = Self::new(num_vars);
+        for i in 0..tt.num_bits() {
+            let mut source = 0;
+            for (var, target) in mapping.iter().enumerate() {
+                source |= (i >> target & 1) << var;
+            }
+            tt.set_bit(i, self.bit(source));
+        }
+        tt
+    }
+
+    /// Returns the truth table as a hexadecimal string, most significant digit first.
+    pub fn to_hex(&self) -> String {
+        let digits = (self.num_bits() / 4).max(1);
+        let mut hex = String::with_capacity(digits);
+        for digit in (0..digits).rev() {
+            let word = self.words[digit / 16];
+            let value = (word >> (4 * (digit % 16))) & 0xf;
+            hex.push(char::from_digit(value as u32, 16).unwrap());
+        }
+        hex
+    }
+
+    /// Parses a truth table from a hexadecimal string as returned by [`to_hex`](Self::to_hex).
+    pub fn from_hex(num_vars: u32, hex: &str) -> Option<Self> {
+        let mut tt = Self::new(num_vars);
+        for (digit, c) in hex.chars().rev().enumerate() {
+            let value = c.to_digit(16)? as u64;
+            if digit / 16 >= tt.words.len() {
+                return (value == 0).then_some(tt);
+            }
+            tt.words[digit / 16] |= value << (4 * (digit % 16));
+        }
+        tt.mask();
+        Some(tt)
+    }
+
+    /// Computes the output of this function from the values of its inputs, where `inputs[i]` is
+    /// the value of variable `i`.
+    ///
+    /// # Panics
+    /// Panics if the number of inputs differs from the number of variables or if there are no
+    /// variables.
+    pub fn evaluate<V: SimulationValue>(&self, inputs: &[V]) -> V {
+        assert_eq!(
+            inputs.len(),
+            self.num_vars as usize,
+            "wrong number of inputs"
+        );
+        let first = inputs
+            .first()
+            .expect("functions should have at least one variable");
+        let zero = first.clone() ^ first.clone();
+        self.shannon(inputs, 0, inputs.len(), &zero)
+    }
+
+    fn shannon<V: SimulationValue>(&self, inputs: &[V], offset: usize, vars: usize, zero: &V) -> V {
+        let Some(var) = vars.checked_sub(1) else {
+            return if self.bit(offset) {
+                !zero.clone()
+            } else {
+                zero.clone()
+            };
+        };
+        let low = self.shannon(inputs, offset, var, zero);
+        let high = self.shannon(inputs, offset | 1 << var, var, zero);
+        (inputs[var].clone() & high) | (!inputs[var].clone() & low)
+    }
+
+    fn zip_with(mut self, rhs: Self, op: impl Fn(u64, u64) -> u64) -> Self {
+        assert_eq!(self.num_vars, rhs.num_vars, "truth tables differ in size");
+        for (a, b) in self.words.iter_mut().zip(rhs.words) {
+            *a = op(*a, b);
+        }
+        self
+    }
+}
+
+impl BitAnd for TruthTable {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        self.zip_with(rhs, |a, b| a & b)
+    }
+}
+
+impl BitOr for TruthTable {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.zip_with(rhs, |a, b| a | b)
+    }
+}
+
+impl BitXor for TruthTable {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self {
+        self.zip_with(rhs, |a, b| a ^ b)
+    }
+}
+
+impl Not for TruthTable {
+    type Output = Self;
+
+    fn not(mut self) -> Self {
+        for word in self.words.iter_mut() {
+            *word = !*word;
+        }
+        self.mask();
+        self
+    }
+}
+
+impl Display for TruthTable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+impl Debug for TruthTable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TruthTable({}, 0x{})", self.num_vars, self.to_hex())
+    }
+}